@@ -9,6 +9,21 @@ pub struct Config {
     pub jwt_refresh_expires_in: i64,  // in seconds
     pub server_host: String,
     pub server_port: u16,
+    pub trash_retention_days: i64,
+    pub trash_purge_interval_secs: u64,
+    pub auto_submit_interval_secs: u64,
+    pub trust_proxy_headers: bool,
+    /// Where attachments are stored: `local` or `s3`
+    pub storage_backend: String,
+    pub storage_local_path: String,
+    /// S3-compatible endpoint, e.g. a MinIO server; path-style addressing is used
+    pub s3_endpoint: Option<String>,
+    pub s3_region: String,
+    pub s3_bucket: Option<String>,
+    pub s3_access_key: Option<String>,
+    pub s3_secret_key: Option<String>,
+    pub attachment_max_bytes: usize,
+    pub attachment_url_ttl_secs: i64,
 }
 
 impl Config {
@@ -31,6 +46,34 @@ impl Config {
             server_port: std::env::var("SERVER_PORT")
                 .unwrap_or_else(|_| "3000".to_string())
                 .parse()?,
+            trash_retention_days: std::env::var("TRASH_RETENTION_DAYS")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse()?,
+            trash_purge_interval_secs: std::env::var("TRASH_PURGE_INTERVAL_SECS")
+                .unwrap_or_else(|_| "86400".to_string()) // daily
+                .parse()?,
+            auto_submit_interval_secs: std::env::var("AUTO_SUBMIT_INTERVAL_SECS")
+                .unwrap_or_else(|_| "60".to_string()) // 1 minute
+                .parse()?,
+            trust_proxy_headers: std::env::var("TRUST_PROXY_HEADERS")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()?,
+            storage_backend: std::env::var("STORAGE_BACKEND")
+                .unwrap_or_else(|_| "local".to_string()),
+            storage_local_path: std::env::var("STORAGE_LOCAL_PATH")
+                .unwrap_or_else(|_| "./uploads".to_string()),
+            s3_endpoint: std::env::var("S3_ENDPOINT").ok(),
+            s3_region: std::env::var("S3_REGION")
+                .unwrap_or_else(|_| "us-east-1".to_string()),
+            s3_bucket: std::env::var("S3_BUCKET").ok(),
+            s3_access_key: std::env::var("S3_ACCESS_KEY").ok(),
+            s3_secret_key: std::env::var("S3_SECRET_KEY").ok(),
+            attachment_max_bytes: std::env::var("ATTACHMENT_MAX_BYTES")
+                .unwrap_or_else(|_| "10485760".to_string()) // 10 MiB
+                .parse()?,
+            attachment_url_ttl_secs: std::env::var("ATTACHMENT_URL_TTL_SECS")
+                .unwrap_or_else(|_| "300".to_string()) // 5 minutes
+                .parse()?,
         };
 
         Ok(config)
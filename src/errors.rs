@@ -43,6 +43,23 @@ pub enum AppError {
     #[error("Bad request: {0}")]
     BadRequest(String),
 
+    /// The exam has prerequisites the user has not met yet
+    #[error("Exam is locked")]
+    ExamLocked(Vec<String>),
+
+    /// Wrong or missing access code, or a client address outside the allowlist
+    #[error("Exam access denied: {0}")]
+    ExamAccessDenied(String),
+
+    /// An upload over the configured size limit
+    #[error("Payload too large: {0}")]
+    PayloadTooLarge(String),
+
+    /// Reading or writing a blob in attachment storage failed
+    #[error("Storage error: {0}")]
+    Storage(String),
+
+    #[allow(dead_code)]
     #[error("Internal server error")]
     InternalServer,
 }
@@ -90,6 +107,21 @@ impl IntoResponse for AppError {
             AppError::NotFound(ref msg) => (StatusCode::NOT_FOUND, msg.clone(), None),
             AppError::Conflict(ref msg) => (StatusCode::CONFLICT, msg.clone(), None),
             AppError::BadRequest(ref msg) => (StatusCode::BAD_REQUEST, msg.clone(), None),
+            AppError::ExamLocked(ref reasons) => (
+                StatusCode::FORBIDDEN,
+                "Exam is locked".to_string(),
+                Some(json!({"code": "exam_locked", "reasons": reasons}))
+            ),
+            AppError::ExamAccessDenied(ref msg) => (
+                StatusCode::FORBIDDEN,
+                msg.clone(),
+                Some(json!({"code": "exam_access_denied"}))
+            ),
+            AppError::PayloadTooLarge(ref msg) => (StatusCode::PAYLOAD_TOO_LARGE, msg.clone(), None),
+            AppError::Storage(ref msg) => {
+                tracing::error!("Storage error: {}", msg);
+                (StatusCode::INTERNAL_SERVER_ERROR, "Internal server error".to_string(), None)
+            }
             AppError::InternalServer => (StatusCode::INTERNAL_SERVER_ERROR, "Internal server error".to_string(), None),
         };
 
@@ -98,13 +130,11 @@ impl IntoResponse for AppError {
             "status": status.as_u16()
         });
 
-        if let Some(details) = details {
-            if let serde_json::Value::Object(ref mut map) = body {
-                if let serde_json::Value::Object(details_map) = details {
-                    for (key, value) in details_map {
-                        map.insert(key, value);
-                    }
-                }
+        if let (Some(serde_json::Value::Object(details_map)), serde_json::Value::Object(map)) =
+            (details, &mut body)
+        {
+            for (key, value) in details_map {
+                map.insert(key, value);
             }
         }
 
@@ -0,0 +1,103 @@
+use axum::{
+    Extension, Json,
+    extract::{Path, Query, State},
+};
+use serde_json::{Value, json};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    AppState,
+    errors::{AppError, AppResult},
+    middleware::auth::AuthUser,
+    models::{
+        access::{ClearAccessCodeQuery, RotateAccessCodeRequest, UpdateIpAllowlistRequest},
+        user::UserRole,
+    },
+    require_role,
+    services::access::ExamAccessService,
+};
+
+pub struct ExamAccessHandler;
+
+impl ExamAccessHandler {
+    /// Get the access codes and IP allowlist of an exam (Teacher only)
+    pub async fn get_access(
+        State(state): State<AppState>,
+        Extension(auth_user): Extension<AuthUser>,
+        Path(exam_id): Path<Uuid>,
+    ) -> AppResult<Json<Value>> {
+        require_role!(auth_user, UserRole::Teacher)?;
+
+        let access_service = ExamAccessService::new(state.db.clone());
+        let access = access_service.get_access(exam_id).await?;
+
+        Ok(Json(json!({
+            "message": "Exam access settings retrieved successfully",
+            "data": access
+        })))
+    }
+
+    /// Generate a new access code for the exam or one of its assignments (Teacher only)
+    pub async fn rotate_access_code(
+        State(state): State<AppState>,
+        Extension(auth_user): Extension<AuthUser>,
+        Path(exam_id): Path<Uuid>,
+        Json(request): Json<RotateAccessCodeRequest>,
+    ) -> AppResult<Json<Value>> {
+        require_role!(auth_user, UserRole::Teacher)?;
+
+        let access_service = ExamAccessService::new(state.db.clone());
+        let access = access_service
+            .rotate_access_code(exam_id, request.class_id)
+            .await?;
+
+        Ok(Json(json!({
+            "message": "Access code rotated successfully",
+            "data": access
+        })))
+    }
+
+    /// Remove the access code of the exam or one of its assignments (Teacher only)
+    pub async fn clear_access_code(
+        State(state): State<AppState>,
+        Extension(auth_user): Extension<AuthUser>,
+        Path(exam_id): Path<Uuid>,
+        Query(query): Query<ClearAccessCodeQuery>,
+    ) -> AppResult<Json<Value>> {
+        require_role!(auth_user, UserRole::Teacher)?;
+
+        let access_service = ExamAccessService::new(state.db.clone());
+        let access = access_service
+            .clear_access_code(exam_id, query.class_id)
+            .await?;
+
+        Ok(Json(json!({
+            "message": "Access code removed successfully",
+            "data": access
+        })))
+    }
+
+    /// Replace the IP allowlist of an exam (Teacher only)
+    pub async fn update_ip_allowlist(
+        State(state): State<AppState>,
+        Extension(auth_user): Extension<AuthUser>,
+        Path(exam_id): Path<Uuid>,
+        Json(request): Json<UpdateIpAllowlistRequest>,
+    ) -> AppResult<Json<Value>> {
+        request
+            .validate()
+            .map_err(|e| AppError::Validation(format!("Validation error: {}", e)))?;
+        require_role!(auth_user, UserRole::Teacher)?;
+
+        let access_service = ExamAccessService::new(state.db.clone());
+        let access = access_service
+            .update_ip_allowlist(exam_id, request.ip_allowlist)
+            .await?;
+
+        Ok(Json(json!({
+            "message": "IP allowlist updated successfully",
+            "data": access
+        })))
+    }
+}
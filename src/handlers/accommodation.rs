@@ -0,0 +1,126 @@
+use axum::{
+    Extension, Json,
+    extract::{Path, State},
+};
+use serde_json::{Value, json};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    AppState,
+    errors::{AppError, AppResult},
+    middleware::auth::AuthUser,
+    models::{
+        accommodation::{CreateAccommodationRequest, UpdateAccommodationRequest},
+        user::UserRole,
+    },
+    require_role,
+    services::accommodation::AccommodationService,
+};
+
+pub struct AccommodationHandler;
+
+/// Teachers are limited to students of their own classes, admins are not
+fn class_teacher(auth_user: &AuthUser) -> Option<Uuid> {
+    (auth_user.role != UserRole::Admin).then_some(auth_user.id)
+}
+
+impl AccommodationHandler {
+    /// Grant an accommodation to a student of one of the caller's classes (Teacher only)
+    pub async fn create_accommodation(
+        State(state): State<AppState>,
+        Extension(auth_user): Extension<AuthUser>,
+        Json(request): Json<CreateAccommodationRequest>,
+    ) -> AppResult<Json<Value>> {
+        request
+            .validate()
+            .map_err(|e| AppError::Validation(format!("Validation error: {}", e)))?;
+        require_role!(auth_user, UserRole::Teacher)?;
+
+        let accommodation_service = AccommodationService::new(state.db.clone());
+        let accommodation = accommodation_service
+            .create_accommodation(request, auth_user.id, class_teacher(&auth_user))
+            .await?;
+
+        Ok(Json(json!({
+            "message": "Accommodation created successfully",
+            "data": accommodation
+        })))
+    }
+
+    /// Update an accommodation (Teacher only)
+    pub async fn update_accommodation(
+        State(state): State<AppState>,
+        Extension(auth_user): Extension<AuthUser>,
+        Path(accommodation_id): Path<Uuid>,
+        Json(request): Json<UpdateAccommodationRequest>,
+    ) -> AppResult<Json<Value>> {
+        request
+            .validate()
+            .map_err(|e| AppError::Validation(format!("Validation error: {}", e)))?;
+        require_role!(auth_user, UserRole::Teacher)?;
+
+        let accommodation_service = AccommodationService::new(state.db.clone());
+        let accommodation = accommodation_service
+            .update_accommodation(accommodation_id, request, class_teacher(&auth_user))
+            .await?;
+
+        Ok(Json(json!({
+            "message": "Accommodation updated successfully",
+            "data": accommodation
+        })))
+    }
+
+    /// Delete an accommodation (Teacher only)
+    pub async fn delete_accommodation(
+        State(state): State<AppState>,
+        Extension(auth_user): Extension<AuthUser>,
+        Path(accommodation_id): Path<Uuid>,
+    ) -> AppResult<Json<Value>> {
+        require_role!(auth_user, UserRole::Teacher)?;
+
+        let accommodation_service = AccommodationService::new(state.db.clone());
+        accommodation_service
+            .delete_accommodation(accommodation_id, class_teacher(&auth_user))
+            .await?;
+
+        Ok(Json(json!({
+            "message": "Accommodation deleted successfully"
+        })))
+    }
+
+    /// Get accommodations of a student (Teacher only)
+    pub async fn get_user_accommodations(
+        State(state): State<AppState>,
+        Extension(auth_user): Extension<AuthUser>,
+        Path(user_id): Path<Uuid>,
+    ) -> AppResult<Json<Value>> {
+        require_role!(auth_user, UserRole::Teacher)?;
+
+        let accommodation_service = AccommodationService::new(state.db.clone());
+        let accommodations = accommodation_service
+            .get_user_accommodations(user_id, class_teacher(&auth_user))
+            .await?;
+
+        Ok(Json(json!({
+            "message": "Accommodations retrieved successfully",
+            "data": accommodations
+        })))
+    }
+
+    /// Get the accommodations of the current user
+    pub async fn get_my_accommodations(
+        State(state): State<AppState>,
+        Extension(auth_user): Extension<AuthUser>,
+    ) -> AppResult<Json<Value>> {
+        let accommodation_service = AccommodationService::new(state.db.clone());
+        let accommodations = accommodation_service
+            .get_user_accommodations(auth_user.id, None)
+            .await?;
+
+        Ok(Json(json!({
+            "message": "Accommodations retrieved successfully",
+            "data": accommodations
+        })))
+    }
+}
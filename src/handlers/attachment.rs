@@ -0,0 +1,139 @@
+use axum::{
+    Extension, Json,
+    extract::{Multipart, Path, Query, State, multipart::MultipartError},
+    http::header,
+    response::{IntoResponse, Response},
+};
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    errors::{AppError, AppResult},
+    middleware::auth::AuthUser,
+    models::{attachment::SignedDownloadQuery, user::UserRole},
+    require_role,
+    services::attachment::AttachmentService,
+};
+
+pub struct AttachmentHandler;
+
+impl AttachmentHandler {
+    /// Upload an image or audio file from the multipart field `file` (Teacher only)
+    pub async fn upload_attachment(
+        State(state): State<AppState>,
+        Extension(auth_user): Extension<AuthUser>,
+        mut multipart: Multipart,
+    ) -> AppResult<Json<Value>> {
+        require_role!(auth_user, UserRole::Teacher)?;
+
+        let max_bytes = state.config.attachment_max_bytes;
+        let mut upload = None;
+        while let Some(mut field) = multipart.next_field().await.map_err(multipart_error)? {
+            if field.name() != Some("file") {
+                continue;
+            }
+            let file_name = field.file_name().unwrap_or("upload").to_string();
+            let content_type = field.content_type().map(str::to_string);
+
+            // Stop reading as soon as the file is over the limit
+            let mut bytes = Vec::new();
+            while let Some(chunk) = field.chunk().await.map_err(multipart_error)? {
+                if bytes.len() + chunk.len() > max_bytes {
+                    return Err(AppError::PayloadTooLarge(format!(
+                        "Attachments can be at most {} bytes",
+                        max_bytes
+                    )));
+                }
+                bytes.extend_from_slice(&chunk);
+            }
+            upload = Some((file_name, content_type, bytes));
+            break;
+        }
+        let Some((file_name, content_type, bytes)) = upload else {
+            return Err(AppError::BadRequest("Missing multipart field 'file'".to_string()));
+        };
+
+        let attachment_service = AttachmentService::new(state.db.clone(), state.blob_store.clone());
+        let attachment = attachment_service
+            .upload(auth_user.id, file_name, content_type.as_deref(), bytes, max_bytes)
+            .await?;
+
+        Ok(Json(json!({
+            "message": "Attachment uploaded successfully",
+            "data": attachment
+        })))
+    }
+
+    /// Get an attachment's details (Teacher only)
+    pub async fn get_attachment(
+        State(state): State<AppState>,
+        Extension(auth_user): Extension<AuthUser>,
+        Path(attachment_id): Path<Uuid>,
+    ) -> AppResult<Json<Value>> {
+        require_role!(auth_user, UserRole::Teacher)?;
+
+        let attachment_service = AttachmentService::new(state.db.clone(), state.blob_store.clone());
+        let attachment = attachment_service.get_attachment(attachment_id).await?;
+
+        Ok(Json(json!({
+            "message": "Attachment retrieved successfully",
+            "data": attachment
+        })))
+    }
+
+    /// Get a time-limited download URL. Students only get one for media of
+    /// a question in an attempt they have in progress.
+    pub async fn get_download_url(
+        State(state): State<AppState>,
+        Extension(auth_user): Extension<AuthUser>,
+        Path(attachment_id): Path<Uuid>,
+    ) -> AppResult<Json<Value>> {
+        let student_id = (auth_user.role == UserRole::Student).then_some(auth_user.id);
+
+        let attachment_service = AttachmentService::new(state.db.clone(), state.blob_store.clone());
+        let download = attachment_service
+            .get_download_url(attachment_id, student_id, state.config.attachment_url_ttl_secs)
+            .await?;
+
+        Ok(Json(json!({
+            "message": "Download URL created successfully",
+            "data": download
+        })))
+    }
+
+    /// Serve attachment content behind a signed link from `get_download_url`.
+    /// Needs no login; the signature is the credential.
+    pub async fn get_content(
+        State(state): State<AppState>,
+        Path(storage_key): Path<String>,
+        Query(query): Query<SignedDownloadQuery>,
+    ) -> AppResult<Response> {
+        if !state
+            .blob_store
+            .verify_download(&storage_key, query.expires, &query.signature)
+        {
+            return Err(AppError::Forbidden);
+        }
+
+        let attachment_service = AttachmentService::new(state.db.clone(), state.blob_store.clone());
+        let (content_type, bytes) = attachment_service.get_content(&storage_key).await?;
+
+        Ok((
+            [
+                (header::CONTENT_TYPE, content_type),
+                (header::CACHE_CONTROL, "private, no-store".to_string()),
+            ],
+            bytes,
+        )
+            .into_response())
+    }
+}
+
+fn multipart_error(e: MultipartError) -> AppError {
+    if e.status() == axum::http::StatusCode::PAYLOAD_TOO_LARGE {
+        AppError::PayloadTooLarge(e.body_text())
+    } else {
+        AppError::BadRequest(e.body_text())
+    }
+}
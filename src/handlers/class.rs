@@ -91,6 +91,7 @@ impl ClassHandlers {
 
     pub async fn delete_class(
         State(state): State<AppState>,
+        Extension(auth_user): Extension<AuthUser>,
         Path(class_id): Path<Uuid>,
     ) -> AppResult<Json<Value>> {
         let class_service = ClassService::new(state.db.clone());
@@ -99,7 +100,7 @@ impl ClassHandlers {
             .await
             .map_err(|_| AppError::NotFound("Class not found".to_string()))?;
 
-        class_service.delete_class(class_id).await?;
+        class_service.delete_class(class_id, auth_user.id).await?;
 
         Ok(Json(json!({
             "message":"class delete successfully"
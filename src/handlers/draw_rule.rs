@@ -0,0 +1,74 @@
+use axum::{
+    Extension, Json,
+    extract::{Path, State},
+};
+use serde_json::{Value, json};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    AppState,
+    errors::{AppError, AppResult},
+    middleware::auth::AuthUser,
+    models::{draw_rule::CreateDrawRuleRequest, user::UserRole},
+    require_role,
+    services::draw_rule::DrawRuleService,
+};
+
+pub struct DrawRuleHandler;
+
+impl DrawRuleHandler {
+    /// Add a draw rule to an exam (Teacher only)
+    pub async fn create_draw_rule(
+        State(state): State<AppState>,
+        Extension(auth_user): Extension<AuthUser>,
+        Path(exam_id): Path<Uuid>,
+        Json(request): Json<CreateDrawRuleRequest>,
+    ) -> AppResult<Json<Value>> {
+        request
+            .validate()
+            .map_err(|e| AppError::Validation(format!("Validation error: {}", e)))?;
+        require_role!(auth_user, UserRole::Teacher)?;
+
+        let draw_rule_service = DrawRuleService::new(state.db.clone());
+        let draw_rule = draw_rule_service.create_draw_rule(exam_id, request).await?;
+
+        Ok(Json(json!({
+            "message": "Draw rule created successfully",
+            "data": draw_rule
+        })))
+    }
+
+    /// Get the draw rules of an exam (Teacher only)
+    pub async fn get_draw_rules(
+        State(state): State<AppState>,
+        Extension(auth_user): Extension<AuthUser>,
+        Path(exam_id): Path<Uuid>,
+    ) -> AppResult<Json<Value>> {
+        require_role!(auth_user, UserRole::Teacher)?;
+
+        let draw_rule_service = DrawRuleService::new(state.db.clone());
+        let draw_rules = draw_rule_service.get_draw_rules(exam_id).await?;
+
+        Ok(Json(json!({
+            "message": "Draw rules retrieved successfully",
+            "data": draw_rules
+        })))
+    }
+
+    /// Remove a draw rule (Teacher only)
+    pub async fn delete_draw_rule(
+        State(state): State<AppState>,
+        Extension(auth_user): Extension<AuthUser>,
+        Path(rule_id): Path<Uuid>,
+    ) -> AppResult<Json<Value>> {
+        require_role!(auth_user, UserRole::Teacher)?;
+
+        let draw_rule_service = DrawRuleService::new(state.db.clone());
+        draw_rule_service.delete_draw_rule(rule_id).await?;
+
+        Ok(Json(json!({
+            "message": "Draw rule deleted successfully"
+        })))
+    }
+}
@@ -10,7 +10,7 @@ use validator::Validate;
 
 pub struct ExamHandlers;
 use crate::models::exams::{
-    CreateExamRequest, UpdateExamRequest, CreateExamAssignmentRequest, DeleteExamAssignmentRequest,
+    CloneExamRequest, CreateExamRequest, ExamFilter, UpdateExamRequest, CreateExamAssignmentRequest, DeleteExamAssignmentRequest,
 };
 use crate::require_role;
 use crate::utils::pagination::Pagination;
@@ -18,10 +18,15 @@ use crate::utils::pagination::Pagination;
 impl ExamHandlers {
     pub async fn get_exams(
         State(state): State<AppState>,
+        Extension(auth_user): Extension<AuthUser>,
         Query(pagination): Query<Pagination>,
+        Query(filter): Query<ExamFilter>,
     ) -> AppResult<Json<Value>> {
+        // Students only see the exams assigned to their classes
+        let student_id = (auth_user.role == UserRole::Student).then_some(auth_user.id);
+
         let exam_service = ExamService::new(state.db.clone());
-        let exams = exam_service.get_exams(&pagination).await?;
+        let (exams, total) = exam_service.get_exams(&pagination, &filter, student_id).await?;
 
         Ok(Json(json!({
             "message": "Exams retrieved successfully",
@@ -30,6 +35,7 @@ impl ExamHandlers {
                 "limit": pagination.limit_or_default(20),
                 "skip": pagination.skip_or_default(),
                 "page": pagination.page_or_default(),
+                "total": total,
             }
         })))
     }
@@ -106,13 +112,67 @@ impl ExamHandlers {
             .await
             .map_err(|_| AppError::NotFound("Exam not found".to_string()))?;
 
-        exam_service.delete_exam(exam_id).await?;
+        exam_service.delete_exam(exam_id, auth_user.id).await?;
 
         Ok(Json(json!({
             "message": "Exam deleted successfully"
         })))
     }
 
+    pub async fn clone_exam(
+        State(state): State<AppState>,
+        Extension(auth_user): Extension<AuthUser>,
+        Path(exam_id): Path<Uuid>,
+        Json(request): Json<CloneExamRequest>,
+    ) -> AppResult<Json<Value>> {
+        request
+            .validate()
+            .map_err(|e| AppError::Validation(format!("Validation error: {}", e)))?;
+        require_role!(auth_user, UserRole::Teacher)?;
+
+        let exam_service = ExamService::new(state.db.clone());
+        let cloned = exam_service.clone_exam(exam_id, request, auth_user.id).await?;
+
+        Ok(Json(json!({
+            "message": "Exam cloned successfully",
+            "data": cloned
+        })))
+    }
+
+    pub async fn get_assigned_exams(
+        State(state): State<AppState>,
+        Extension(auth_user): Extension<AuthUser>,
+    ) -> AppResult<Json<Value>> {
+        let exam_service = ExamService::new(state.db.clone());
+        let exams = exam_service.get_assigned_exams(auth_user.id).await?;
+
+        Ok(Json(json!({
+            "message": "Assigned exams retrieved successfully",
+            "data": exams
+        })))
+    }
+
+    pub async fn get_templates(
+        State(state): State<AppState>,
+        Extension(auth_user): Extension<AuthUser>,
+        Query(pagination): Query<Pagination>,
+    ) -> AppResult<Json<Value>> {
+        require_role!(auth_user, UserRole::Teacher)?;
+
+        let exam_service = ExamService::new(state.db.clone());
+        let templates = exam_service.get_templates(&pagination).await?;
+
+        Ok(Json(json!({
+            "message": "Exam templates retrieved successfully",
+            "data": templates,
+            "pagination": {
+                "limit": pagination.limit_or_default(20),
+                "skip": pagination.skip_or_default(),
+                "page": pagination.page_or_default(),
+            }
+        })))
+    }
+
     pub async fn assign_exam_to_class(
         State(state): State<AppState>,
         Extension(auth_user): Extension<AuthUser>,
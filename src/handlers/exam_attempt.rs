@@ -1,8 +1,10 @@
 use axum::{
     Extension, Json,
-    extract::{Path, State},
+    extract::{ConnectInfo, Path, State},
+    http::HeaderMap,
 };
 use serde_json::{Value, json};
+use std::net::SocketAddr;
 use uuid::Uuid;
 
 use crate::{
@@ -10,14 +12,12 @@ use crate::{
     errors::AppResult,
     middleware::auth::AuthUser,
     models::{
-        attempt::{
-            ExamAttemptResponse, StartExamAttemptRequest,
-            SubmitExamAttemptRequest,
-        },
+        attempt::{SaveAnswerRequest, StartExamAttemptRequest, SubmitExamAttemptRequest},
         user::UserRole,
     },
     require_role,
     services::exam_attempt::ExamAttemptService,
+    utils::ip::client_ip,
 };
 
 pub struct ExamAttemptHandler;
@@ -26,31 +26,58 @@ impl ExamAttemptHandler {
     pub async fn start_exam_attempt(
         State(state): State<AppState>,
         Extension(auth_user): Extension<AuthUser>,
+        ConnectInfo(peer): ConnectInfo<SocketAddr>,
+        headers: HeaderMap,
         Json(request): Json<StartExamAttemptRequest>,
     ) -> AppResult<Json<Value>> {
+        let ip = client_ip(&headers, peer, state.config.trust_proxy_headers);
         let exam_attempt_service = ExamAttemptService::new(state.db.clone());
         let exam_attempt = exam_attempt_service
-            .start_exam_attempt(request, auth_user.id)
+            .start_exam_attempt(request, auth_user.id, ip)
             .await?;
         Ok(Json(json!({
             "message": "Exam attempt started successfully",
-            "data": ExamAttemptResponse::from(exam_attempt)
+            "data": exam_attempt
         })))
     }
 
     pub async fn submit_exam_attempt(
         State(state): State<AppState>,
         Extension(auth_user): Extension<AuthUser>,
+        ConnectInfo(peer): ConnectInfo<SocketAddr>,
+        headers: HeaderMap,
         Json(request): Json<SubmitExamAttemptRequest>,
     ) -> AppResult<Json<Value>> {
+        let ip = client_ip(&headers, peer, state.config.trust_proxy_headers);
         let exam_attempt_service = ExamAttemptService::new(state.db.clone());
         let exam_attempt = exam_attempt_service
-            .submit_exam_attempt(request, auth_user.id)
+            .submit_exam_attempt(request, auth_user.id, ip)
             .await?;
 
         Ok(Json(json!({
               "message": "Exam attempt submitted successfully",
-              "data": ExamAttemptResponse::from(exam_attempt)
+              "data": exam_attempt
+        })))
+    }
+
+    /// Save the answer to one question while the attempt is in progress
+    pub async fn save_answer(
+        State(state): State<AppState>,
+        Extension(auth_user): Extension<AuthUser>,
+        ConnectInfo(peer): ConnectInfo<SocketAddr>,
+        headers: HeaderMap,
+        Path((attempt_id, question_id)): Path<(Uuid, Uuid)>,
+        Json(request): Json<SaveAnswerRequest>,
+    ) -> AppResult<Json<Value>> {
+        let ip = client_ip(&headers, peer, state.config.trust_proxy_headers);
+        let exam_attempt_service = ExamAttemptService::new(state.db.clone());
+        let draft = exam_attempt_service
+            .save_answer(attempt_id, question_id, auth_user.id, request, ip)
+            .await?;
+
+        Ok(Json(json!({
+            "message": "Answer saved successfully",
+            "data": draft
         })))
     }
 
@@ -72,9 +99,14 @@ impl ExamAttemptHandler {
         Extension(auth_user): Extension<AuthUser>,
         Path(attempt_id): Path<Uuid>,
     ) -> AppResult<Json<Value>> {
+        // Teachers can review any attempt, students only their own
+        let owner = match require_role!(auth_user, UserRole::Teacher) {
+            Ok(()) => None,
+            Err(_) => Some(auth_user.id),
+        };
         let exam_attempt_service = ExamAttemptService::new(state.db.clone());
         let attempt_with_answers = exam_attempt_service
-            .get_attempt_with_answers(attempt_id, auth_user.id)
+            .get_attempt_with_answers(attempt_id, owner)
             .await?;
 
         Ok(Json(json!({
@@ -113,6 +145,54 @@ impl ExamAttemptHandler {
         })))
     }
 
+    pub async fn start_section(
+        State(state): State<AppState>,
+        Extension(auth_user): Extension<AuthUser>,
+        Path((attempt_id, section_id)): Path<(Uuid, Uuid)>,
+    ) -> AppResult<Json<Value>> {
+        let exam_attempt_service = ExamAttemptService::new(state.db.clone());
+        let attempt_section = exam_attempt_service
+            .start_section(attempt_id, section_id, auth_user.id)
+            .await?;
+
+        Ok(Json(json!({
+            "message": "Section started successfully",
+            "data": attempt_section
+        })))
+    }
+
+    pub async fn finish_section(
+        State(state): State<AppState>,
+        Extension(auth_user): Extension<AuthUser>,
+        Path((attempt_id, section_id)): Path<(Uuid, Uuid)>,
+    ) -> AppResult<Json<Value>> {
+        let exam_attempt_service = ExamAttemptService::new(state.db.clone());
+        let attempt_section = exam_attempt_service
+            .finish_section(attempt_id, section_id, auth_user.id)
+            .await?;
+
+        Ok(Json(json!({
+            "message": "Section finished successfully",
+            "data": attempt_section
+        })))
+    }
+
+    pub async fn get_attempt_sections(
+        State(state): State<AppState>,
+        Extension(auth_user): Extension<AuthUser>,
+        Path(attempt_id): Path<Uuid>,
+    ) -> AppResult<Json<Value>> {
+        let exam_attempt_service = ExamAttemptService::new(state.db.clone());
+        let sections = exam_attempt_service
+            .get_attempt_sections(attempt_id, auth_user.id)
+            .await?;
+
+        Ok(Json(json!({
+            "message": "Attempt sections retrieved successfully",
+            "data": sections
+        })))
+    }
+
 
 
 }
@@ -0,0 +1,114 @@
+use axum::{
+    Extension, Json,
+    extract::{Path, State},
+};
+use serde_json::{Value, json};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    AppState,
+    errors::{AppError, AppResult},
+    middleware::auth::AuthUser,
+    models::{grade_scale::GradeScaleRequest, user::UserRole},
+    require_role,
+    services::grade_scale::GradeScaleService,
+};
+
+pub struct GradeScaleHandler;
+
+impl GradeScaleHandler {
+    /// Create a grade scale (Teacher only)
+    pub async fn create_grade_scale(
+        State(state): State<AppState>,
+        Extension(auth_user): Extension<AuthUser>,
+        Json(request): Json<GradeScaleRequest>,
+    ) -> AppResult<Json<Value>> {
+        request
+            .validate()
+            .map_err(|e| AppError::Validation(format!("Validation error: {}", e)))?;
+        require_role!(auth_user, UserRole::Teacher)?;
+
+        let grade_scale_service = GradeScaleService::new(state.db.clone());
+        let grade_scale = grade_scale_service
+            .create_grade_scale(request, auth_user.id)
+            .await?;
+
+        Ok(Json(json!({
+            "message": "Grade scale created successfully",
+            "data": grade_scale
+        })))
+    }
+
+    /// List all grade scales (Teacher only)
+    pub async fn get_grade_scales(
+        State(state): State<AppState>,
+        Extension(auth_user): Extension<AuthUser>,
+    ) -> AppResult<Json<Value>> {
+        require_role!(auth_user, UserRole::Teacher)?;
+
+        let grade_scale_service = GradeScaleService::new(state.db.clone());
+        let grade_scales = grade_scale_service.get_grade_scales().await?;
+
+        Ok(Json(json!({
+            "message": "Grade scales retrieved successfully",
+            "data": grade_scales
+        })))
+    }
+
+    /// Get a grade scale with its bands (Teacher only)
+    pub async fn get_grade_scale_by_id(
+        State(state): State<AppState>,
+        Extension(auth_user): Extension<AuthUser>,
+        Path(scale_id): Path<Uuid>,
+    ) -> AppResult<Json<Value>> {
+        require_role!(auth_user, UserRole::Teacher)?;
+
+        let grade_scale_service = GradeScaleService::new(state.db.clone());
+        let grade_scale = grade_scale_service.get_grade_scale_by_id(scale_id).await?;
+
+        Ok(Json(json!({
+            "message": "Grade scale retrieved successfully",
+            "data": grade_scale
+        })))
+    }
+
+    /// Replace the name and bands of a grade scale (Teacher only)
+    pub async fn update_grade_scale(
+        State(state): State<AppState>,
+        Extension(auth_user): Extension<AuthUser>,
+        Path(scale_id): Path<Uuid>,
+        Json(request): Json<GradeScaleRequest>,
+    ) -> AppResult<Json<Value>> {
+        request
+            .validate()
+            .map_err(|e| AppError::Validation(format!("Validation error: {}", e)))?;
+        require_role!(auth_user, UserRole::Teacher)?;
+
+        let grade_scale_service = GradeScaleService::new(state.db.clone());
+        let grade_scale = grade_scale_service
+            .update_grade_scale(scale_id, request)
+            .await?;
+
+        Ok(Json(json!({
+            "message": "Grade scale updated successfully",
+            "data": grade_scale
+        })))
+    }
+
+    /// Delete a grade scale (Teacher only)
+    pub async fn delete_grade_scale(
+        State(state): State<AppState>,
+        Extension(auth_user): Extension<AuthUser>,
+        Path(scale_id): Path<Uuid>,
+    ) -> AppResult<Json<Value>> {
+        require_role!(auth_user, UserRole::Teacher)?;
+
+        let grade_scale_service = GradeScaleService::new(state.db.clone());
+        grade_scale_service.delete_grade_scale(scale_id).await?;
+
+        Ok(Json(json!({
+            "message": "Grade scale deleted successfully"
+        })))
+    }
+}
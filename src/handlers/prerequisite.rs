@@ -0,0 +1,82 @@
+use axum::{
+    Extension, Json,
+    extract::{Path, State},
+};
+use serde_json::{Value, json};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    AppState,
+    errors::{AppError, AppResult},
+    middleware::auth::AuthUser,
+    models::{
+        prerequisite::{CreatePrerequisiteRequest, PrerequisiteResponse},
+        user::UserRole,
+    },
+    require_role,
+    services::prerequisite::PrerequisiteService,
+};
+
+pub struct PrerequisiteHandler;
+
+impl PrerequisiteHandler {
+    /// Add a prerequisite to an exam (Teacher only)
+    pub async fn create_prerequisite(
+        State(state): State<AppState>,
+        Extension(auth_user): Extension<AuthUser>,
+        Path(exam_id): Path<Uuid>,
+        Json(request): Json<CreatePrerequisiteRequest>,
+    ) -> AppResult<Json<Value>> {
+        request
+            .validate()
+            .map_err(|e| AppError::Validation(format!("Validation error: {}", e)))?;
+        require_role!(auth_user, UserRole::Teacher)?;
+
+        let prerequisite_service = PrerequisiteService::new(state.db.clone());
+        let prerequisite = prerequisite_service
+            .create_prerequisite(exam_id, request)
+            .await?;
+
+        Ok(Json(json!({
+            "message": "Prerequisite created successfully",
+            "data": prerequisite
+        })))
+    }
+
+    /// Get the prerequisites of an exam
+    pub async fn get_prerequisites(
+        State(state): State<AppState>,
+        Extension(_auth_user): Extension<AuthUser>,
+        Path(exam_id): Path<Uuid>,
+    ) -> AppResult<Json<Value>> {
+        let prerequisite_service = PrerequisiteService::new(state.db.clone());
+        let prerequisites: Vec<PrerequisiteResponse> = prerequisite_service
+            .get_prerequisites(exam_id)
+            .await?
+            .into_iter()
+            .map(Into::into)
+            .collect();
+
+        Ok(Json(json!({
+            "message": "Prerequisites retrieved successfully",
+            "data": prerequisites
+        })))
+    }
+
+    /// Remove a prerequisite (Teacher only)
+    pub async fn delete_prerequisite(
+        State(state): State<AppState>,
+        Extension(auth_user): Extension<AuthUser>,
+        Path(prerequisite_id): Path<Uuid>,
+    ) -> AppResult<Json<Value>> {
+        require_role!(auth_user, UserRole::Teacher)?;
+
+        let prerequisite_service = PrerequisiteService::new(state.db.clone());
+        prerequisite_service.delete_prerequisite(prerequisite_id).await?;
+
+        Ok(Json(json!({
+            "message": "Prerequisite deleted successfully"
+        })))
+    }
+}
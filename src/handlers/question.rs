@@ -1,6 +1,6 @@
 use axum::{
     Extension, Json,
-    extract::{Path, State},
+    extract::{Path, Query, State},
 };
 use serde_json::{Value, json};
 use uuid::Uuid;
@@ -14,6 +14,7 @@ use crate::{
         question::{
             BulkCreateQuestionsRequest, CreateQuestionRequest, UpdateQuestionRequest,
         },
+        revision::RevisionDiffQuery,
         user::UserRole,
     },
     require_role,
@@ -65,11 +66,13 @@ impl QuestionHandler {
     /// Get questions for students (without correct answers)
     pub async fn get_questions_for_student(
         State(state): State<AppState>,
-        Extension(_auth_user): Extension<AuthUser>,
+        Extension(auth_user): Extension<AuthUser>,
         Path(exam_id): Path<Uuid>,
     ) -> AppResult<Json<Value>> {
         let question_service = QuestionService::new(state.db.clone());
-        let questions = question_service.get_questions_for_student(exam_id).await?;
+        let questions = question_service
+            .get_questions_for_student(exam_id, auth_user.id)
+            .await?;
 
         Ok(Json(json!({
             "message": "Questions retrieved successfully",
@@ -108,11 +111,68 @@ impl QuestionHandler {
         require_role!(auth_user, UserRole::Teacher)?;
 
         let question_service = QuestionService::new(state.db.clone());
-        let question = question_service.update_question(question_id, request).await?;
+        let (question, regraded_attempts) = question_service
+            .update_question(question_id, request, auth_user.id)
+            .await?;
 
         Ok(Json(json!({
             "message": "Question updated successfully",
-            "data": question
+            "data": question,
+            "regraded_attempts": regraded_attempts
+        })))
+    }
+
+    /// List the revisions of a question (Teacher only)
+    pub async fn get_revisions(
+        State(state): State<AppState>,
+        Extension(auth_user): Extension<AuthUser>,
+        Path(question_id): Path<Uuid>,
+    ) -> AppResult<Json<Value>> {
+        require_role!(auth_user, UserRole::Teacher)?;
+
+        let question_service = QuestionService::new(state.db.clone());
+        let revisions = question_service.get_revisions(question_id).await?;
+
+        Ok(Json(json!({
+            "message": "Question revisions retrieved successfully",
+            "data": revisions
+        })))
+    }
+
+    /// Get a question as it was at one revision (Teacher only)
+    pub async fn get_revision(
+        State(state): State<AppState>,
+        Extension(auth_user): Extension<AuthUser>,
+        Path((question_id, revision)): Path<(Uuid, i32)>,
+    ) -> AppResult<Json<Value>> {
+        require_role!(auth_user, UserRole::Teacher)?;
+
+        let question_service = QuestionService::new(state.db.clone());
+        let revision = question_service.get_revision(question_id, revision).await?;
+
+        Ok(Json(json!({
+            "message": "Question revision retrieved successfully",
+            "data": revision
+        })))
+    }
+
+    /// Compare two revisions of a question (Teacher only)
+    pub async fn diff_revisions(
+        State(state): State<AppState>,
+        Extension(auth_user): Extension<AuthUser>,
+        Path(question_id): Path<Uuid>,
+        Query(query): Query<RevisionDiffQuery>,
+    ) -> AppResult<Json<Value>> {
+        require_role!(auth_user, UserRole::Teacher)?;
+
+        let question_service = QuestionService::new(state.db.clone());
+        let diff = question_service
+            .diff_revisions(question_id, query.from, query.to)
+            .await?;
+
+        Ok(Json(json!({
+            "message": "Question revision diff retrieved successfully",
+            "data": diff
         })))
     }
 
@@ -125,7 +185,7 @@ impl QuestionHandler {
         require_role!(auth_user, UserRole::Teacher)?;
 
         let question_service = QuestionService::new(state.db.clone());
-        question_service.delete_question(question_id).await?;
+        question_service.delete_question(question_id, auth_user.id).await?;
 
         Ok(Json(json!({
             "message": "Question deleted successfully"
@@ -176,4 +236,22 @@ impl QuestionHandler {
             }
         })))
     }
+
+    /// Multiple choice and true/false questions whose choices and
+    /// correct_answer disagree (Teacher only)
+    pub async fn get_consistency_report(
+        State(state): State<AppState>,
+        Extension(auth_user): Extension<AuthUser>,
+    ) -> AppResult<Json<Value>> {
+        require_role!(auth_user, UserRole::Teacher)?;
+
+        let question_service = QuestionService::new(state.db.clone());
+        let questions = question_service.get_inconsistent_questions().await?;
+
+        Ok(Json(json!({
+            "message": "Consistency report retrieved successfully",
+            "data": questions,
+            "count": questions.len()
+        })))
+    }
 }
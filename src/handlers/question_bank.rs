@@ -0,0 +1,157 @@
+use axum::{
+    Extension, Json,
+    extract::{Path, Query, State},
+};
+use serde_json::{Value, json};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    AppState,
+    errors::{AppError, AppResult},
+    middleware::auth::AuthUser,
+    models::{
+        question::CreateQuestionRequest,
+        question_bank::{BankQuestionFilter, LinkBankQuestionRequest, UpdateBankLinkRequest},
+        user::UserRole,
+    },
+    require_role,
+    services::question_bank::QuestionBankService,
+    utils::pagination::Pagination,
+};
+
+pub struct QuestionBankHandler;
+
+/// Teachers work in their own bank, admins in every bank
+fn bank_owner(auth_user: &AuthUser) -> Option<Uuid> {
+    (auth_user.role != UserRole::Admin).then_some(auth_user.id)
+}
+
+impl QuestionBankHandler {
+    /// Add a question to the caller's question bank (Teacher only)
+    pub async fn create_bank_question(
+        State(state): State<AppState>,
+        Extension(auth_user): Extension<AuthUser>,
+        Json(request): Json<CreateQuestionRequest>,
+    ) -> AppResult<Json<Value>> {
+        request
+            .validate()
+            .map_err(|e| AppError::Validation(format!("Validation error: {}", e)))?;
+        require_role!(auth_user, UserRole::Teacher)?;
+
+        let question_bank_service = QuestionBankService::new(state.db.clone());
+        let question = question_bank_service.create_question(auth_user.id, request).await?;
+
+        Ok(Json(json!({
+            "message": "Question added to bank successfully",
+            "data": question
+        })))
+    }
+
+    /// Search the question bank by tag, topic, difficulty and text (Teacher only)
+    pub async fn search_bank_questions(
+        State(state): State<AppState>,
+        Extension(auth_user): Extension<AuthUser>,
+        Query(pagination): Query<Pagination>,
+        Query(filter): Query<BankQuestionFilter>,
+    ) -> AppResult<Json<Value>> {
+        require_role!(auth_user, UserRole::Teacher)?;
+
+        let question_bank_service = QuestionBankService::new(state.db.clone());
+        let (questions, total) = question_bank_service
+            .search(bank_owner(&auth_user), &filter, &pagination)
+            .await?;
+
+        Ok(Json(json!({
+            "message": "Bank questions retrieved successfully",
+            "data": questions,
+            "pagination": {
+                "limit": pagination.limit_or_default(20),
+                "skip": pagination.skip_or_default(),
+                "page": pagination.page_or_default(),
+                "total": total,
+            }
+        })))
+    }
+
+    /// Exams a bank question is used in (Teacher only)
+    pub async fn get_question_usage(
+        State(state): State<AppState>,
+        Extension(auth_user): Extension<AuthUser>,
+        Path(question_id): Path<Uuid>,
+    ) -> AppResult<Json<Value>> {
+        require_role!(auth_user, UserRole::Teacher)?;
+
+        let question_bank_service = QuestionBankService::new(state.db.clone());
+        let usage = question_bank_service
+            .get_usage(question_id, bank_owner(&auth_user))
+            .await?;
+
+        Ok(Json(json!({
+            "message": "Question usage retrieved successfully",
+            "data": usage
+        })))
+    }
+
+    /// Link a bank question into an exam (Teacher only)
+    pub async fn link_bank_question(
+        State(state): State<AppState>,
+        Extension(auth_user): Extension<AuthUser>,
+        Path(exam_id): Path<Uuid>,
+        Json(request): Json<LinkBankQuestionRequest>,
+    ) -> AppResult<Json<Value>> {
+        request
+            .validate()
+            .map_err(|e| AppError::Validation(format!("Validation error: {}", e)))?;
+        require_role!(auth_user, UserRole::Teacher)?;
+
+        let question_bank_service = QuestionBankService::new(state.db.clone());
+        let link = question_bank_service
+            .link_question(exam_id, request, bank_owner(&auth_user))
+            .await?;
+
+        Ok(Json(json!({
+            "message": "Question linked successfully",
+            "data": link
+        })))
+    }
+
+    /// Change the section or score override of a linked question (Teacher only)
+    pub async fn update_bank_link(
+        State(state): State<AppState>,
+        Extension(auth_user): Extension<AuthUser>,
+        Path((exam_id, question_id)): Path<(Uuid, Uuid)>,
+        Json(request): Json<UpdateBankLinkRequest>,
+    ) -> AppResult<Json<Value>> {
+        request
+            .validate()
+            .map_err(|e| AppError::Validation(format!("Validation error: {}", e)))?;
+        require_role!(auth_user, UserRole::Teacher)?;
+
+        let question_bank_service = QuestionBankService::new(state.db.clone());
+        let link = question_bank_service
+            .update_link(exam_id, question_id, request)
+            .await?;
+
+        Ok(Json(json!({
+            "message": "Question link updated successfully",
+            "data": link
+        })))
+    }
+
+    /// Remove a bank question from an exam; the question stays in the bank (Teacher only)
+    pub async fn unlink_bank_question(
+        State(state): State<AppState>,
+        Extension(auth_user): Extension<AuthUser>,
+        Path((exam_id, question_id)): Path<(Uuid, Uuid)>,
+    ) -> AppResult<Json<Value>> {
+        require_role!(auth_user, UserRole::Teacher)?;
+
+        let question_bank_service = QuestionBankService::new(state.db.clone());
+        question_bank_service.unlink_question(exam_id, question_id).await?;
+
+        Ok(Json(json!({
+            "message": "Question unlinked successfully"
+        })))
+    }
+}
@@ -0,0 +1,101 @@
+use axum::{
+    Extension, Json,
+    extract::{Path, State},
+};
+use serde_json::{Value, json};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    AppState,
+    errors::{AppError, AppResult},
+    middleware::auth::AuthUser,
+    models::{
+        section::{CreateSectionRequest, SectionResponse, UpdateSectionRequest},
+        user::UserRole,
+    },
+    require_role,
+    services::section::SectionService,
+};
+
+pub struct SectionHandler;
+
+impl SectionHandler {
+    /// Add a section to an exam (Teacher only)
+    pub async fn create_section(
+        State(state): State<AppState>,
+        Extension(auth_user): Extension<AuthUser>,
+        Path(exam_id): Path<Uuid>,
+        Json(request): Json<CreateSectionRequest>,
+    ) -> AppResult<Json<Value>> {
+        request
+            .validate()
+            .map_err(|e| AppError::Validation(format!("Validation error: {}", e)))?;
+        require_role!(auth_user, UserRole::Teacher)?;
+
+        let section_service = SectionService::new(state.db.clone());
+        let section = section_service.create_section(exam_id, request).await?;
+
+        Ok(Json(json!({
+            "message": "Section created successfully",
+            "data": section
+        })))
+    }
+
+    /// Get the sections of an exam in order
+    pub async fn get_sections(
+        State(state): State<AppState>,
+        Extension(_auth_user): Extension<AuthUser>,
+        Path(exam_id): Path<Uuid>,
+    ) -> AppResult<Json<Value>> {
+        let section_service = SectionService::new(state.db.clone());
+        let sections: Vec<SectionResponse> = section_service
+            .get_sections(exam_id)
+            .await?
+            .into_iter()
+            .map(Into::into)
+            .collect();
+
+        Ok(Json(json!({
+            "message": "Sections retrieved successfully",
+            "data": sections
+        })))
+    }
+
+    /// Update a section (Teacher only)
+    pub async fn update_section(
+        State(state): State<AppState>,
+        Extension(auth_user): Extension<AuthUser>,
+        Path(section_id): Path<Uuid>,
+        Json(request): Json<UpdateSectionRequest>,
+    ) -> AppResult<Json<Value>> {
+        request
+            .validate()
+            .map_err(|e| AppError::Validation(format!("Validation error: {}", e)))?;
+        require_role!(auth_user, UserRole::Teacher)?;
+
+        let section_service = SectionService::new(state.db.clone());
+        let section = section_service.update_section(section_id, request).await?;
+
+        Ok(Json(json!({
+            "message": "Section updated successfully",
+            "data": section
+        })))
+    }
+
+    /// Delete a section (Teacher only)
+    pub async fn delete_section(
+        State(state): State<AppState>,
+        Extension(auth_user): Extension<AuthUser>,
+        Path(section_id): Path<Uuid>,
+    ) -> AppResult<Json<Value>> {
+        require_role!(auth_user, UserRole::Teacher)?;
+
+        let section_service = SectionService::new(state.db.clone());
+        section_service.delete_section(section_id).await?;
+
+        Ok(Json(json!({
+            "message": "Section deleted successfully"
+        })))
+    }
+}
@@ -0,0 +1,81 @@
+use axum::{
+    Extension, Json,
+    extract::{Path, Query, State},
+};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+use crate::{
+    AppState,
+    errors::AppResult,
+    middleware::auth::AuthUser,
+    models::{trash::TrashItemType, user::UserRole},
+    require_role,
+    services::trash::TrashService,
+};
+
+#[derive(Debug, Deserialize)]
+pub struct TrashQuery {
+    pub item_type: Option<TrashItemType>,
+}
+
+pub struct TrashHandler;
+
+/// Teachers see their own trash, admins everyone's
+fn trash_owner(auth_user: &AuthUser) -> Option<Uuid> {
+    (auth_user.role != UserRole::Admin).then_some(auth_user.id)
+}
+
+impl TrashHandler {
+    /// List the caller's soft-deleted exams, questions and classes (Teacher only)
+    pub async fn get_trash(
+        State(state): State<AppState>,
+        Extension(auth_user): Extension<AuthUser>,
+        Query(query): Query<TrashQuery>,
+    ) -> AppResult<Json<Value>> {
+        require_role!(auth_user, UserRole::Teacher)?;
+
+        let trash_service = TrashService::new(state.db.clone());
+        let items = trash_service.list_trash(query.item_type, trash_owner(&auth_user)).await?;
+
+        Ok(Json(json!({
+            "message": "Trash retrieved successfully",
+            "data": items
+        })))
+    }
+
+    /// Restore one of the caller's soft-deleted items (Teacher only)
+    pub async fn restore_item(
+        State(state): State<AppState>,
+        Extension(auth_user): Extension<AuthUser>,
+        Path((item_type, id)): Path<(TrashItemType, Uuid)>,
+    ) -> AppResult<Json<Value>> {
+        require_role!(auth_user, UserRole::Teacher)?;
+
+        let trash_service = TrashService::new(state.db.clone());
+        trash_service.restore(item_type, id, trash_owner(&auth_user)).await?;
+
+        Ok(Json(json!({
+            "message": "Item restored successfully"
+        })))
+    }
+
+    /// Permanently delete items past the retention period (Admin only)
+    pub async fn purge_trash(
+        State(state): State<AppState>,
+        Extension(auth_user): Extension<AuthUser>,
+    ) -> AppResult<Json<Value>> {
+        require_role!(auth_user, UserRole::Admin)?;
+
+        let trash_service = TrashService::new(state.db.clone());
+        let summary = trash_service
+            .purge_expired(state.config.trash_retention_days)
+            .await?;
+
+        Ok(Json(json!({
+            "message": "Trash purged successfully",
+            "data": summary
+        })))
+    }
+}
@@ -0,0 +1,21 @@
+use std::time::Duration;
+
+use crate::database::Database;
+use crate::services::exam_attempt::ExamAttemptService;
+
+/// Periodically submit attempts left open past their deadline
+pub fn spawn(db: Database, interval_secs: u64) {
+    tokio::spawn(async move {
+        let attempt_service = ExamAttemptService::new(db);
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+
+        loop {
+            interval.tick().await;
+            match attempt_service.auto_submit_expired().await {
+                Ok(0) => {}
+                Ok(submitted) => tracing::info!("Auto-submitted {} expired exam attempts", submitted),
+                Err(e) => tracing::error!("Auto-submit of expired attempts failed: {}", e),
+            }
+        }
+    });
+}
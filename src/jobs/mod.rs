@@ -0,0 +1,2 @@
+pub mod auto_submit;
+pub mod trash_purge;
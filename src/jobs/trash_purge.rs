@@ -0,0 +1,34 @@
+use std::time::Duration;
+
+use crate::database::Database;
+use crate::services::trash::TrashService;
+
+/// Periodically purge soft-deleted rows older than `retention_days`
+pub fn spawn(db: Database, retention_days: i64, interval_secs: u64) {
+    tokio::spawn(async move {
+        let trash_service = TrashService::new(db);
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+
+        loop {
+            interval.tick().await;
+            match trash_service.purge_expired(retention_days).await {
+                Ok(summary) => {
+                    tracing::info!(
+                        "Trash purge removed {} exams, {} questions, {} classes",
+                        summary.exams,
+                        summary.questions,
+                        summary.classes
+                    );
+                    if !summary.kept_exams.is_empty() {
+                        tracing::warn!(
+                            "Trash purge kept {} exams that other exams require: {:?}",
+                            summary.kept_exams.len(),
+                            summary.kept_exams
+                        );
+                    }
+                }
+                Err(e) => tracing::error!("Trash purge failed: {}", e),
+            }
+        }
+    });
+}
@@ -2,9 +2,11 @@ mod config;
 mod database;
 mod errors;
 mod handlers;
+mod jobs;
 mod middleware;
 mod models;
 mod services;
+mod storage;
 mod utils;
 mod routes;
 
@@ -26,6 +28,7 @@ pub type AppState = Arc<AppStateInner>;
 pub struct AppStateInner {
     pub db: Database,
     pub config: Config,
+    pub blob_store: Arc<dyn storage::BlobStore>,
 }
 
 #[tokio::main]
@@ -45,6 +48,9 @@ async fn main() -> anyhow::Result<()> {
     // Initialize database
     let db = Database::new(&config.database_url).await?;
 
+    // Attachment storage
+    let blob_store = storage::from_config(&config)?;
+
     // CORS configuration
     let cors = CorsLayer::new()
         .allow_origin(Any)
@@ -52,7 +58,15 @@ async fn main() -> anyhow::Result<()> {
         .allow_headers([header::CONTENT_TYPE, header::AUTHORIZATION]);
 
     // Create shared application state
-    let state = Arc::new(AppStateInner { db, config });
+    let state = Arc::new(AppStateInner { db, config, blob_store });
+
+    // Background jobs
+    jobs::trash_purge::spawn(
+        state.db.clone(),
+        state.config.trash_retention_days,
+        state.config.trash_purge_interval_secs,
+    );
+    jobs::auto_submit::spawn(state.db.clone(), state.config.auto_submit_interval_secs);
 
     // Build the router
     let app = routes::create_routes(state.clone(), cors);
@@ -65,7 +79,11 @@ async fn main() -> anyhow::Result<()> {
     tracing::info!("Server running on http://{}", addr);
 
     let listener = tokio::net::TcpListener::bind(addr).await?;
-    axum::serve(listener, app).await?;
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<std::net::SocketAddr>(),
+    )
+    .await?;
 
     Ok(())
 }
@@ -14,6 +14,7 @@ use uuid::Uuid;
 #[derive(Debug, Clone)]
 pub struct AuthUser {
     pub id: Uuid,
+    #[allow(dead_code)]
     pub email: String,
     pub role: UserRole,
 }
@@ -72,9 +73,9 @@ fn extract_token_from_header(headers: &HeaderMap) -> Result<String, AppError> {
 macro_rules! require_role {
     ($auth_user:expr, $required_role:expr) => {
         match ($auth_user.role.clone(), $required_role) {
-            (crate::models::user::UserRole::Admin, _) => Ok(()),
+            ($crate::models::user::UserRole::Admin, _) => Ok(()),
             (role, required) if role == required => Ok(()),
-            _ => Err(crate::errors::AppError::Forbidden),
+            _ => Err($crate::errors::AppError::Forbidden),
         }
     };
 }
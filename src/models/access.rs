@@ -0,0 +1,45 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::{Validate, ValidationError};
+
+use crate::utils::ip::parse_cidr;
+
+/// Generate a new access code for an exam, or for one class assignment of it
+#[derive(Debug, Deserialize, Validate)]
+pub struct RotateAccessCodeRequest {
+    pub class_id: Option<Uuid>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ClearAccessCodeQuery {
+    pub class_id: Option<Uuid>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateIpAllowlistRequest {
+    /// CIDR ranges students must connect from; empty to allow any address
+    #[validate(custom(function = "validate_ip_ranges"))]
+    pub ip_allowlist: Vec<String>,
+}
+
+fn validate_ip_ranges(ranges: &[String]) -> Result<(), ValidationError> {
+    for range in ranges {
+        parse_cidr(range).map_err(|_| ValidationError::new("invalid_ip_range"))?;
+    }
+    Ok(())
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct AssignmentAccessCode {
+    pub class_id: Uuid,
+    pub access_code: String,
+}
+
+/// Supervised sitting settings of an exam, only shown to teachers
+#[derive(Debug, Serialize, Clone)]
+pub struct ExamAccessResponse {
+    pub exam_id: Uuid,
+    pub access_code: Option<String>,
+    pub assignment_access_codes: Vec<AssignmentAccessCode>,
+    pub ip_allowlist: Vec<String>,
+}
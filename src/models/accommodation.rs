@@ -0,0 +1,88 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+/// Extra time granted to a student. A record without `exam_id` applies to
+/// every exam; a per-exam record takes precedence over the global one.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Accommodation {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub exam_id: Option<Uuid>,
+    pub time_multiplier: f64,
+    pub extra_minutes: i32,
+    pub extended_end_time: Option<DateTime<Utc>>,
+    pub notes: Option<String>,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Accommodation {
+    /// Time allowed for an exam of `duration_minutes` after applying the
+    /// multiplier (rounded up to the minute) and the extra minutes.
+    pub fn apply_to_duration(&self, duration_minutes: i32) -> Duration {
+        let scaled = (duration_minutes as f64 * self.time_multiplier).ceil() as i64;
+        Duration::minutes(scaled + self.extra_minutes as i64)
+    }
+
+    /// Closing time of the exam window for this student.
+    pub fn apply_to_end_time(&self, end_time: DateTime<Utc>) -> DateTime<Utc> {
+        match self.extended_end_time {
+            Some(extended) if extended > end_time => extended,
+            _ => end_time,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateAccommodationRequest {
+    pub user_id: Uuid,
+    pub exam_id: Option<Uuid>,
+    #[validate(range(min = 1.0, max = 5.0))]
+    pub time_multiplier: Option<f64>,
+    #[validate(range(min = 0, max = 1440))]
+    pub extra_minutes: Option<i32>,
+    pub extended_end_time: Option<DateTime<Utc>>,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateAccommodationRequest {
+    #[validate(range(min = 1.0, max = 5.0))]
+    pub time_multiplier: f64,
+    #[validate(range(min = 0, max = 1440))]
+    pub extra_minutes: i32,
+    pub extended_end_time: Option<DateTime<Utc>>,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct AccommodationResponse {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub exam_id: Option<Uuid>,
+    pub time_multiplier: f64,
+    pub extra_minutes: i32,
+    pub extended_end_time: Option<DateTime<Utc>>,
+    pub notes: Option<String>,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<Accommodation> for AccommodationResponse {
+    fn from(accommodation: Accommodation) -> Self {
+        Self {
+            id: accommodation.id,
+            user_id: accommodation.user_id,
+            exam_id: accommodation.exam_id,
+            time_multiplier: accommodation.time_multiplier,
+            extra_minutes: accommodation.extra_minutes,
+            extended_end_time: accommodation.extended_end_time,
+            notes: accommodation.notes,
+            created_by: accommodation.created_by,
+            created_at: accommodation.created_at,
+        }
+    }
+}
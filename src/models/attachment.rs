@@ -0,0 +1,96 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Media that can be attached to questions and choices
+pub const ALLOWED_CONTENT_TYPES: [&str; 7] = [
+    "image/png",
+    "image/jpeg",
+    "image/gif",
+    "image/webp",
+    "audio/mpeg",
+    "audio/ogg",
+    "audio/wav",
+];
+
+/// An uploaded file. Its content lives in the blob store under `storage_key`.
+#[derive(Debug, Clone, Serialize)]
+pub struct Attachment {
+    pub id: Uuid,
+    #[serde(skip_serializing)]
+    pub storage_key: String,
+    pub file_name: String,
+    pub content_type: String,
+    pub size_bytes: i64,
+    pub uploaded_by: Uuid,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A time-limited link to an attachment's content
+#[derive(Debug, Clone, Serialize)]
+pub struct DownloadUrlResponse {
+    pub attachment_id: Uuid,
+    pub url: String,
+    pub content_type: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// The content type the file's leading bytes belong to, if it is one of
+/// `ALLOWED_CONTENT_TYPES`
+pub fn sniff_content_type(bytes: &[u8]) -> Option<&'static str> {
+    let riff = |form: &[u8]| bytes.len() >= 12 && bytes.starts_with(b"RIFF") && &bytes[8..12] == form;
+
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("image/png")
+    } else if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("image/jpeg")
+    } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        Some("image/gif")
+    } else if riff(b"WEBP") {
+        Some("image/webp")
+    } else if riff(b"WAVE") {
+        Some("audio/wav")
+    } else if bytes.starts_with(b"OggS") {
+        Some("audio/ogg")
+    } else if bytes.starts_with(b"ID3") || (bytes.len() >= 2 && bytes[0] == 0xFF && bytes[1] & 0xE0 == 0xE0) {
+        Some("audio/mpeg")
+    } else {
+        None
+    }
+}
+
+/// Reject an upload that is too large, of a type that cannot be attached, or
+/// whose content is not what its declared content type says. Returns the
+/// content type to store it with.
+pub fn check_upload(declared_type: Option<&str>, bytes: &[u8], max_bytes: usize) -> Result<&'static str, String> {
+    if bytes.is_empty() {
+        return Err("The file is empty".to_string());
+    }
+    if bytes.len() > max_bytes {
+        return Err(format!("The file is larger than {} bytes", max_bytes));
+    }
+    let sniffed = sniff_content_type(bytes).ok_or_else(|| {
+        format!("Unsupported file type, allowed: {}", ALLOWED_CONTENT_TYPES.join(", "))
+    })?;
+    // Browsers send a few aliases for the same formats
+    let declared = declared_type.map(|declared| match declared.trim().to_ascii_lowercase().as_str() {
+        "image/jpg" => "image/jpeg".to_string(),
+        "audio/mp3" => "audio/mpeg".to_string(),
+        "audio/wave" | "audio/x-wav" => "audio/wav".to_string(),
+        other => other.to_string(),
+    });
+    match declared {
+        Some(declared) if declared != sniffed && declared != "application/octet-stream" => Err(format!(
+            "The file is declared as {} but its content is {}",
+            declared, sniffed
+        )),
+        _ => Ok(sniffed),
+    }
+}
+
+/// Query of a signed link to attachment content served by the API
+#[derive(Debug, Deserialize)]
+pub struct SignedDownloadQuery {
+    pub expires: i64,
+    pub signature: String,
+}
@@ -4,6 +4,8 @@ use sqlx::FromRow;
 use uuid::Uuid;
 use validator::Validate;
 
+use crate::models::question::{QuestionResponse, StructuredResponse};
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct ExamAttempt {
     pub id: Uuid,
@@ -12,23 +14,112 @@ pub struct ExamAttempt {
     pub started_at: Option<DateTime<Utc>>,
     pub submitted_at: Option<DateTime<Utc>>,
     pub score_total: Option<i32>,
+    /// Total score of the questions drawn for this attempt
+    pub max_score: Option<i32>,
+    /// Seed of the question and choice order of this attempt
+    pub shuffle_seed: i64,
+    /// Address the attempt was started from
+    pub client_ip: Option<String>,
+    /// Address the attempt was submitted from
+    pub submitted_ip: Option<String>,
+    /// Set at submission when the exam has a pass mark
+    pub passed: Option<bool>,
+    /// Set at submission when the exam has a grade scale
+    pub grade: Option<String>,
+    /// Submitted by the server with the saved answers after the deadline passed
+    pub auto_submitted: bool,
 }
 
 #[derive(Debug, Deserialize, Validate)]
 pub struct StartExamAttemptRequest {
     pub exam_id: Uuid,
+    /// Required when the exam or the student's class assignment has an access code
+    pub access_code: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Validate)]
 pub struct SubmitExamAttemptRequest {
     pub attempt_id: Uuid,
     pub answers: Vec<AnswerSubmission>,
+    pub access_code: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Validate)]
 pub struct AnswerSubmission {
+    pub question_id: Uuid,
+    #[serde(default)]
+    pub answer_text: String,
+    /// Selected choices of a `multiple_select` question, or the one selected
+    /// choice of a `multiple_choice` or `true_false` question
+    #[serde(default)]
+    pub choice_ids: Vec<Uuid>,
+    /// Answer to a `matching`, `ordering` or `cloze` question
+    pub response: Option<StructuredResponse>,
+}
+
+/// Save the answer to one question while the attempt is in progress
+#[derive(Debug, Deserialize, Validate)]
+pub struct SaveAnswerRequest {
+    #[serde(default)]
+    pub answer_text: String,
+    #[serde(default)]
+    pub choice_ids: Vec<Uuid>,
+    pub response: Option<StructuredResponse>,
+    /// Version of the saved answer this save replaces; omitted for the
+    /// first save of a question
+    pub version: Option<i32>,
+}
+
+/// An answer saved during an attempt. It is graded when the attempt is
+/// submitted.
+#[derive(Debug, Clone, Serialize)]
+pub struct AnswerDraft {
     pub question_id: Uuid,
     pub answer_text: String,
+    pub choice_ids: Vec<Uuid>,
+    pub response: Option<StructuredResponse>,
+    /// Bumped by every save
+    pub version: i32,
+    /// Server time of the last save
+    pub saved_at: DateTime<Utc>,
+}
+
+impl From<AnswerDraft> for AnswerSubmission {
+    fn from(draft: AnswerDraft) -> Self {
+        Self {
+            question_id: draft.question_id,
+            answer_text: draft.answer_text,
+            choice_ids: draft.choice_ids,
+            response: draft.response,
+        }
+    }
+}
+
+/// The answers an attempt is graded with: the saved answers, replaced by
+/// any answer to the same question in the submission, then the remaining
+/// submitted answers. Only the last answer to a question counts.
+pub fn merge_answers(saved: Vec<AnswerSubmission>, submitted: Vec<AnswerSubmission>) -> Vec<AnswerSubmission> {
+    let mut answers: Vec<AnswerSubmission> = Vec::with_capacity(saved.len() + submitted.len());
+    for answer in saved.into_iter().chain(submitted) {
+        match answers.iter_mut().find(|existing| existing.question_id == answer.question_id) {
+            Some(existing) => *existing = answer,
+            None => answers.push(answer),
+        }
+    }
+    answers
+}
+
+impl AnswerSubmission {
+    /// Nothing was answered; blank answers are not penalised
+    pub fn is_blank(&self) -> bool {
+        let response_blank = match &self.response {
+            None => true,
+            Some(StructuredResponse::Matching(pairs)) => pairs.is_empty(),
+            Some(StructuredResponse::Ordering(items)) => items.is_empty(),
+            Some(StructuredResponse::Cloze(answers)) => answers.values().all(|answer| answer.trim().is_empty()),
+        };
+        self.answer_text.trim().is_empty() && self.choice_ids.is_empty() && response_blank
+    }
 }
 
 #[derive(Debug, Serialize, Clone)]
@@ -39,7 +130,35 @@ pub struct ExamAttemptResponse {
     pub started_at: Option<DateTime<Utc>>,
     pub submitted_at: Option<DateTime<Utc>>,
     pub score_total: Option<i32>,
+    pub max_score: Option<i32>,
+    pub client_ip: Option<String>,
+    pub submitted_ip: Option<String>,
+    pub passed: Option<bool>,
+    pub grade: Option<String>,
+    pub auto_submitted: bool,
     pub status: String,
+    /// Time by which the attempt must be submitted, set while it is in progress
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deadline: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remaining_seconds: Option<i64>,
+    /// Answers saved so far, returned when an attempt in progress is resumed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub saved_answers: Option<Vec<AnswerDraft>>,
+}
+
+impl ExamAttemptResponse {
+    /// Attach the attempt deadline and the time left until it
+    pub fn with_deadline(mut self, deadline: DateTime<Utc>) -> Self {
+        self.remaining_seconds = Some((deadline - Utc::now()).num_seconds().max(0));
+        self.deadline = Some(deadline);
+        self
+    }
+
+    pub fn with_saved_answers(mut self, saved_answers: Vec<AnswerDraft>) -> Self {
+        self.saved_answers = Some(saved_answers);
+        self
+    }
 }
 
 impl From<ExamAttempt> for ExamAttemptResponse {
@@ -59,7 +178,16 @@ impl From<ExamAttempt> for ExamAttemptResponse {
             started_at: attempt.started_at,
             submitted_at: attempt.submitted_at,
             score_total: attempt.score_total,
+            max_score: attempt.max_score,
+            client_ip: attempt.client_ip,
+            submitted_ip: attempt.submitted_ip,
+            passed: attempt.passed,
+            grade: attempt.grade,
+            auto_submitted: attempt.auto_submitted,
             status,
+            deadline: None,
+            remaining_seconds: None,
+            saved_answers: None,
         }
     }
 }
@@ -70,8 +198,16 @@ pub struct Answer {
     pub attempt_id: Uuid,
     pub question_id: Uuid,
     pub answer_text: Option<String>,
+    pub choice_ids: Option<Vec<Uuid>>,
     pub is_correct: Option<bool>,
     pub score_awarded: Option<i32>,
+    /// Points deducted by the exam's negative marking
+    pub penalty: f64,
+    /// Why the answer was marked wrong, e.g. "wrong_unit"
+    pub incorrect_reason: Option<String>,
+    pub response: Option<StructuredResponse>,
+    /// How each gap of a cloze answer was graded
+    pub gap_results: Option<Vec<GapResult>>,
 }
 
 #[derive(Debug, Serialize, Clone)]
@@ -80,8 +216,54 @@ pub struct AnswerResponse {
     pub attempt_id: Uuid,
     pub question_id: Uuid,
     pub answer_text: Option<String>,
+    pub choice_ids: Option<Vec<Uuid>>,
     pub is_correct: Option<bool>,
     pub score_awarded: Option<i32>,
+    /// Points deducted by the exam's negative marking
+    pub penalty: f64,
+    /// Why the answer was marked wrong, e.g. "wrong_unit"
+    pub incorrect_reason: Option<String>,
+    pub response: Option<StructuredResponse>,
+    /// How each gap of a cloze answer was graded
+    pub gap_results: Option<Vec<GapResult>>,
+    /// Explanation and choice feedback, once released by the exam's review policy
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub feedback: Option<AnswerFeedback>,
+}
+
+/// What a student learns about a question after the exam
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct AnswerFeedback {
+    pub explanation: Option<String>,
+    /// Feedback of the choices the student selected
+    pub choices: Vec<ChoiceFeedback>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ChoiceFeedback {
+    pub choice_id: Uuid,
+    pub feedback: String,
+}
+
+impl AnswerFeedback {
+    /// Feedback from the revision of the question the student answered
+    pub fn for_answer(question: &QuestionResponse, choice_ids: &[Uuid]) -> Self {
+        let choices = choice_ids
+            .iter()
+            .filter_map(|choice_id| {
+                let choice = question.choices.iter().find(|choice| choice.id == *choice_id)?;
+                choice.feedback.clone().map(|feedback| ChoiceFeedback {
+                    choice_id: *choice_id,
+                    feedback,
+                })
+            })
+            .collect();
+
+        Self {
+            explanation: question.explanation.clone(),
+            choices,
+        }
+    }
 }
 
 impl From<Answer> for AnswerResponse {
@@ -91,20 +273,43 @@ impl From<Answer> for AnswerResponse {
             attempt_id: answer.attempt_id,
             question_id: answer.question_id,
             answer_text: answer.answer_text,
+            choice_ids: answer.choice_ids,
             is_correct: answer.is_correct,
             score_awarded: answer.score_awarded,
+            penalty: answer.penalty,
+            incorrect_reason: answer.incorrect_reason,
+            response: answer.response,
+            gap_results: answer.gap_results,
+            feedback: None,
         }
     }
 }
 
+/// Grading of one gap of a cloze answer
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GapResult {
+    pub key: String,
+    pub answer: String,
+    pub is_correct: bool,
+    pub score_awarded: i32,
+    /// Why a numeric gap was marked wrong, e.g. "wrong_unit"
+    pub incorrect_reason: Option<String>,
+}
+
+/// A question of an attempt with the choice order the student saw
+#[derive(Debug, Serialize, Clone)]
+pub struct AttemptQuestionOrder {
+    pub question_id: Uuid,
+    pub position: i32,
+    pub choice_ids: Vec<Uuid>,
+}
+
 #[derive(Debug, Serialize)]
 pub struct ExamAttemptWithAnswers {
     pub attempt: ExamAttemptResponse,
     pub answers: Vec<AnswerResponse>,
-}
-
-#[derive(Debug, Serialize)]
-pub struct ExamAttemptWithAnswersRequest {
-    pub attempt_id: Uuid,
-    pub user_id: Uuid,
+    /// Questions in the order the student got them
+    pub questions: Vec<AttemptQuestionOrder>,
+    /// Whether explanations and choice feedback are included
+    pub feedback_released: bool,
 }
@@ -0,0 +1,75 @@
+use pulldown_cmark::{Options, Parser};
+use serde::{Deserialize, Serialize};
+
+/// How `question_text` and `choice_text` of a question are written
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ContentFormat {
+    /// Shown as it is, without any markup
+    #[default]
+    Plain,
+    /// CommonMark with tables and strikethrough
+    Markdown,
+    /// Markdown plus `$inline$` and `$$display$$` LaTeX math
+    MarkdownMath,
+}
+
+impl ContentFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ContentFormat::Plain => "plain",
+            ContentFormat::Markdown => "markdown",
+            ContentFormat::MarkdownMath => "markdown_math",
+        }
+    }
+}
+
+impl std::str::FromStr for ContentFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "plain" => Ok(ContentFormat::Plain),
+            "markdown" => Ok(ContentFormat::Markdown),
+            "markdown_math" => Ok(ContentFormat::MarkdownMath),
+            _ => Err(format!("Invalid content format: {}", s)),
+        }
+    }
+}
+
+/// `source` rendered to HTML that is safe to insert into a page. Raw HTML in
+/// markdown is reduced to an allowlist of formatting tags without scripts,
+/// event handlers, styles or `javascript:` links. Math is left as
+/// `<span class="math math-inline">` and `<span class="math math-display">`
+/// with the LaTeX inside, for the client to typeset.
+pub fn render_html(format: ContentFormat, source: &str) -> String {
+    let mut options = Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH;
+    match format {
+        ContentFormat::Plain => return escape_html(source).replace('\n', "<br>\n"),
+        ContentFormat::Markdown => {}
+        ContentFormat::MarkdownMath => options |= Options::ENABLE_MATH,
+    }
+
+    let mut html = String::new();
+    pulldown_cmark::html::push_html(&mut html, Parser::new_ext(source, options));
+
+    ammonia::Builder::default()
+        .add_allowed_classes("span", &["math", "math-inline", "math-display"])
+        .clean(&html)
+        .to_string()
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,118 @@
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+use validator::Validate;
+
+/// "Pick `draw_count` random questions" from the questions of an exam that
+/// match the rule's section, pool and difficulty. Stratify by difficulty by
+/// adding one rule per difficulty.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct DrawRule {
+    pub id: Uuid,
+    pub exam_id: Uuid,
+    pub section_id: Option<Uuid>,
+    pub pool: Option<String>,
+    pub difficulty: Option<String>,
+    pub draw_count: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+impl DrawRule {
+    pub fn matches(&self, question: &PoolQuestion) -> bool {
+        self.section_id.is_none_or(|id| question.section_id == Some(id))
+            && self.pool.as_ref().is_none_or(|pool| question.pool.as_ref() == Some(pool))
+            && self
+                .difficulty
+                .as_ref()
+                .is_none_or(|difficulty| question.difficulty.as_ref() == Some(difficulty))
+    }
+}
+
+/// The fields of a question that draw rules select on
+#[derive(Debug, Clone)]
+pub struct PoolQuestion {
+    pub id: Uuid,
+    pub section_id: Option<Uuid>,
+    pub pool: Option<String>,
+    pub difficulty: Option<String>,
+}
+
+/// Draw the questions of one attempt, keeping the order of `questions`.
+///
+/// Each rule draws from the questions that match it and were not drawn by an
+/// earlier rule. Questions that match no rule are included, unless their
+/// section has a draw count, in which case that many are drawn from them.
+pub fn draw_questions<R: Rng>(
+    questions: &[PoolQuestion],
+    rules: &[DrawRule],
+    section_draw_counts: &HashMap<Uuid, i32>,
+    rng: &mut R,
+) -> Vec<Uuid> {
+    let mut drawn = HashSet::new();
+
+    for rule in rules {
+        let candidates: Vec<Uuid> = questions
+            .iter()
+            .filter(|q| rule.matches(q) && !drawn.contains(&q.id))
+            .map(|q| q.id)
+            .collect();
+        drawn.extend(candidates.choose_multiple(rng, rule.draw_count.max(0) as usize).copied());
+    }
+
+    let mut unruled_by_section: HashMap<Option<Uuid>, Vec<Uuid>> = HashMap::new();
+    for question in questions.iter().filter(|q| !rules.iter().any(|r| r.matches(q))) {
+        unruled_by_section.entry(question.section_id).or_default().push(question.id);
+    }
+    for (section_id, ids) in unruled_by_section {
+        match section_id.and_then(|id| section_draw_counts.get(&id)) {
+            Some(&count) => drawn.extend(ids.choose_multiple(rng, count.max(0) as usize).copied()),
+            None => drawn.extend(ids),
+        }
+    }
+
+    questions.iter().map(|q| q.id).filter(|id| drawn.contains(id)).collect()
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateDrawRuleRequest {
+    pub section_id: Option<Uuid>,
+    #[validate(length(min = 1))]
+    pub pool: Option<String>,
+    #[validate(length(min = 1))]
+    pub difficulty: Option<String>,
+    #[validate(range(min = 1))]
+    pub draw_count: i32,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct DrawRuleResponse {
+    pub id: Uuid,
+    pub exam_id: Uuid,
+    pub section_id: Option<Uuid>,
+    pub pool: Option<String>,
+    pub difficulty: Option<String>,
+    pub draw_count: i32,
+    /// Questions currently matching the rule; fewer than `draw_count` means
+    /// every student gets all of them
+    pub available: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+impl DrawRuleResponse {
+    pub fn new(rule: DrawRule, available: i64) -> Self {
+        Self {
+            id: rule.id,
+            exam_id: rule.exam_id,
+            section_id: rule.section_id,
+            pool: rule.pool,
+            difficulty: rule.difficulty,
+            draw_count: rule.draw_count,
+            available,
+            created_at: rule.created_at,
+        }
+    }
+}
@@ -4,6 +4,8 @@ use sqlx::FromRow;
 use uuid::Uuid;
 use validator::{Validate};
 
+use crate::models::grade_scale::score_percent;
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Exam {
     pub id: Uuid,
@@ -15,7 +17,162 @@ pub struct Exam {
     pub end_time: DateTime<Utc>,
     pub is_active: bool,
     pub category: String,
-    pub difficulty: String
+    pub difficulty: String,
+    pub is_template: bool,
+    pub pass_mark: Option<i32>,
+    pub pass_mark_type: PassMarkType,
+    pub grade_scale_id: Option<Uuid>,
+    pub shuffle_questions: bool,
+    pub shuffle_choices: bool,
+    /// Share of a question's score deducted for a wrong answer
+    pub wrong_answer_penalty: f64,
+    pub score_floor: ScoreFloor,
+    /// When students see explanations and choice feedback
+    pub review_policy: ReviewPolicy,
+}
+
+/// When question explanations and choice feedback are released to students
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReviewPolicy {
+    /// As soon as the student submits the attempt
+    #[default]
+    AfterSubmission,
+    /// Once the exam window has closed for everyone
+    AfterExamEnd,
+    /// Only teachers see feedback
+    Never,
+}
+
+impl ReviewPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReviewPolicy::AfterSubmission => "after_submission",
+            ReviewPolicy::AfterExamEnd => "after_exam_end",
+            ReviewPolicy::Never => "never",
+        }
+    }
+
+    /// Whether a student may see feedback on an attempt at `now`
+    pub fn is_released(&self, submitted: bool, exam_end: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+        match self {
+            ReviewPolicy::AfterSubmission => submitted,
+            ReviewPolicy::AfterExamEnd => submitted && now >= exam_end,
+            ReviewPolicy::Never => false,
+        }
+    }
+}
+
+impl std::str::FromStr for ReviewPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "after_submission" => Ok(ReviewPolicy::AfterSubmission),
+            "after_exam_end" => Ok(ReviewPolicy::AfterExamEnd),
+            "never" => Ok(ReviewPolicy::Never),
+            _ => Err(format!("Invalid review policy: {}", s)),
+        }
+    }
+}
+
+/// Where negative marking stops deducting points
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ScoreFloor {
+    /// A question never scores below zero
+    Question,
+    /// Questions may score below zero, the exam total never does
+    #[default]
+    Exam,
+}
+
+impl ScoreFloor {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScoreFloor::Question => "question",
+            ScoreFloor::Exam => "exam",
+        }
+    }
+}
+
+impl std::str::FromStr for ScoreFloor {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "question" => Ok(ScoreFloor::Question),
+            "exam" => Ok(ScoreFloor::Exam),
+            _ => Err(format!("Invalid score floor: {}", s)),
+        }
+    }
+}
+
+/// Negative marking rules of an exam
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct NegativeMarking {
+    pub wrong_answer_penalty: f64,
+    pub score_floor: ScoreFloor,
+}
+
+impl NegativeMarking {
+    /// Points deducted for an answer. Only answers marked wrong lose points;
+    /// blank answers and answers left for manual grading never do. With a
+    /// per-question floor the deduction never exceeds what the answer earned.
+    pub fn penalty(&self, is_correct: Option<bool>, is_blank: bool, score_awarded: i32, question_score: i32) -> f64 {
+        if is_blank || is_correct != Some(false) {
+            return 0.0;
+        }
+        let penalty = self.wrong_answer_penalty * question_score as f64;
+        match self.score_floor {
+            ScoreFloor::Question => penalty.min(score_awarded.max(0) as f64),
+            ScoreFloor::Exam => penalty,
+        }
+    }
+
+    /// Attempt score from the points earned and deducted, rounded to the
+    /// nearest point and never below zero
+    pub fn total(&self, score_awarded: i32, penalties: f64) -> i32 {
+        (score_awarded as f64 - penalties).round().max(0.0) as i32
+    }
+}
+
+/// How the pass mark of an exam is expressed
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PassMarkType {
+    Points,
+    #[default]
+    Percent,
+}
+
+impl PassMarkType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PassMarkType::Points => "points",
+            PassMarkType::Percent => "percent",
+        }
+    }
+
+    /// Whether `total_score` out of `max_score` reaches `pass_mark`
+    pub fn is_passed(&self, pass_mark: i32, total_score: i32, max_score: i32) -> bool {
+        match self {
+            PassMarkType::Points => total_score >= pass_mark,
+            PassMarkType::Percent => score_percent(total_score, max_score) >= pass_mark as f64,
+        }
+    }
+}
+
+impl std::str::FromStr for PassMarkType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "points" => Ok(PassMarkType::Points),
+            "percent" => Ok(PassMarkType::Percent),
+            _ => Err(format!("Invalid pass mark type: {}", s)),
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, Validate)]
@@ -30,6 +187,23 @@ pub struct CreateExamRequest {
     pub end_time: DateTime<Utc>,
     pub category: String,
     pub difficulty: String,
+    /// Templates are listed in the template gallery and cannot be attempted
+    pub is_template: Option<bool>,
+    /// Minimum score to pass, in points or percent depending on `pass_mark_type`
+    #[validate(range(min = 0))]
+    pub pass_mark: Option<i32>,
+    pub pass_mark_type: Option<PassMarkType>,
+    pub grade_scale_id: Option<Uuid>,
+    /// Give each attempt its own question order
+    pub shuffle_questions: Option<bool>,
+    /// Give each attempt its own choice order; pinned choices keep their place
+    pub shuffle_choices: Option<bool>,
+    /// Share of a question's score deducted for a wrong answer, e.g. 0.25
+    #[validate(range(min = 0.0, max = 1.0))]
+    pub wrong_answer_penalty: Option<f64>,
+    pub score_floor: Option<ScoreFloor>,
+    /// When students see explanations and choice feedback
+    pub review_policy: Option<ReviewPolicy>,
 }
 
 #[derive(Debug, Deserialize, Validate)]
@@ -44,7 +218,26 @@ pub struct UpdateExamRequest {
     pub start_time: DateTime<Utc>,
     pub end_time: DateTime<Utc>,
     pub category: String,
-    pub difficulty: String
+    pub difficulty: String,
+    /// Keeps the current value when omitted
+    pub is_template: Option<bool>,
+    /// Keep the current value when omitted
+    #[validate(range(min = 0))]
+    pub pass_mark: Option<i32>,
+    pub pass_mark_type: Option<PassMarkType>,
+    pub grade_scale_id: Option<Uuid>,
+    /// Remove the pass mark or grade scale
+    #[serde(default)]
+    pub clear_pass_mark: bool,
+    #[serde(default)]
+    pub clear_grade_scale: bool,
+    /// Keep the current value when omitted
+    pub shuffle_questions: Option<bool>,
+    pub shuffle_choices: Option<bool>,
+    #[validate(range(min = 0.0, max = 1.0))]
+    pub wrong_answer_penalty: Option<f64>,
+    pub score_floor: Option<ScoreFloor>,
+    pub review_policy: Option<ReviewPolicy>,
 }
 
 #[derive(Debug, Serialize, Clone)]
@@ -59,6 +252,15 @@ pub struct ExamResponse {
     pub is_active: bool,
     pub category: String,
     pub difficulty: String,
+    pub is_template: bool,
+    pub pass_mark: Option<i32>,
+    pub pass_mark_type: PassMarkType,
+    pub grade_scale_id: Option<Uuid>,
+    pub shuffle_questions: bool,
+    pub shuffle_choices: bool,
+    pub wrong_answer_penalty: f64,
+    pub score_floor: ScoreFloor,
+    pub review_policy: ReviewPolicy,
 }
 
 impl From<Exam> for ExamResponse {
@@ -74,10 +276,132 @@ impl From<Exam> for ExamResponse {
             is_active: exam.is_active,
             category: exam.category,
             difficulty: exam.difficulty,
+            is_template: exam.is_template,
+            pass_mark: exam.pass_mark,
+            pass_mark_type: exam.pass_mark_type,
+            grade_scale_id: exam.grade_scale_id,
+            shuffle_questions: exam.shuffle_questions,
+            shuffle_choices: exam.shuffle_choices,
+            wrong_answer_penalty: exam.wrong_answer_penalty,
+            score_floor: exam.score_floor,
+            review_policy: exam.review_policy,
+        }
+    }
+}
+
+/// Time window of an exam relative to now
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExamWindow {
+    Active,
+    Upcoming,
+    Past,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExamSort {
+    #[default]
+    StartTimeDesc,
+    StartTimeAsc,
+    EndTimeAsc,
+    EndTimeDesc,
+    TitleAsc,
+    TitleDesc,
+}
+
+impl ExamSort {
+    pub fn order_by(&self) -> &'static str {
+        match self {
+            ExamSort::StartTimeDesc => "start_time DESC",
+            ExamSort::StartTimeAsc => "start_time ASC",
+            ExamSort::EndTimeAsc => "end_time ASC",
+            ExamSort::EndTimeDesc => "end_time DESC",
+            ExamSort::TitleAsc => "title ASC",
+            ExamSort::TitleDesc => "title DESC",
+        }
+    }
+}
+
+/// Query parameters for searching exams, used together with `Pagination`
+#[derive(Debug, Default, Deserialize)]
+pub struct ExamFilter {
+    pub category: Option<String>,
+    pub difficulty: Option<String>,
+    pub created_by: Option<Uuid>,
+    pub status: Option<ExamWindow>,
+    /// Full-text search on the title
+    pub q: Option<String>,
+    #[serde(default)]
+    pub sort: ExamSort,
+}
+
+/// Whether a student can currently start an exam
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExamAvailability {
+    Upcoming,
+    Open,
+    Closed,
+}
+
+impl ExamAvailability {
+    pub fn at(now: DateTime<Utc>, start_time: DateTime<Utc>, window_end: DateTime<Utc>) -> Self {
+        if now < start_time {
+            ExamAvailability::Upcoming
+        } else if now > window_end {
+            ExamAvailability::Closed
+        } else {
+            ExamAvailability::Open
         }
     }
 }
 
+/// An exam assigned to the current student, with their attempt status
+#[derive(Debug, Serialize, Clone)]
+pub struct AssignedExamResponse {
+    pub exam: ExamResponse,
+    pub availability: ExamAvailability,
+    /// Close of the exam window for this student (includes accommodations)
+    pub window_end: DateTime<Utc>,
+    /// Seconds until the in-progress attempt must be submitted, or until the
+    /// window closes when there is no attempt in progress
+    pub time_remaining_seconds: Option<i64>,
+    pub attempts_used: i64,
+    pub attempts_allowed: i64,
+    pub active_attempt_id: Option<Uuid>,
+    pub best_score: Option<i32>,
+    pub max_score: i64,
+    /// True while a prerequisite is not met; `lock_reasons` says which
+    pub locked: bool,
+    pub lock_reasons: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CloneExamRequest {
+    /// Defaults to the title of the source exam
+    #[validate(length(min = 1))]
+    pub title: Option<String>,
+    #[validate(range(min = 1))]
+    pub duration_minutes: Option<i32>,
+    pub start_time: DateTime<Utc>,
+    pub end_time: DateTime<Utc>,
+    /// Copy the class assignments of the source exam
+    #[serde(default)]
+    pub include_assignments: bool,
+    /// Create the copy as a template
+    #[serde(default)]
+    pub as_template: bool,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct CloneExamResponse {
+    pub exam: ExamResponse,
+    pub source_exam_id: Uuid,
+    pub questions_copied: usize,
+    pub assignments_copied: u64,
+}
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct ExamAssignments {
     pub exam_id: Uuid,
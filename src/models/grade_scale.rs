@@ -0,0 +1,78 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+/// Named set of grade bands that can be attached to any number of exams
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct GradeScale {
+    pub id: Uuid,
+    pub name: String,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A grade awarded from `min_percent` upwards, up to the next band
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct GradeBand {
+    pub label: String,
+    pub min_percent: f64,
+}
+
+/// Percentage of `max_score` achieved, 0 when the exam has no points
+pub fn score_percent(total_score: i32, max_score: i32) -> f64 {
+    if max_score > 0 {
+        total_score as f64 * 100.0 / max_score as f64
+    } else {
+        0.0
+    }
+}
+
+/// Grade for a percentage: the band with the highest `min_percent` that the
+/// percentage reaches. `None` when it is below every band.
+pub fn grade_for(bands: &[GradeBand], percent: f64) -> Option<String> {
+    bands
+        .iter()
+        .filter(|band| percent >= band.min_percent)
+        .max_by(|a, b| a.min_percent.total_cmp(&b.min_percent))
+        .map(|band| band.label.clone())
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct GradeBandRequest {
+    #[validate(length(min = 1, max = 20))]
+    pub label: String,
+    #[validate(range(min = 0.0, max = 100.0))]
+    pub min_percent: f64,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct GradeScaleRequest {
+    #[validate(length(min = 1))]
+    pub name: String,
+    #[validate(length(min = 1), nested)]
+    pub bands: Vec<GradeBandRequest>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct GradeScaleResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    /// Ordered from the highest grade to the lowest
+    pub bands: Vec<GradeBand>,
+}
+
+impl GradeScaleResponse {
+    pub fn new(scale: GradeScale, bands: Vec<GradeBand>) -> Self {
+        Self {
+            id: scale.id,
+            name: scale.name,
+            created_by: scale.created_by,
+            created_at: scale.created_at,
+            bands,
+        }
+    }
+}
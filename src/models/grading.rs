@@ -0,0 +1,436 @@
+use regex::RegexBuilder;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use uuid::Uuid;
+
+use crate::models::attempt::{AnswerSubmission, GapResult};
+use crate::models::question::{
+    AcceptedAnswer, ClozeGap, GapType, MatchingPair, NumericAnswer, QuestionResponse, QuestionType, ScoringPolicy,
+    StructuredResponse, ToleranceType,
+};
+use crate::utils::text::{TextNormalization, levenshtein, normalize_text};
+
+/// Grade a `multiple_select` answer: `selected` are the choice IDs the
+/// student picked, `correct` the IDs of the choices marked correct.
+///
+/// Returns whether the answer is exactly right and the score awarded.
+/// Partial scores are rounded to the nearest point.
+pub fn grade_multiple_select(
+    selected: &[Uuid],
+    correct: &[Uuid],
+    policy: ScoringPolicy,
+    score: i32,
+) -> (Option<bool>, i32) {
+    let selected: HashSet<&Uuid> = selected.iter().collect();
+    let correct: HashSet<&Uuid> = correct.iter().collect();
+    if correct.is_empty() {
+        return (None, 0);
+    }
+
+    let right = selected.intersection(&correct).count() as f64;
+    let wrong = selected.difference(&correct).count() as f64;
+    let is_correct = selected == correct;
+
+    let fraction = match policy {
+        ScoringPolicy::AllOrNothing => {
+            if is_correct { 1.0 } else { 0.0 }
+        }
+        ScoringPolicy::PerCorrect => {
+            if wrong > 0.0 { 0.0 } else { right / correct.len() as f64 }
+        }
+        ScoringPolicy::RightMinusWrong => ((right - wrong) / correct.len() as f64).max(0.0),
+    };
+
+    (Some(is_correct), (score as f64 * fraction).round() as i32)
+}
+
+/// Grade a `multiple_choice` or `true_false` answer by the choice the
+/// student selected: `choices` are the ID and `is_correct` of each choice of
+/// the question. No selection is a wrong answer.
+pub fn grade_single_choice(selected: Option<Uuid>, choices: &[(Uuid, bool)], score: i32) -> (Option<bool>, i32) {
+    let is_correct = selected.is_some_and(|selected| {
+        choices
+            .iter()
+            .any(|(choice_id, is_correct)| *choice_id == selected && *is_correct)
+    });
+    (Some(is_correct), if is_correct { score } else { 0 })
+}
+
+/// Grade a `short_answer` answer against the question's accepted answers.
+///
+/// The best matching accepted answer decides the score; the answer counts as
+/// correct when that one is worth the full score. Without accepted answers
+/// the question is left for manual grading.
+pub fn grade_short_answer(answer: &str, accepted: &[AcceptedAnswer], score: i32) -> (Option<bool>, i32) {
+    if accepted.is_empty() {
+        return (None, 0);
+    }
+
+    let best_percent = accepted
+        .iter()
+        .filter(|candidate| matches_accepted_answer(answer, candidate))
+        .map(|candidate| candidate.score_percent)
+        .max();
+
+    match best_percent {
+        Some(percent) => (
+            Some(percent >= 100),
+            (score as f64 * percent as f64 / 100.0).round() as i32,
+        ),
+        None => (Some(false), 0),
+    }
+}
+
+pub fn matches_accepted_answer(answer: &str, accepted: &AcceptedAnswer) -> bool {
+    let options = TextNormalization {
+        case_sensitive: accepted.case_sensitive,
+        ignore_whitespace: accepted.ignore_whitespace,
+        ignore_diacritics: accepted.ignore_diacritics,
+    };
+    let answer = normalize_text(answer, options);
+
+    if accepted.regex {
+        return RegexBuilder::new(&format!("^(?:{})$", accepted.text))
+            .case_insensitive(!accepted.case_sensitive)
+            .build()
+            .is_ok_and(|regex| regex.is_match(&answer));
+    }
+
+    let expected = normalize_text(&accepted.text, options);
+    levenshtein(&answer, &expected) <= accepted.max_distance.max(0) as usize
+}
+
+/// Why a numeric answer was marked wrong
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NumericMismatch {
+    NotANumber,
+    OutOfTolerance,
+    MissingUnit,
+    WrongUnit,
+    SignificantFigures,
+}
+
+impl NumericMismatch {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NumericMismatch::NotANumber => "not_a_number",
+            NumericMismatch::OutOfTolerance => "out_of_tolerance",
+            NumericMismatch::MissingUnit => "missing_unit",
+            NumericMismatch::WrongUnit => "wrong_unit",
+            NumericMismatch::SignificantFigures => "significant_figures",
+        }
+    }
+}
+
+/// Split an answer like "9.80 m/s^2" into the number as written and the
+/// unit after it. `None` when it does not start with a number.
+pub fn split_numeric_answer(input: &str) -> Option<(&str, &str)> {
+    let input = input.trim();
+    let bytes = input.as_bytes();
+    let digits_from = |mut i: usize| {
+        while i < bytes.len() && bytes[i].is_ascii_digit() {
+            i += 1;
+        }
+        i
+    };
+
+    let mut end = usize::from(matches!(bytes.first(), Some(b'+' | b'-')));
+    let integer_end = digits_from(end);
+    let mut has_digits = integer_end > end;
+    end = integer_end;
+    if bytes.get(end) == Some(&b'.') {
+        let fraction_end = digits_from(end + 1);
+        has_digits |= fraction_end > end + 1;
+        end = fraction_end;
+    }
+    if !has_digits {
+        return None;
+    }
+    if matches!(bytes.get(end), Some(b'e' | b'E')) {
+        let sign = usize::from(matches!(bytes.get(end + 1), Some(b'+' | b'-')));
+        let exponent_end = digits_from(end + 1 + sign);
+        if exponent_end > end + 1 + sign {
+            end = exponent_end;
+        }
+    }
+
+    Some((&input[..end], input[end..].trim()))
+}
+
+/// Significant figures of a number as written. Trailing zeros count only
+/// after a decimal point, so "1500" has 2 and "1500." or "1.500e3" have 4.
+pub fn significant_figures(number: &str) -> u32 {
+    let mantissa = number
+        .trim_start_matches(['+', '-'])
+        .split(['e', 'E'])
+        .next()
+        .unwrap_or_default();
+    let has_point = mantissa.contains('.');
+    let digits: String = mantissa.chars().filter(char::is_ascii_digit).collect();
+    let digits = digits.trim_start_matches('0');
+    let digits = if has_point { digits } else { digits.trim_end_matches('0') };
+    (digits.len() as u32).max(1)
+}
+
+/// Check a numeric answer against the question's spec
+pub fn check_numeric(answer: &str, spec: &NumericAnswer) -> Result<(), NumericMismatch> {
+    let (number, unit) = split_numeric_answer(answer).ok_or(NumericMismatch::NotANumber)?;
+    let value: f64 = number.parse().map_err(|_| NumericMismatch::NotANumber)?;
+
+    let allowed = match spec.tolerance_type {
+        ToleranceType::Absolute => spec.tolerance,
+        ToleranceType::Relative => spec.tolerance * spec.value.abs(),
+    };
+    // Leave room for floating point error in values like 0.1 + 0.2
+    let epsilon = f64::EPSILON * 16.0 * spec.value.abs().max(1.0);
+    if (value - spec.value).abs() > allowed + epsilon {
+        return Err(NumericMismatch::OutOfTolerance);
+    }
+
+    let unit: String = unit.split_whitespace().collect();
+    if unit.is_empty() {
+        if spec.unit_required {
+            return Err(NumericMismatch::MissingUnit);
+        }
+    } else if !spec
+        .units
+        .iter()
+        .any(|accepted| accepted.split_whitespace().collect::<String>() == unit)
+    {
+        return Err(NumericMismatch::WrongUnit);
+    }
+
+    if spec
+        .significant_figures
+        .is_some_and(|expected| significant_figures(number) != expected)
+    {
+        return Err(NumericMismatch::SignificantFigures);
+    }
+
+    Ok(())
+}
+
+/// Grade a `numeric` answer: the full score when it passes every check of
+/// the spec, otherwise nothing and the reason it failed
+pub fn grade_numeric(answer: &str, spec: &NumericAnswer, score: i32) -> (Option<bool>, i32, Option<NumericMismatch>) {
+    match check_numeric(answer, spec) {
+        Ok(()) => (Some(true), score, None),
+        Err(mismatch) => (Some(false), 0, Some(mismatch)),
+    }
+}
+
+/// Share of `score` for `right` out of `total` parts, rounded to the nearest
+/// point. The answer is correct when every part is right.
+fn partial_score(right: usize, total: usize, score: i32) -> (Option<bool>, i32) {
+    if total == 0 {
+        return (None, 0);
+    }
+    (
+        Some(right == total),
+        (score as f64 * right as f64 / total as f64).round() as i32,
+    )
+}
+
+/// Grade a `matching` answer with a share of the score per pair of the key
+/// the student matched. Items compare by their trimmed text; when a left
+/// item is paired more than once the first pair counts.
+pub fn grade_matching(answer: &[MatchingPair], key: &[MatchingPair], score: i32) -> (Option<bool>, i32) {
+    let mut matched: HashMap<&str, &str> = HashMap::new();
+    for pair in answer {
+        matched.entry(pair.left.trim()).or_insert(pair.right.trim());
+    }
+
+    let right = key
+        .iter()
+        .filter(|pair| matched.get(pair.left.trim()) == Some(&pair.right.trim()))
+        .count();
+    partial_score(right, key.len(), score)
+}
+
+/// Grade an `ordering` answer with a share of the score per item in its
+/// correct position
+pub fn grade_ordering(answer: &[String], key: &[String], score: i32) -> (Option<bool>, i32) {
+    let right = key
+        .iter()
+        .zip(answer)
+        .filter(|(expected, given)| expected.trim() == given.trim())
+        .count();
+    partial_score(right, key.len(), score)
+}
+
+/// Grade a `cloze` answer gap by gap. `answers` holds the student's answer
+/// per gap key; a missing or blank answer earns nothing.
+///
+/// The gap scores add up to the question score unless a bank link overrides
+/// it, in which case the total is scaled to `score` and rounded to the
+/// nearest point. The answer is correct when every gap is.
+pub fn grade_cloze(
+    answers: &BTreeMap<String, String>,
+    gaps: &[ClozeGap],
+    score: i32,
+) -> (Option<bool>, i32, Vec<GapResult>) {
+    if gaps.is_empty() {
+        return (None, 0, Vec::new());
+    }
+
+    let results: Vec<GapResult> = gaps
+        .iter()
+        .map(|gap| {
+            let answer = answers.get(&gap.key).map(|answer| answer.trim()).unwrap_or_default();
+            let (is_correct, score_awarded, incorrect_reason) = if answer.is_empty() {
+                (false, 0, None)
+            } else {
+                grade_gap(answer, gap)
+            };
+            GapResult {
+                key: gap.key.clone(),
+                answer: answer.to_string(),
+                is_correct,
+                score_awarded,
+                incorrect_reason,
+            }
+        })
+        .collect();
+
+    let awarded: i32 = results.iter().map(|result| result.score_awarded).sum();
+    let gap_total: i32 = gaps.iter().map(|gap| gap.score).sum();
+    let total = if gap_total > 0 && gap_total != score {
+        (awarded as f64 * score as f64 / gap_total as f64).round() as i32
+    } else {
+        awarded
+    };
+
+    (Some(results.iter().all(|result| result.is_correct)), total, results)
+}
+
+fn grade_gap(answer: &str, gap: &ClozeGap) -> (bool, i32, Option<String>) {
+    match gap.gap_type {
+        GapType::Dropdown => {
+            let is_correct = gap
+                .correct_option
+                .as_deref()
+                .is_some_and(|correct| correct.trim() == answer);
+            (is_correct, if is_correct { gap.score } else { 0 }, None)
+        }
+        GapType::Text => {
+            let (is_correct, score_awarded) = grade_short_answer(answer, &gap.accepted_answers, gap.score);
+            (is_correct == Some(true), score_awarded, None)
+        }
+        GapType::Numeric => match &gap.numeric_answer {
+            Some(spec) => {
+                let (is_correct, score_awarded, mismatch) = grade_numeric(answer, spec, gap.score);
+                (is_correct == Some(true), score_awarded, mismatch.map(|m| m.as_str().to_string()))
+            }
+            None => (false, 0, None),
+        },
+    }
+}
+
+/// Outcome of grading one answer against one revision of a question
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GradedAnswer {
+    pub is_correct: Option<bool>,
+    pub score_awarded: i32,
+    pub incorrect_reason: Option<&'static str>,
+    pub gap_results: Option<Vec<GapResult>>,
+    /// Choices the answer selected, for choice questions
+    pub choice_ids: Option<Vec<Uuid>>,
+}
+
+/// Grade an answer against `question`, worth `score` points in the exam.
+/// Fails when the answer does not fit the question, e.g. a choice of
+/// another question or a response of the wrong kind.
+pub fn grade_answer(question: &QuestionResponse, score: i32, answer: &AnswerSubmission) -> Result<GradedAnswer, String> {
+    let mut graded = GradedAnswer::default();
+    let (is_correct, score_awarded) = match question.question_type {
+        QuestionType::MultipleChoice | QuestionType::TrueFalse => {
+            if question.choices.is_empty() {
+                // Questions without choices, e.g. a bare true/false
+                match &question.correct_answer {
+                    Some(correct) => {
+                        let is_correct = answer.answer_text.trim().eq_ignore_ascii_case(correct.trim());
+                        (Some(is_correct), if is_correct { score } else { 0 })
+                    }
+                    None => (None, 0),
+                }
+            } else {
+                let selected = match answer.choice_ids.as_slice() {
+                    [] => {
+                        // Older clients send the choice text
+                        let text = answer.answer_text.trim();
+                        question
+                            .choices
+                            .iter()
+                            .find(|choice| !text.is_empty() && choice.choice_text.trim().eq_ignore_ascii_case(text))
+                            .map(|choice| choice.id)
+                    }
+                    [choice_id] => {
+                        if !question.choices.iter().any(|choice| choice.id == *choice_id) {
+                            return Err("Choice does not belong to this question".to_string());
+                        }
+                        Some(*choice_id)
+                    }
+                    _ => {
+                        return Err(format!("A {} question is answered with one choice", question.question_type));
+                    }
+                };
+                graded.choice_ids = selected.map(|id| vec![id]);
+
+                let choices: Vec<(Uuid, bool)> =
+                    question.choices.iter().map(|choice| (choice.id, choice.is_correct)).collect();
+                grade_single_choice(selected, &choices, score)
+            }
+        }
+        QuestionType::MultipleSelect => {
+            if answer
+                .choice_ids
+                .iter()
+                .any(|id| !question.choices.iter().any(|choice| choice.id == *id))
+            {
+                return Err("Choice does not belong to this question".to_string());
+            }
+            let correct: Vec<Uuid> = question
+                .choices
+                .iter()
+                .filter(|choice| choice.is_correct)
+                .map(|choice| choice.id)
+                .collect();
+            graded.choice_ids = Some(answer.choice_ids.clone());
+            grade_multiple_select(&answer.choice_ids, &correct, question.scoring_policy, score)
+        }
+        QuestionType::ShortAnswer => grade_short_answer(&answer.answer_text, &question.accepted_answers, score),
+        QuestionType::Numeric => match &question.numeric_answer {
+            Some(spec) => {
+                let (is_correct, score_awarded, mismatch) = grade_numeric(&answer.answer_text, spec, score);
+                graded.incorrect_reason = mismatch.map(|mismatch| mismatch.as_str());
+                (is_correct, score_awarded)
+            }
+            None => (None, 0),
+        },
+        QuestionType::Matching => match &answer.response {
+            Some(StructuredResponse::Matching(pairs)) => grade_matching(pairs, &question.matching_pairs, score),
+            None => grade_matching(&[], &question.matching_pairs, score),
+            Some(_) => return Err("A matching question is answered with a matching response".to_string()),
+        },
+        QuestionType::Ordering => match &answer.response {
+            Some(StructuredResponse::Ordering(items)) => grade_ordering(items, &question.ordering_items, score),
+            None => grade_ordering(&[], &question.ordering_items, score),
+            Some(_) => return Err("An ordering question is answered with an ordering response".to_string()),
+        },
+        QuestionType::Cloze => {
+            let no_answers = BTreeMap::new();
+            let answers = match &answer.response {
+                Some(StructuredResponse::Cloze(answers)) => answers,
+                None => &no_answers,
+                Some(_) => return Err("A cloze question is answered with a cloze response".to_string()),
+            };
+            let (is_correct, score_awarded, results) = grade_cloze(answers, &question.cloze_gaps, score);
+            graded.gap_results = Some(results);
+            (is_correct, score_awarded)
+        }
+        // Essay questions need manual grading
+        QuestionType::Essay => (None, 0),
+    };
+    graded.is_correct = is_correct;
+    graded.score_awarded = score_awarded;
+    Ok(graded)
+}
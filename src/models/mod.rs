@@ -3,4 +3,16 @@ pub mod class;
 pub mod exams;
 pub mod attempt;
 pub mod question;
-pub mod progress;
\ No newline at end of file
+pub mod progress;
+pub mod accommodation;
+pub mod trash;
+pub mod section;
+pub mod prerequisite;
+pub mod access;
+pub mod grade_scale;
+pub mod draw_rule;
+pub mod question_bank;
+pub mod grading;
+pub mod revision;
+pub mod attachment;
+pub mod content;
\ No newline at end of file
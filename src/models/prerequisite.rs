@@ -0,0 +1,65 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+/// Condition that has to be met before a student can start an exam: either
+/// a score on another exam or an achievement from `user_levels.achievements`
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ExamPrerequisite {
+    pub id: Uuid,
+    pub exam_id: Uuid,
+    pub required_exam_id: Option<Uuid>,
+    pub min_score_percent: Option<i32>,
+    pub required_achievement: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl ExamPrerequisite {
+    /// Whether the best submitted score on the required exam is high enough.
+    /// Without a minimum percentage, any submitted attempt counts.
+    pub fn is_score_met(&self, best_score: Option<i32>, max_score: i32) -> bool {
+        let Some(best_score) = best_score else {
+            return false;
+        };
+        let min_percent = self.min_score_percent.unwrap_or(0);
+        if max_score <= 0 {
+            return true;
+        }
+
+        best_score as i64 * 100 >= min_percent as i64 * max_score as i64
+    }
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreatePrerequisiteRequest {
+    pub required_exam_id: Option<Uuid>,
+    #[validate(range(min = 0, max = 100))]
+    pub min_score_percent: Option<i32>,
+    #[validate(length(min = 1))]
+    pub required_achievement: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct PrerequisiteResponse {
+    pub id: Uuid,
+    pub exam_id: Uuid,
+    pub required_exam_id: Option<Uuid>,
+    pub min_score_percent: Option<i32>,
+    pub required_achievement: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<ExamPrerequisite> for PrerequisiteResponse {
+    fn from(prerequisite: ExamPrerequisite) -> Self {
+        Self {
+            id: prerequisite.id,
+            exam_id: prerequisite.exam_id,
+            required_exam_id: prerequisite.required_exam_id,
+            min_score_percent: prerequisite.min_score_percent,
+            required_achievement: prerequisite.required_achievement,
+            created_at: prerequisite.created_at,
+        }
+    }
+}
@@ -1,9 +1,13 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use sqlx::FromRow;
 use uuid::Uuid;
 use validator::Validate;
 
+use crate::models::content::{render_html, ContentFormat};
+use crate::models::revision::ApplyTo;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum QuestionType {
     #[serde(rename = "multiple_choice")]
@@ -12,6 +16,24 @@ pub enum QuestionType {
     Essay,
     #[serde(rename = "true_false")]
     TrueFalse,
+    /// Select all that apply; answered with a set of choice IDs
+    #[serde(rename = "multiple_select")]
+    MultipleSelect,
+    /// Free text graded against a list of accepted answers
+    #[serde(rename = "short_answer")]
+    ShortAnswer,
+    /// A number graded within a tolerance, see `NumericAnswer`
+    #[serde(rename = "numeric")]
+    Numeric,
+    /// Pair each left item with a right item, see `MatchingPair`
+    #[serde(rename = "matching")]
+    Matching,
+    /// Put items in the right sequence
+    #[serde(rename = "ordering")]
+    Ordering,
+    /// A passage with gaps marked `{{key}}` in the question text, see `ClozeGap`
+    #[serde(rename = "cloze")]
+    Cloze,
 }
 
 impl std::fmt::Display for QuestionType {
@@ -20,6 +42,12 @@ impl std::fmt::Display for QuestionType {
             QuestionType::MultipleChoice => write!(f, "multiple_choice"),
             QuestionType::Essay => write!(f, "essay"),
             QuestionType::TrueFalse => write!(f, "true_false"),
+            QuestionType::MultipleSelect => write!(f, "multiple_select"),
+            QuestionType::ShortAnswer => write!(f, "short_answer"),
+            QuestionType::Numeric => write!(f, "numeric"),
+            QuestionType::Matching => write!(f, "matching"),
+            QuestionType::Ordering => write!(f, "ordering"),
+            QuestionType::Cloze => write!(f, "cloze"),
         }
     }
 }
@@ -32,19 +60,426 @@ impl std::str::FromStr for QuestionType {
             "multiple_choice" => Ok(QuestionType::MultipleChoice),
             "essay" => Ok(QuestionType::Essay),
             "true_false" => Ok(QuestionType::TrueFalse),
+            "multiple_select" => Ok(QuestionType::MultipleSelect),
+            "short_answer" => Ok(QuestionType::ShortAnswer),
+            "numeric" => Ok(QuestionType::Numeric),
+            "matching" => Ok(QuestionType::Matching),
+            "ordering" => Ok(QuestionType::Ordering),
+            "cloze" => Ok(QuestionType::Cloze),
             _ => Err(format!("Invalid question type: {}", s)),
         }
     }
 }
 
+/// How a `multiple_select` answer that is not exactly right is scored
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ScoringPolicy {
+    /// Full score for exactly the correct set, nothing otherwise
+    #[default]
+    AllOrNothing,
+    /// A share of the score per correct choice selected, nothing when a
+    /// wrong choice is selected
+    PerCorrect,
+    /// A share of the score per correct choice selected minus one per wrong
+    /// choice selected, never below zero
+    RightMinusWrong,
+}
+
+impl ScoringPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScoringPolicy::AllOrNothing => "all_or_nothing",
+            ScoringPolicy::PerCorrect => "per_correct",
+            ScoringPolicy::RightMinusWrong => "right_minus_wrong",
+        }
+    }
+}
+
+impl std::str::FromStr for ScoringPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "all_or_nothing" => Ok(ScoringPolicy::AllOrNothing),
+            "per_correct" => Ok(ScoringPolicy::PerCorrect),
+            "right_minus_wrong" => Ok(ScoringPolicy::RightMinusWrong),
+            _ => Err(format!("Invalid scoring policy: {}", s)),
+        }
+    }
+}
+
+/// An answer a `short_answer` question accepts, with how it is matched
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct AcceptedAnswer {
+    /// The answer, or a regular expression when `regex` is set
+    #[validate(length(min = 1))]
+    pub text: String,
+    /// Share of the question score this answer earns
+    #[serde(default = "full_score_percent")]
+    #[validate(range(min = 0, max = 100))]
+    pub score_percent: i32,
+    #[serde(default)]
+    pub case_sensitive: bool,
+    /// Compare without any whitespace instead of only collapsing runs of it
+    #[serde(default)]
+    pub ignore_whitespace: bool,
+    /// Treat "é" as "e"
+    #[serde(default)]
+    pub ignore_diacritics: bool,
+    /// `text` is a regular expression the whole answer has to match
+    #[serde(default)]
+    pub regex: bool,
+    /// Typos still accepted, as a Levenshtein distance
+    #[serde(default)]
+    #[validate(range(min = 0, max = 10))]
+    pub max_distance: i32,
+}
+
+fn full_score_percent() -> i32 {
+    100
+}
+
+/// Reject accepted answers a question cannot be graded with
+pub fn check_accepted_answers(question_type: &QuestionType, answers: &[AcceptedAnswer]) -> Result<(), String> {
+    if matches!(question_type, QuestionType::ShortAnswer) && answers.is_empty() {
+        return Err("A short_answer question needs at least one accepted answer".to_string());
+    }
+    for answer in answers {
+        if answer.regex {
+            if answer.max_distance > 0 {
+                return Err("A regex answer cannot have a max_distance".to_string());
+            }
+            regex::Regex::new(&answer.text).map_err(|e| format!("Invalid regex '{}': {}", answer.text, e))?;
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ToleranceType {
+    /// The answer may differ from the value by `tolerance`
+    #[default]
+    Absolute,
+    /// The answer may differ from the value by `tolerance` times the value,
+    /// e.g. 0.01 for 1%
+    Relative,
+}
+
+/// Expected answer of a `numeric` question
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct NumericAnswer {
+    pub value: f64,
+    #[serde(default)]
+    #[validate(range(min = 0.0))]
+    pub tolerance: f64,
+    #[serde(default)]
+    pub tolerance_type: ToleranceType,
+    /// Number of significant figures the answer has to be given with
+    #[validate(range(min = 1, max = 15))]
+    pub significant_figures: Option<u32>,
+    /// Units the answer may be given in, e.g. "m/s^2". Whitespace is ignored.
+    #[serde(default)]
+    pub units: Vec<String>,
+    /// Reject an answer without one of `units`
+    #[serde(default)]
+    pub unit_required: bool,
+}
+
+/// Reject a numeric question without an answer spec
+pub fn check_numeric_answer(question_type: &QuestionType, answer: Option<&NumericAnswer>) -> Result<(), String> {
+    match (question_type, answer) {
+        (QuestionType::Numeric, None) => Err("A numeric question needs a numeric_answer".to_string()),
+        (_, Some(answer)) if answer.unit_required && answer.units.is_empty() => {
+            Err("unit_required needs at least one unit".to_string())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// A left item and the right item it belongs with. The key of a `matching`
+/// question and, from students, one pair of their answer.
+#[derive(Debug, Clone, Serialize, Deserialize, Validate, PartialEq)]
+pub struct MatchingPair {
+    #[validate(length(min = 1))]
+    pub left: String,
+    #[validate(length(min = 1))]
+    pub right: String,
+}
+
+/// Answer to a `matching` or `ordering` question, sent instead of `answer_text`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum StructuredResponse {
+    /// The right item the student paired with each left item
+    Matching(Vec<MatchingPair>),
+    /// The items in the order the student put them
+    Ordering(Vec<String>),
+    /// The answer to each gap of a cloze question by gap key
+    Cloze(BTreeMap<String, String>),
+}
+
+/// Reject a matching or ordering question without a usable key. Items are
+/// identified by their text, so they have to be unique.
+pub fn check_structured_key(
+    question_type: &QuestionType,
+    matching_pairs: &[MatchingPair],
+    ordering_items: &[String],
+) -> Result<(), String> {
+    fn has_duplicates<'a>(items: impl Iterator<Item = &'a str>) -> bool {
+        let mut seen = std::collections::HashSet::new();
+        !items.into_iter().all(|item| seen.insert(item.trim()))
+    }
+
+    match question_type {
+        QuestionType::Matching => {
+            if matching_pairs.len() < 2 {
+                return Err("A matching question needs at least two matching_pairs".to_string());
+            }
+            if has_duplicates(matching_pairs.iter().map(|pair| pair.left.as_str()))
+                || has_duplicates(matching_pairs.iter().map(|pair| pair.right.as_str()))
+            {
+                return Err("Matching items must be unique on each side".to_string());
+            }
+        }
+        QuestionType::Ordering => {
+            if ordering_items.len() < 2 {
+                return Err("An ordering question needs at least two ordering_items".to_string());
+            }
+            if ordering_items.iter().any(|item| item.trim().is_empty()) {
+                return Err("Ordering items cannot be empty".to_string());
+            }
+            if has_duplicates(ordering_items.iter().map(String::as_str)) {
+                return Err("Ordering items must be unique".to_string());
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum GapType {
+    /// Pick one of `options`; right when it is `correct_option`
+    Dropdown,
+    /// Free text graded like a short_answer question by `accepted_answers`
+    #[default]
+    Text,
+    /// A number graded like a numeric question by `numeric_answer`
+    Numeric,
+}
+
+/// One gap of a `cloze` question. It is placed in the question text with
+/// `{{key}}` and earns its own `score`.
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct ClozeGap {
+    #[validate(length(min = 1))]
+    pub key: String,
+    #[serde(default)]
+    pub gap_type: GapType,
+    #[serde(default = "default_gap_score")]
+    #[validate(range(min = 1))]
+    pub score: i32,
+    /// Options of a dropdown gap
+    #[serde(default)]
+    pub options: Vec<String>,
+    pub correct_option: Option<String>,
+    #[serde(default)]
+    #[validate(nested)]
+    pub accepted_answers: Vec<AcceptedAnswer>,
+    #[validate(nested)]
+    pub numeric_answer: Option<NumericAnswer>,
+}
+
+fn default_gap_score() -> i32 {
+    1
+}
+
+/// Keys of the `{{key}}` gap markers in a cloze question text, in order.
+/// Keys are letters, digits, `_` and `-`.
+pub fn cloze_gap_keys(question_text: &str) -> Vec<&str> {
+    let marker = regex::Regex::new(r"\{\{\s*([A-Za-z0-9_-]+)\s*\}\}").unwrap();
+    marker
+        .captures_iter(question_text)
+        .filter_map(|captures| captures.get(1))
+        .map(|key| key.as_str())
+        .collect()
+}
+
+/// Reject a cloze question whose gaps do not line up with the markers in its
+/// text, lack an answer spec, or do not add up to the question score
+pub fn check_cloze(question_type: &QuestionType, question_text: &str, score: i32, gaps: &[ClozeGap]) -> Result<(), String> {
+    if !matches!(question_type, QuestionType::Cloze) {
+        return Ok(());
+    }
+    if gaps.is_empty() {
+        return Err("A cloze question needs at least one gap".to_string());
+    }
+
+    let markers = cloze_gap_keys(question_text);
+    let mut keys = std::collections::HashSet::new();
+    for gap in gaps {
+        if !keys.insert(gap.key.as_str()) {
+            return Err(format!("Gap '{}' is defined more than once", gap.key));
+        }
+        match markers.iter().filter(|marker| **marker == gap.key).count() {
+            0 => return Err(format!("Gap '{}' has no {{{{{}}}}} marker in the question text", gap.key, gap.key)),
+            1 => {}
+            _ => return Err(format!("Gap '{}' is marked more than once in the question text", gap.key)),
+        }
+        check_cloze_gap(gap).map_err(|e| format!("Gap '{}': {}", gap.key, e))?;
+    }
+    if let Some(marker) = markers.iter().find(|marker| !keys.contains(*marker)) {
+        return Err(format!("The question text marks gap '{}' which is not defined", marker));
+    }
+
+    let gap_total: i32 = gaps.iter().map(|gap| gap.score).sum();
+    if gap_total != score {
+        return Err(format!(
+            "The gap scores add up to {} but the question score is {}",
+            gap_total, score
+        ));
+    }
+    Ok(())
+}
+
+fn check_cloze_gap(gap: &ClozeGap) -> Result<(), String> {
+    match gap.gap_type {
+        GapType::Dropdown => {
+            if gap.options.len() < 2 {
+                return Err("A dropdown gap needs at least two options".to_string());
+            }
+            let mut seen = std::collections::HashSet::new();
+            if !gap.options.iter().all(|option| seen.insert(option.trim())) {
+                return Err("Dropdown options must be unique".to_string());
+            }
+            match &gap.correct_option {
+                Some(correct) if gap.options.iter().any(|option| option.trim() == correct.trim()) => Ok(()),
+                _ => Err("A dropdown gap needs a correct_option that is one of its options".to_string()),
+            }
+        }
+        GapType::Text => {
+            if gap.accepted_answers.is_empty() {
+                return Err("A text gap needs at least one accepted answer".to_string());
+            }
+            check_accepted_answers(&QuestionType::Cloze, &gap.accepted_answers)
+        }
+        GapType::Numeric => match &gap.numeric_answer {
+            Some(spec) => check_numeric_answer(&QuestionType::Cloze, Some(spec)),
+            None => Err("A numeric gap needs a numeric_answer".to_string()),
+        },
+    }
+}
+
+/// A cloze gap as students see it, without its answer
+#[derive(Debug, Serialize, Clone)]
+pub struct ClozeGapForStudentResponse {
+    pub key: String,
+    pub gap_type: GapType,
+    pub score: i32,
+    pub options: Vec<String>,
+}
+
+impl From<ClozeGap> for ClozeGapForStudentResponse {
+    fn from(gap: ClozeGap) -> Self {
+        Self {
+            key: gap.key,
+            gap_type: gap.gap_type,
+            score: gap.score,
+            options: gap.options,
+        }
+    }
+}
+
+/// Reject a question whose choices it cannot be graded with. `choices` are
+/// the text and `is_correct` of each choice. A multiple_choice or true_false
+/// question with choices needs exactly one correct choice, and a
+/// `correct_answer` given with it has to name that choice.
+pub fn check_choices(
+    question_type: &QuestionType,
+    correct_answer: Option<&str>,
+    choices: &[(&str, bool)],
+) -> Result<(), String> {
+    let correct: Vec<&str> = choices
+        .iter()
+        .filter(|(_, is_correct)| *is_correct)
+        .map(|(text, _)| *text)
+        .collect();
+
+    match question_type {
+        QuestionType::MultipleSelect if correct.is_empty() => {
+            Err("A multiple_select question needs at least one correct choice".to_string())
+        }
+        QuestionType::MultipleChoice | QuestionType::TrueFalse if !choices.is_empty() => {
+            if correct.len() != 1 {
+                return Err(format!(
+                    "A {} question needs exactly one correct choice, found {}",
+                    question_type,
+                    correct.len()
+                ));
+            }
+            if let Some(answer) = correct_answer
+                && !answer.trim().eq_ignore_ascii_case(correct[0].trim())
+            {
+                return Err(format!(
+                    "correct_answer '{}' does not match the correct choice '{}'",
+                    answer, correct[0]
+                ));
+            }
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// A multiple_choice or true_false question whose choices and
+/// `correct_answer` disagree
+#[derive(Debug, Serialize, Clone)]
+pub struct InconsistentQuestion {
+    pub question_id: Uuid,
+    pub exam_id: Option<Uuid>,
+    pub question_text: String,
+    pub question_type: String,
+    pub correct_answer: Option<String>,
+    pub correct_choices: i64,
+    /// `no_correct_choice`, `multiple_correct_choices` or `correct_answer_mismatch`
+    pub issue: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Question {
     pub id: Uuid,
-    pub exam_id: Uuid,
+    /// `None` for questions in a teacher's question bank
+    pub exam_id: Option<Uuid>,
+    pub section_id: Option<Uuid>,
     pub question_text: String,
     pub question_type: String, // Will be converted to/from QuestionType
+    pub content_format: ContentFormat,
     pub correct_answer: Option<String>,
     pub score: i32,
+    pub scoring_policy: ScoringPolicy,
+    pub accepted_answers: Vec<AcceptedAnswer>,
+    pub numeric_answer: Option<NumericAnswer>,
+    pub matching_pairs: Vec<MatchingPair>,
+    /// Items of an ordering question in the correct order
+    pub ordering_items: Vec<String>,
+    pub cloze_gaps: Vec<ClozeGap>,
+    /// Shown to students after the exam according to its review policy
+    pub explanation: Option<String>,
+    /// Media shown with the question
+    pub attachment_ids: Vec<Uuid>,
+    /// Pool label that draw rules select on
+    pub pool: Option<String>,
+    pub difficulty: Option<String>,
+    /// Teacher whose question bank the question is in
+    pub owner_id: Option<Uuid>,
+    pub tags: Vec<String>,
+    pub topic: Option<String>,
+    /// Linked into the exam from the question bank rather than owned by it
+    pub from_bank: bool,
+    pub revision: i32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -53,6 +488,11 @@ pub struct Choice {
     pub question_id: Uuid,
     pub choice_text: String,
     pub is_correct: bool,
+    /// Stays in place when choices are shuffled, e.g. "All of the above"
+    pub pin_to_bottom: bool,
+    /// Why this choice is right or wrong, shown to students who picked it
+    pub feedback: Option<String>,
+    pub attachment_ids: Vec<Uuid>,
     pub created_at: DateTime<Utc>,
 }
 
@@ -61,10 +501,50 @@ pub struct CreateQuestionRequest {
     #[validate(length(min = 1))]
     pub question_text: String,
     pub question_type: QuestionType,
+    /// How `question_text` and the choice texts are written
+    #[serde(default)]
+    pub content_format: ContentFormat,
     pub correct_answer: Option<String>,
     #[validate(range(min = 1))]
     pub score: i32,
+    /// Only used by `multiple_select` questions
+    #[serde(default)]
+    pub scoring_policy: ScoringPolicy,
+    /// Required for short_answer questions
+    #[serde(default)]
+    #[validate(nested)]
+    pub accepted_answers: Vec<AcceptedAnswer>,
+    /// Required for numeric questions, which ignore `correct_answer`
+    #[validate(nested)]
+    pub numeric_answer: Option<NumericAnswer>,
+    /// Required for matching questions
+    #[serde(default)]
+    #[validate(nested)]
+    pub matching_pairs: Vec<MatchingPair>,
+    /// Required for ordering questions, in the correct order
+    #[serde(default)]
+    pub ordering_items: Vec<String>,
+    /// Required for cloze questions, one per `{{key}}` marker
+    #[serde(default)]
+    #[validate(nested)]
+    pub cloze_gaps: Vec<ClozeGap>,
+    /// Explanation of the answer, released to students by the exam's review policy
+    #[validate(length(min = 1))]
+    pub explanation: Option<String>,
+    /// Uploaded attachments to show with the question
+    #[serde(default)]
+    pub attachment_ids: Vec<Uuid>,
     pub choices: Option<Vec<CreateChoiceRequest>>,
+    /// Section of the exam the question belongs to
+    pub section_id: Option<Uuid>,
+    #[validate(length(min = 1))]
+    pub pool: Option<String>,
+    #[validate(length(min = 1))]
+    pub difficulty: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[validate(length(min = 1))]
+    pub topic: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Validate)]
@@ -72,6 +552,13 @@ pub struct CreateChoiceRequest {
     #[validate(length(min = 1))]
     pub choice_text: String,
     pub is_correct: bool,
+    #[serde(default)]
+    pub pin_to_bottom: bool,
+    /// Feedback for students who pick this choice
+    #[validate(length(min = 1))]
+    pub feedback: Option<String>,
+    #[serde(default)]
+    pub attachment_ids: Vec<Uuid>,
 }
 
 #[derive(Debug, Deserialize, Validate)]
@@ -79,10 +566,45 @@ pub struct UpdateQuestionRequest {
     #[validate(length(min = 1))]
     pub question_text: String,
     pub question_type: QuestionType,
+    #[serde(default)]
+    pub content_format: ContentFormat,
     pub correct_answer: Option<String>,
     #[validate(range(min = 1))]
     pub score: i32,
+    #[serde(default)]
+    pub scoring_policy: ScoringPolicy,
+    #[serde(default)]
+    #[validate(nested)]
+    pub accepted_answers: Vec<AcceptedAnswer>,
+    #[validate(nested)]
+    pub numeric_answer: Option<NumericAnswer>,
+    #[serde(default)]
+    #[validate(nested)]
+    pub matching_pairs: Vec<MatchingPair>,
+    #[serde(default)]
+    pub ordering_items: Vec<String>,
+    #[serde(default)]
+    #[validate(nested)]
+    pub cloze_gaps: Vec<ClozeGap>,
+    /// Omitting the explanation removes it
+    #[validate(length(min = 1))]
+    pub explanation: Option<String>,
+    /// Omitting the attachments removes them
+    #[serde(default)]
+    pub attachment_ids: Vec<Uuid>,
     pub choices: Option<Vec<UpdateChoiceRequest>>,
+    /// Whether attempts already started move to the edited question
+    #[serde(default)]
+    pub apply_to: ApplyTo,
+    pub section_id: Option<Uuid>,
+    #[validate(length(min = 1))]
+    pub pool: Option<String>,
+    #[validate(length(min = 1))]
+    pub difficulty: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[validate(length(min = 1))]
+    pub topic: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Validate)]
@@ -91,26 +613,106 @@ pub struct UpdateChoiceRequest {
     #[validate(length(min = 1))]
     pub choice_text: String,
     pub is_correct: bool,
+    #[serde(default)]
+    pub pin_to_bottom: bool,
+    #[validate(length(min = 1))]
+    pub feedback: Option<String>,
+    #[serde(default)]
+    pub attachment_ids: Vec<Uuid>,
     pub delete: Option<bool>, // True to delete this choice
 }
 
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct QuestionResponse {
     pub id: Uuid,
-    pub exam_id: Uuid,
+    pub exam_id: Option<Uuid>,
+    pub section_id: Option<Uuid>,
     pub question_text: String,
     pub question_type: QuestionType,
+    /// Missing from revisions recorded before content formats existed
+    #[serde(default)]
+    pub content_format: ContentFormat,
+    /// `question_text` rendered and sanitized, see `render_html`
+    #[serde(default)]
+    pub question_html: String,
     pub correct_answer: Option<String>,
     pub score: i32,
+    pub scoring_policy: ScoringPolicy,
+    pub accepted_answers: Vec<AcceptedAnswer>,
+    pub numeric_answer: Option<NumericAnswer>,
+    pub matching_pairs: Vec<MatchingPair>,
+    pub ordering_items: Vec<String>,
+    pub cloze_gaps: Vec<ClozeGap>,
+    pub explanation: Option<String>,
+    /// Missing from revisions recorded before attachments existed
+    #[serde(default)]
+    pub attachment_ids: Vec<Uuid>,
+    pub pool: Option<String>,
+    pub difficulty: Option<String>,
+    pub tags: Vec<String>,
+    pub topic: Option<String>,
+    /// Editing a bank question changes it in every exam it is linked into
+    pub from_bank: bool,
+    /// Current revision; every edit adds one
+    pub revision: i32,
     pub choices: Vec<ChoiceResponse>,
 }
 
-#[derive(Debug, Serialize, Clone)]
+impl QuestionResponse {
+    pub fn new(question: Question, choices: Vec<ChoiceResponse>) -> Self {
+        let mut response = Self {
+            id: question.id,
+            exam_id: question.exam_id,
+            section_id: question.section_id,
+            question_text: question.question_text,
+            question_type: question.question_type.parse().unwrap_or(QuestionType::Essay),
+            content_format: question.content_format,
+            question_html: String::new(),
+            correct_answer: question.correct_answer,
+            score: question.score,
+            scoring_policy: question.scoring_policy,
+            accepted_answers: question.accepted_answers,
+            numeric_answer: question.numeric_answer,
+            matching_pairs: question.matching_pairs,
+            ordering_items: question.ordering_items,
+            cloze_gaps: question.cloze_gaps,
+            explanation: question.explanation,
+            attachment_ids: question.attachment_ids,
+            pool: question.pool,
+            difficulty: question.difficulty,
+            tags: question.tags,
+            topic: question.topic,
+            from_bank: question.from_bank,
+            revision: question.revision,
+            choices,
+        };
+        response.render_html();
+        response
+    }
+
+    /// Fill in `question_html` and the `choice_html` of every choice from
+    /// their source text
+    pub fn render_html(&mut self) {
+        self.question_html = render_html(self.content_format, &self.question_text);
+        for choice in &mut self.choices {
+            choice.choice_html = render_html(self.content_format, &choice.choice_text);
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ChoiceResponse {
     pub id: Uuid,
     pub question_id: Uuid,
     pub choice_text: String,
+    /// Rendered in the question's content format
+    #[serde(default)]
+    pub choice_html: String,
     pub is_correct: bool,
+    pub pin_to_bottom: bool,
+    pub feedback: Option<String>,
+    #[serde(default)]
+    pub attachment_ids: Vec<Uuid>,
     pub created_at: DateTime<Utc>,
 }
 
@@ -120,7 +722,11 @@ impl From<Choice> for ChoiceResponse {
             id: choice.id,
             question_id: choice.question_id,
             choice_text: choice.choice_text,
+            choice_html: String::new(),
             is_correct: choice.is_correct,
+            pin_to_bottom: choice.pin_to_bottom,
+            feedback: choice.feedback,
+            attachment_ids: choice.attachment_ids,
             created_at: choice.created_at,
         }
     }
@@ -130,25 +736,32 @@ impl From<Choice> for ChoiceResponse {
 pub struct QuestionForStudentResponse {
     pub id: Uuid,
     pub exam_id: Uuid,
+    pub section_id: Option<Uuid>,
     pub question_text: String,
     pub question_type: QuestionType,
+    pub content_format: ContentFormat,
+    /// Render this rather than `question_text`; it is already sanitized
+    pub question_html: String,
     pub score: i32,
+    pub scoring_policy: ScoringPolicy,
     pub choices: Vec<ChoiceForStudentResponse>,
+    /// Left items of a matching question, in the order of the key
+    pub matching_left: Vec<String>,
+    /// Right items of a matching question, shuffled
+    pub matching_right: Vec<String>,
+    /// Items of an ordering question, shuffled
+    pub ordering_items: Vec<String>,
+    pub cloze_gaps: Vec<ClozeGapForStudentResponse>,
+    /// Fetch download URLs for these while the attempt is in progress
+    pub attachment_ids: Vec<Uuid>,
 }
 
 #[derive(Debug, Serialize, Clone)]
 pub struct ChoiceForStudentResponse {
     pub id: Uuid,
     pub choice_text: String,
-}
-
-impl From<Choice> for ChoiceForStudentResponse {
-    fn from(choice: Choice) -> Self {
-        Self {
-            id: choice.id,
-            choice_text: choice.choice_text,
-        }
-    }
+    pub choice_html: String,
+    pub attachment_ids: Vec<Uuid>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -0,0 +1,66 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::models::question::QuestionResponse;
+
+/// Query parameters for searching a question bank, used together with `Pagination`
+#[derive(Debug, Default, Deserialize)]
+pub struct BankQuestionFilter {
+    pub tag: Option<String>,
+    pub topic: Option<String>,
+    pub difficulty: Option<String>,
+    /// Case-insensitive search in the question text
+    pub q: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct BankQuestionResponse {
+    #[serde(flatten)]
+    pub question: QuestionResponse,
+    pub owner_id: Option<Uuid>,
+    /// Number of exams the question is linked into
+    pub used_in_exams: i64,
+}
+
+/// Link a bank question into an exam. Without `score_override` the
+/// question's own score counts.
+#[derive(Debug, Deserialize, Validate)]
+pub struct LinkBankQuestionRequest {
+    pub question_id: Uuid,
+    pub section_id: Option<Uuid>,
+    #[validate(range(min = 1))]
+    pub score_override: Option<i32>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateBankLinkRequest {
+    pub section_id: Option<Uuid>,
+    #[validate(range(min = 1))]
+    pub score_override: Option<i32>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct BankLinkResponse {
+    pub exam_id: Uuid,
+    pub question_id: Uuid,
+    pub section_id: Option<Uuid>,
+    pub score_override: Option<i32>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// One exam a bank question is linked into
+#[derive(Debug, Serialize, Clone)]
+pub struct QuestionUsage {
+    pub exam_id: Uuid,
+    pub exam_title: String,
+    pub is_template: bool,
+    pub section_id: Option<Uuid>,
+    pub score_override: Option<i32>,
+    /// Score the question is worth in this exam
+    pub score: i32,
+    /// Answers given to the question in attempts at this exam
+    pub answers: i64,
+    pub linked_at: DateTime<Utc>,
+}
@@ -0,0 +1,136 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::models::question::QuestionResponse;
+
+/// A question as it was at one revision. Revisions are never changed;
+/// editing a question adds a new one.
+#[derive(Debug, Clone, Serialize)]
+pub struct QuestionRevision {
+    pub question_id: Uuid,
+    pub revision: i32,
+    pub question: QuestionResponse,
+    /// Who made the edit; unknown for the revision a question was created with
+    pub created_by: Option<Uuid>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct QuestionRevisionSummary {
+    pub revision: i32,
+    pub created_by: Option<Uuid>,
+    pub created_at: Option<DateTime<Utc>>,
+    /// Attempts that were pinned to this revision
+    pub attempt_count: i64,
+}
+
+/// Which attempts an edit applies to
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ApplyTo {
+    /// Attempts already started keep the revision they saw
+    #[default]
+    FutureAttempts,
+    /// Move every attempt to the new revision and regrade submitted ones
+    Regrade,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RevisionDiffQuery {
+    pub from: i32,
+    pub to: i32,
+}
+
+/// One field that differs between two revisions
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct FieldChange {
+    pub field: String,
+    pub from: Value,
+    pub to: Value,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RevisionDiff {
+    pub question_id: Uuid,
+    pub from: i32,
+    pub to: i32,
+    pub changes: Vec<FieldChange>,
+}
+
+/// Bookkeeping fields that are not part of a question's content,
+/// or are derived from other fields
+const IGNORED_FIELDS: [&str; 4] = ["id", "revision", "from_bank", "question_html"];
+
+/// Fields that differ between two revisions of a question, sorted by name.
+/// Choices are compared by ID, so each added, removed or edited choice is
+/// reported on its own as `choices[<id>]`.
+pub fn diff_revisions(from: &QuestionResponse, to: &QuestionResponse) -> Vec<FieldChange> {
+    let (Value::Object(from), Value::Object(to)) = (
+        serde_json::to_value(from).unwrap_or_default(),
+        serde_json::to_value(to).unwrap_or_default(),
+    ) else {
+        return Vec::new();
+    };
+
+    let mut changes = Vec::new();
+    for (field, from_value) in &from {
+        if IGNORED_FIELDS.contains(&field.as_str()) {
+            continue;
+        }
+        let to_value = to.get(field).cloned().unwrap_or(Value::Null);
+        if field == "choices" {
+            changes.extend(diff_choices(from_value, &to_value));
+        } else if *from_value != to_value {
+            changes.push(FieldChange {
+                field: field.clone(),
+                from: from_value.clone(),
+                to: to_value,
+            });
+        }
+    }
+    changes
+}
+
+fn diff_choices(from: &Value, to: &Value) -> Vec<FieldChange> {
+    let by_id = |choices: &Value| -> Vec<(String, Value)> {
+        choices
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|choice| {
+                let id = choice.get("id").and_then(Value::as_str).unwrap_or_default().to_string();
+                let mut choice = choice.clone();
+                if let Some(fields) = choice.as_object_mut() {
+                    fields.remove("choice_html");
+                }
+                (id, choice)
+            })
+            .collect()
+    };
+    let from = by_id(from);
+    let to = by_id(to);
+
+    let mut changes = Vec::new();
+    for (id, from_choice) in &from {
+        let to_choice = to.iter().find(|(to_id, _)| to_id == id).map(|(_, choice)| choice.clone());
+        if to_choice.as_ref() != Some(from_choice) {
+            changes.push(FieldChange {
+                field: format!("choices[{}]", id),
+                from: from_choice.clone(),
+                to: to_choice.unwrap_or(Value::Null),
+            });
+        }
+    }
+    for (id, to_choice) in &to {
+        if !from.iter().any(|(from_id, _)| from_id == id) {
+            changes.push(FieldChange {
+                field: format!("choices[{}]", id),
+                from: Value::Null,
+                to: to_choice.clone(),
+            });
+        }
+    }
+    changes
+}
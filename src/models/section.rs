@@ -0,0 +1,152 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::models::question::QuestionForStudentResponse;
+
+/// Ordered part of an exam, e.g. "Part A: multiple choice, 20 minutes"
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ExamSection {
+    pub id: Uuid,
+    pub exam_id: Uuid,
+    pub title: String,
+    pub instructions: Option<String>,
+    pub position: i32,
+    pub time_limit_minutes: Option<i32>,
+    /// When set, each student gets this many questions drawn from the section
+    pub draw_count: Option<i32>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl ExamSection {
+    /// Time by which a section started at `started_at` has to be finished
+    pub fn deadline(&self, started_at: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.time_limit_minutes
+            .map(|minutes| started_at + Duration::minutes(minutes as i64))
+    }
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateSectionRequest {
+    #[validate(length(min = 1))]
+    pub title: String,
+    pub instructions: Option<String>,
+    /// Defaults to the end of the exam
+    pub position: Option<i32>,
+    #[validate(range(min = 1))]
+    pub time_limit_minutes: Option<i32>,
+    #[validate(range(min = 1))]
+    pub draw_count: Option<i32>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateSectionRequest {
+    #[validate(length(min = 1))]
+    pub title: String,
+    pub instructions: Option<String>,
+    pub position: i32,
+    #[validate(range(min = 1))]
+    pub time_limit_minutes: Option<i32>,
+    #[validate(range(min = 1))]
+    pub draw_count: Option<i32>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct SectionResponse {
+    pub id: Uuid,
+    pub exam_id: Uuid,
+    pub title: String,
+    pub instructions: Option<String>,
+    pub position: i32,
+    pub time_limit_minutes: Option<i32>,
+    pub draw_count: Option<i32>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<ExamSection> for SectionResponse {
+    fn from(section: ExamSection) -> Self {
+        Self {
+            id: section.id,
+            exam_id: section.exam_id,
+            title: section.title,
+            instructions: section.instructions,
+            position: section.position,
+            time_limit_minutes: section.time_limit_minutes,
+            draw_count: section.draw_count,
+            created_at: section.created_at,
+        }
+    }
+}
+
+/// Section of the student view with the questions drawn for that student
+#[derive(Debug, Serialize, Clone)]
+pub struct StudentSectionResponse {
+    pub id: Uuid,
+    pub title: String,
+    pub instructions: Option<String>,
+    pub position: i32,
+    pub time_limit_minutes: Option<i32>,
+    pub questions: Vec<QuestionForStudentResponse>,
+}
+
+impl StudentSectionResponse {
+    pub fn new(section: ExamSection, questions: Vec<QuestionForStudentResponse>) -> Self {
+        Self {
+            id: section.id,
+            title: section.title,
+            instructions: section.instructions,
+            position: section.position,
+            time_limit_minutes: section.time_limit_minutes,
+            questions,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct SectionedQuestionsResponse {
+    pub sections: Vec<StudentSectionResponse>,
+    /// Questions that do not belong to any section
+    pub questions: Vec<QuestionForStudentResponse>,
+}
+
+/// Start and finish time of a section within an attempt
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct AttemptSection {
+    pub attempt_id: Uuid,
+    pub section_id: Uuid,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+impl AttemptSection {
+    /// Whether questions of the section can still be answered: it has not
+    /// been finished and its time limit has not passed
+    pub fn accepts_answers(&self, section: &ExamSection, now: DateTime<Utc>) -> bool {
+        self.finished_at.is_none() && section.deadline(self.started_at).is_none_or(|deadline| now <= deadline)
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct AttemptSectionResponse {
+    pub attempt_id: Uuid,
+    pub section_id: Uuid,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    /// Time by which the section must be finished, when it has a time limit
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deadline: Option<DateTime<Utc>>,
+}
+
+impl AttemptSectionResponse {
+    pub fn new(attempt_section: AttemptSection, deadline: Option<DateTime<Utc>>) -> Self {
+        Self {
+            attempt_id: attempt_section.attempt_id,
+            section_id: attempt_section.section_id,
+            started_at: attempt_section.started_at,
+            finished_at: attempt_section.finished_at,
+            deadline,
+        }
+    }
+}
@@ -0,0 +1,43 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Kinds of records that are soft deleted and can be restored from the trash
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TrashItemType {
+    Exams,
+    Questions,
+    Classes,
+}
+
+impl TrashItemType {
+    pub fn table(&self) -> &'static str {
+        match self {
+            TrashItemType::Exams => "exams",
+            TrashItemType::Questions => "questions",
+            TrashItemType::Classes => "classes",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct TrashedItem {
+    pub item_type: TrashItemType,
+    pub id: Uuid,
+    /// Exam title, question text or class name
+    pub title: String,
+    pub deleted_at: DateTime<Utc>,
+    pub deleted_by: Option<Uuid>,
+}
+
+/// Number of rows permanently removed by a purge run
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct PurgeSummary {
+    pub exams: u64,
+    pub questions: u64,
+    pub classes: u64,
+    /// Expired exams kept in the trash because an exam that is not purged
+    /// has them as a prerequisite
+    pub kept_exams: Vec<Uuid>,
+}
@@ -104,5 +104,6 @@ pub struct ChangePasswordRequest {
 
 #[derive(Debug, Deserialize)]
 pub struct VerifyEmailRequest {
+    #[allow(dead_code)]
     pub token: String,
 }
\ No newline at end of file
@@ -1,8 +1,9 @@
-use crate::handlers::{auth::AuthHandlers, class::ClassHandlers, user::UserHandlers, exam::ExamHandlers, exam_attempt::ExamAttemptHandler, question::QuestionHandler, progress::ProgressHandler};
+use crate::handlers::{auth::AuthHandlers, class::ClassHandlers, user::UserHandlers, exam::ExamHandlers, exam_attempt::ExamAttemptHandler, question::QuestionHandler, progress::ProgressHandler, accommodation::AccommodationHandler, trash::TrashHandler, section::SectionHandler, prerequisite::PrerequisiteHandler, access::ExamAccessHandler, grade_scale::GradeScaleHandler, draw_rule::DrawRuleHandler, question_bank::QuestionBankHandler, attachment::AttachmentHandler};
 use crate::middleware::auth::auth_middleware;
 use crate::{AppState};
 use axum::{
     Router,
+    extract::DefaultBodyLimit,
     routing::{get, post},
 };
 use axum::routing::{delete, put};
@@ -30,6 +31,11 @@ fn api_routes(state: AppState) -> Router<AppState> {
         .nest("/exam-attempts", exam_attempts_routes(state.clone()))
         .nest("/questions", questions_routes(state.clone()))
         .nest("/progress", progress_routes(state.clone()))
+        .nest("/accommodations", accommodations_routes(state.clone()))
+        .nest("/trash", trash_routes(state.clone()))
+        .nest("/grade-scales", grade_scales_routes(state.clone()))
+        .nest("/question-bank", question_bank_routes(state.clone()))
+        .nest("/attachments", attachments_routes(state.clone()))
 }
 
 fn auth_routes() -> Router<AppState> {
@@ -75,6 +81,26 @@ fn exams_routes(state: AppState) -> Router<AppState> {
         .route("/{exam_id}", get(ExamHandlers::get_exam_by_id))
         .route("/{exam_id}", put(ExamHandlers::update_exam))
         .route("/{exam_id}", delete(ExamHandlers::delete_exam))
+        .route("/{exam_id}/clone", post(ExamHandlers::clone_exam))
+        .route("/{exam_id}/sections", post(SectionHandler::create_section))
+        .route("/{exam_id}/sections", get(SectionHandler::get_sections))
+        .route("/sections/{section_id}", put(SectionHandler::update_section))
+        .route("/sections/{section_id}", delete(SectionHandler::delete_section))
+        .route("/{exam_id}/prerequisites", post(PrerequisiteHandler::create_prerequisite))
+        .route("/{exam_id}/prerequisites", get(PrerequisiteHandler::get_prerequisites))
+        .route("/prerequisites/{prerequisite_id}", delete(PrerequisiteHandler::delete_prerequisite))
+        .route("/{exam_id}/draw-rules", post(DrawRuleHandler::create_draw_rule))
+        .route("/{exam_id}/draw-rules", get(DrawRuleHandler::get_draw_rules))
+        .route("/draw-rules/{rule_id}", delete(DrawRuleHandler::delete_draw_rule))
+        .route("/{exam_id}/bank-questions", post(QuestionBankHandler::link_bank_question))
+        .route("/{exam_id}/bank-questions/{question_id}", put(QuestionBankHandler::update_bank_link))
+        .route("/{exam_id}/bank-questions/{question_id}", delete(QuestionBankHandler::unlink_bank_question))
+        .route("/{exam_id}/access", get(ExamAccessHandler::get_access))
+        .route("/{exam_id}/access-code", post(ExamAccessHandler::rotate_access_code))
+        .route("/{exam_id}/access-code", delete(ExamAccessHandler::clear_access_code))
+        .route("/{exam_id}/ip-allowlist", put(ExamAccessHandler::update_ip_allowlist))
+        .route("/templates", get(ExamHandlers::get_templates))
+        .route("/assigned", get(ExamHandlers::get_assigned_exams))
         .route("/assignments", post(ExamHandlers::assign_exam_to_class))
         .route("/assignments", delete(ExamHandlers::unassign_exam_from_class))
         .route_layer(axum::middleware::from_fn_with_state(
@@ -91,6 +117,10 @@ fn exam_attempts_routes(state: AppState) -> Router<AppState> {
         .route("/details/{attempt_id}", get(ExamAttemptHandler::get_attempt_with_answers))
         .route("/exam/{exam_id}", get(ExamAttemptHandler::get_exam_attempts)) // for teachers
         .route("/active/{exam_id}", get(ExamAttemptHandler::get_active_attempt))
+        .route("/{attempt_id}/sections", get(ExamAttemptHandler::get_attempt_sections))
+        .route("/{attempt_id}/sections/{section_id}/start", post(ExamAttemptHandler::start_section))
+        .route("/{attempt_id}/sections/{section_id}/finish", post(ExamAttemptHandler::finish_section))
+        .route("/{attempt_id}/answers/{question_id}", put(ExamAttemptHandler::save_answer))
         .route_layer(axum::middleware::from_fn_with_state(
             state.clone(),
             auth_middleware,
@@ -107,6 +137,10 @@ fn questions_routes(state: AppState) -> Router<AppState> {
         .route("/{question_id}", delete(QuestionHandler::delete_question))
         .route("/bulk", post(QuestionHandler::bulk_create_questions)) 
         .route("/exam/{exam_id}/total-score", get(QuestionHandler::get_exam_total_score)) 
+        .route("/consistency-report", get(QuestionHandler::get_consistency_report)) // Teachers only
+        .route("/{question_id}/revisions", get(QuestionHandler::get_revisions)) // Teachers only
+        .route("/{question_id}/revisions/diff", get(QuestionHandler::diff_revisions)) // Teachers only
+        .route("/{question_id}/revisions/{revision}", get(QuestionHandler::get_revision)) // Teachers only
         .route_layer(axum::middleware::from_fn_with_state(
             state.clone(),
             auth_middleware,
@@ -129,6 +163,70 @@ fn progress_routes(state: AppState) -> Router<AppState> {
         ))
 }
 
+fn accommodations_routes(state: AppState) -> Router<AppState> {
+    Router::new()
+        .route("/", post(AccommodationHandler::create_accommodation)) // Teachers only
+        .route("/my", get(AccommodationHandler::get_my_accommodations))
+        .route("/user/{user_id}", get(AccommodationHandler::get_user_accommodations)) // Teachers only
+        .route("/{accommodation_id}", put(AccommodationHandler::update_accommodation)) // Teachers only
+        .route("/{accommodation_id}", delete(AccommodationHandler::delete_accommodation)) // Teachers only
+        .route_layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            auth_middleware,
+        ))
+}
+
 async fn health_check() -> &'static str {
     "OK"
-}
\ No newline at end of file
+}
+
+fn trash_routes(state: AppState) -> Router<AppState> {
+    Router::new()
+        .route("/", get(TrashHandler::get_trash)) // Teachers only
+        .route("/{item_type}/{id}/restore", post(TrashHandler::restore_item)) // Teachers only
+        .route("/purge", post(TrashHandler::purge_trash)) // Admin only
+        .route_layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            auth_middleware,
+        ))
+}
+
+fn grade_scales_routes(state: AppState) -> Router<AppState> {
+    Router::new()
+        .route("/", post(GradeScaleHandler::create_grade_scale)) // Teachers only
+        .route("/", get(GradeScaleHandler::get_grade_scales)) // Teachers only
+        .route("/{scale_id}", get(GradeScaleHandler::get_grade_scale_by_id)) // Teachers only
+        .route("/{scale_id}", put(GradeScaleHandler::update_grade_scale)) // Teachers only
+        .route("/{scale_id}", delete(GradeScaleHandler::delete_grade_scale)) // Teachers only
+        .route_layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            auth_middleware,
+        ))
+}
+
+fn question_bank_routes(state: AppState) -> Router<AppState> {
+    Router::new()
+        .route("/", post(QuestionBankHandler::create_bank_question)) // Teachers only
+        .route("/", get(QuestionBankHandler::search_bank_questions)) // Teachers only
+        .route("/{question_id}/usage", get(QuestionBankHandler::get_question_usage)) // Teachers only
+        .route_layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            auth_middleware,
+        ))
+}
+
+fn attachments_routes(state: AppState) -> Router<AppState> {
+    // Leave room for the multipart headers around the file
+    let upload_limit = DefaultBodyLimit::max(state.config.attachment_max_bytes + 64 * 1024);
+
+    Router::new()
+        .route("/", post(AttachmentHandler::upload_attachment).layer(upload_limit)) // Teachers only
+        .route("/{attachment_id}", get(AttachmentHandler::get_attachment)) // Teachers only
+        .route("/{attachment_id}/url", get(AttachmentHandler::get_download_url))
+        .route_layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            auth_middleware,
+        ))
+        // Signed links from /url, no login needed
+        .route("/content/{storage_key}", get(AttachmentHandler::get_content))
+}
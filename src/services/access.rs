@@ -0,0 +1,191 @@
+use crate::database::Database;
+use crate::errors::{AppError, AppResult};
+use crate::models::access::{AssignmentAccessCode, ExamAccessResponse};
+use crate::utils::ip::ip_in_cidr;
+use rand::Rng;
+use sqlx::Row;
+use std::net::IpAddr;
+use uuid::Uuid;
+
+/// Characters used in access codes, without easily confused ones (0/O, 1/I)
+const ACCESS_CODE_ALPHABET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+const ACCESS_CODE_LENGTH: usize = 6;
+
+pub struct ExamAccessService {
+    db: Database,
+}
+
+impl ExamAccessService {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    pub fn generate_access_code() -> String {
+        let mut rng = rand::thread_rng();
+        (0..ACCESS_CODE_LENGTH)
+            .map(|_| ACCESS_CODE_ALPHABET[rng.gen_range(0..ACCESS_CODE_ALPHABET.len())] as char)
+            .collect()
+    }
+
+    /// Access code, per-assignment codes and IP allowlist of an exam
+    pub async fn get_access(&self, exam_id: Uuid) -> AppResult<ExamAccessResponse> {
+        let exam_row = sqlx::query("SELECT access_code, ip_allowlist FROM exams WHERE id = $1 AND deleted_at IS NULL")
+            .bind(exam_id)
+            .fetch_optional(&self.db.pool)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Exam not found".to_string()))?;
+
+        let assignment_rows = sqlx::query(
+            "SELECT class_id, access_code FROM exam_assignments WHERE exam_id = $1 AND access_code IS NOT NULL",
+        )
+        .bind(exam_id)
+        .fetch_all(&self.db.pool)
+        .await?;
+
+        Ok(ExamAccessResponse {
+            exam_id,
+            access_code: exam_row.get("access_code"),
+            assignment_access_codes: assignment_rows
+                .into_iter()
+                .map(|row| AssignmentAccessCode {
+                    class_id: row.get("class_id"),
+                    access_code: row.get("access_code"),
+                })
+                .collect(),
+            ip_allowlist: exam_row.get("ip_allowlist"),
+        })
+    }
+
+    /// Replace the access code of the exam, or of one of its class
+    /// assignments, with a newly generated one
+    pub async fn rotate_access_code(&self, exam_id: Uuid, class_id: Option<Uuid>) -> AppResult<ExamAccessResponse> {
+        self.set_access_code(exam_id, class_id, Some(Self::generate_access_code()))
+            .await?;
+        self.get_access(exam_id).await
+    }
+
+    pub async fn clear_access_code(&self, exam_id: Uuid, class_id: Option<Uuid>) -> AppResult<ExamAccessResponse> {
+        self.set_access_code(exam_id, class_id, None).await?;
+        self.get_access(exam_id).await
+    }
+
+    pub async fn update_ip_allowlist(&self, exam_id: Uuid, ip_allowlist: Vec<String>) -> AppResult<ExamAccessResponse> {
+        let ip_allowlist: Vec<String> = ip_allowlist.into_iter().map(|range| range.trim().to_string()).collect();
+        let result = sqlx::query("UPDATE exams SET ip_allowlist = $1 WHERE id = $2 AND deleted_at IS NULL")
+            .bind(&ip_allowlist)
+            .bind(exam_id)
+            .execute(&self.db.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Exam not found".to_string()));
+        }
+
+        self.get_access(exam_id).await
+    }
+
+    /// Check that a student may start or submit the exam from `client_ip`
+    /// with `access_code`. The code has to match the exam code or the code of
+    /// one of the student's class assignments; `previous_code` is also
+    /// accepted so a rotation does not lock out students already sitting.
+    pub async fn check_access(
+        &self,
+        exam_id: Uuid,
+        user_id: Uuid,
+        client_ip: IpAddr,
+        access_code: Option<&str>,
+        previous_code: Option<&str>,
+    ) -> AppResult<()> {
+        let exam_row = sqlx::query("SELECT access_code, ip_allowlist FROM exams WHERE id = $1")
+            .bind(exam_id)
+            .fetch_one(&self.db.pool)
+            .await?;
+
+        check_ip_allowlist(&exam_row.get::<Vec<String>, _>("ip_allowlist"), client_ip)?;
+
+        let mut valid_codes: Vec<String> = sqlx::query(
+            r#"
+            SELECT ea.access_code FROM exam_assignments ea
+            JOIN class_members cm ON ea.class_id = cm.class_id
+            JOIN classes c ON c.id = ea.class_id AND c.deleted_at IS NULL
+            WHERE ea.exam_id = $1 AND cm.user_id = $2 AND ea.access_code IS NOT NULL
+            "#,
+        )
+        .bind(exam_id)
+        .bind(user_id)
+        .fetch_all(&self.db.pool)
+        .await?
+        .into_iter()
+        .map(|row| row.get("access_code"))
+        .collect();
+        valid_codes.extend(exam_row.get::<Option<String>, _>("access_code"));
+
+        if valid_codes.is_empty() {
+            return Ok(());
+        }
+
+        let Some(access_code) = access_code.map(|code| code.trim().to_uppercase()) else {
+            return Err(AppError::ExamAccessDenied("Access code required".to_string()));
+        };
+
+        let matches = valid_codes.contains(&access_code)
+            || previous_code.is_some_and(|code| code == access_code);
+        if !matches {
+            return Err(AppError::ExamAccessDenied("Invalid access code".to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Check only the exam's IP allowlist, for requests made during an
+    /// attempt that was started with a valid access code
+    pub async fn check_network(&self, exam_id: Uuid, client_ip: IpAddr) -> AppResult<()> {
+        let ip_allowlist: Vec<String> = sqlx::query("SELECT ip_allowlist FROM exams WHERE id = $1")
+            .bind(exam_id)
+            .fetch_one(&self.db.pool)
+            .await?
+            .get("ip_allowlist");
+
+        check_ip_allowlist(&ip_allowlist, client_ip)
+    }
+
+    async fn set_access_code(&self, exam_id: Uuid, class_id: Option<Uuid>, code: Option<String>) -> AppResult<()> {
+        let result = match class_id {
+            Some(class_id) => {
+                sqlx::query("UPDATE exam_assignments SET access_code = $1 WHERE exam_id = $2 AND class_id = $3")
+                    .bind(&code)
+                    .bind(exam_id)
+                    .bind(class_id)
+                    .execute(&self.db.pool)
+                    .await?
+            }
+            None => {
+                sqlx::query("UPDATE exams SET access_code = $1 WHERE id = $2 AND deleted_at IS NULL")
+                    .bind(&code)
+                    .bind(exam_id)
+                    .execute(&self.db.pool)
+                    .await?
+            }
+        };
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound(match class_id {
+                Some(_) => "Exam assignment not found".to_string(),
+                None => "Exam not found".to_string(),
+            }));
+        }
+
+        Ok(())
+    }
+}
+
+/// Reject an address outside a non-empty allowlist
+fn check_ip_allowlist(ip_allowlist: &[String], client_ip: IpAddr) -> AppResult<()> {
+    if !ip_allowlist.is_empty() && !ip_allowlist.iter().any(|range| ip_in_cidr(client_ip, range)) {
+        return Err(AppError::ExamAccessDenied(
+            "This exam cannot be taken from your network".to_string(),
+        ));
+    }
+
+    Ok(())
+}
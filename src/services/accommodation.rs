@@ -0,0 +1,258 @@
+use crate::database::Database;
+use crate::errors::{AppError, AppResult};
+use crate::models::accommodation::{
+    Accommodation, AccommodationResponse, CreateAccommodationRequest, UpdateAccommodationRequest,
+};
+use sqlx::{postgres::PgRow, Row};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+const ACCOMMODATION_COLUMNS: &str =
+    "id, user_id, exam_id, time_multiplier, extra_minutes, extended_end_time, notes, created_by, created_at";
+
+pub struct AccommodationService {
+    db: Database,
+}
+
+impl AccommodationService {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    /// Create an accommodation for a student, either global or for one exam
+    pub async fn create_accommodation(
+        &self,
+        request: CreateAccommodationRequest,
+        created_by: Uuid,
+        teacher_id: Option<Uuid>,
+    ) -> AppResult<AccommodationResponse> {
+        self.ensure_teaches_student(request.user_id, teacher_id).await?;
+
+        if request.exam_id.is_none() && request.extended_end_time.is_some() {
+            return Err(AppError::BadRequest(
+                "extended_end_time can only be set on a per-exam accommodation".to_string(),
+            ));
+        }
+
+        if let Some(exam_id) = request.exam_id {
+            let exam = sqlx::query("SELECT 1 FROM exams WHERE id = $1 AND deleted_at IS NULL")
+                .bind(exam_id)
+                .fetch_optional(&self.db.pool)
+                .await?;
+            if exam.is_none() {
+                return Err(AppError::NotFound("Exam not found".to_string()));
+            }
+        }
+
+        let existing = sqlx::query(
+            "SELECT id FROM exam_accommodations WHERE user_id = $1 AND exam_id IS NOT DISTINCT FROM $2",
+        )
+        .bind(request.user_id)
+        .bind(request.exam_id)
+        .fetch_optional(&self.db.pool)
+        .await?;
+
+        if existing.is_some() {
+            return Err(AppError::Conflict(
+                "Accommodation already exists for this user and exam".to_string(),
+            ));
+        }
+
+        let row = sqlx::query(&format!(
+            r#"
+            INSERT INTO exam_accommodations (user_id, exam_id, time_multiplier, extra_minutes, extended_end_time, notes, created_by)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING {ACCOMMODATION_COLUMNS}
+            "#
+        ))
+        .bind(request.user_id)
+        .bind(request.exam_id)
+        .bind(request.time_multiplier.unwrap_or(1.0))
+        .bind(request.extra_minutes.unwrap_or(0))
+        .bind(request.extended_end_time)
+        .bind(&request.notes)
+        .bind(created_by)
+        .fetch_one(&self.db.pool)
+        .await?;
+
+        Ok(self.row_to_accommodation(row)?.into())
+    }
+
+    /// Update the values of an existing accommodation
+    pub async fn update_accommodation(
+        &self,
+        accommodation_id: Uuid,
+        request: UpdateAccommodationRequest,
+        teacher_id: Option<Uuid>,
+    ) -> AppResult<AccommodationResponse> {
+        let existing = sqlx::query("SELECT user_id, exam_id FROM exam_accommodations WHERE id = $1")
+            .bind(accommodation_id)
+            .fetch_optional(&self.db.pool)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Accommodation not found".to_string()))?;
+        self.ensure_teaches_student(existing.get("user_id"), teacher_id).await?;
+
+        let exam_id: Option<Uuid> = existing.get("exam_id");
+        if exam_id.is_none() && request.extended_end_time.is_some() {
+            return Err(AppError::BadRequest(
+                "extended_end_time can only be set on a per-exam accommodation".to_string(),
+            ));
+        }
+
+        let row = sqlx::query(&format!(
+            r#"
+            UPDATE exam_accommodations
+            SET time_multiplier = $1, extra_minutes = $2, extended_end_time = $3, notes = $4
+            WHERE id = $5
+            RETURNING {ACCOMMODATION_COLUMNS}
+            "#
+        ))
+        .bind(request.time_multiplier)
+        .bind(request.extra_minutes)
+        .bind(request.extended_end_time)
+        .bind(&request.notes)
+        .bind(accommodation_id)
+        .fetch_optional(&self.db.pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Accommodation not found".to_string()))?;
+
+        Ok(self.row_to_accommodation(row)?.into())
+    }
+
+    /// Delete an accommodation
+    pub async fn delete_accommodation(&self, accommodation_id: Uuid, teacher_id: Option<Uuid>) -> AppResult<()> {
+        let existing = sqlx::query("SELECT user_id FROM exam_accommodations WHERE id = $1")
+            .bind(accommodation_id)
+            .fetch_optional(&self.db.pool)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Accommodation not found".to_string()))?;
+        self.ensure_teaches_student(existing.get("user_id"), teacher_id).await?;
+
+        let result = sqlx::query("DELETE FROM exam_accommodations WHERE id = $1")
+            .bind(accommodation_id)
+            .execute(&self.db.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Accommodation not found".to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// List all accommodations of a student
+    pub async fn get_user_accommodations(
+        &self,
+        user_id: Uuid,
+        teacher_id: Option<Uuid>,
+    ) -> AppResult<Vec<AccommodationResponse>> {
+        self.ensure_teaches_student(user_id, teacher_id).await?;
+
+        let rows = sqlx::query(&format!(
+            r#"
+            SELECT {ACCOMMODATION_COLUMNS}
+            FROM exam_accommodations
+            WHERE user_id = $1
+            ORDER BY exam_id NULLS FIRST, created_at
+            "#
+        ))
+        .bind(user_id)
+        .fetch_all(&self.db.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| Ok(self.row_to_accommodation(row)?.into()))
+            .collect()
+    }
+
+    /// Accommodation that applies to a user for an exam: the per-exam record
+    /// if there is one, otherwise the global record
+    pub async fn get_effective_accommodation(
+        &self,
+        user_id: Uuid,
+        exam_id: Uuid,
+    ) -> AppResult<Option<Accommodation>> {
+        Ok(self.get_effective_accommodations(user_id, &[exam_id]).await?.remove(&exam_id))
+    }
+
+    /// Accommodations that apply to a user for each of `exam_ids`, see
+    /// `get_effective_accommodation`. Exams without one are left out.
+    pub async fn get_effective_accommodations(
+        &self,
+        user_id: Uuid,
+        exam_ids: &[Uuid],
+    ) -> AppResult<HashMap<Uuid, Accommodation>> {
+        let rows = sqlx::query(&format!(
+            r#"
+            SELECT {ACCOMMODATION_COLUMNS}
+            FROM exam_accommodations
+            WHERE user_id = $1 AND (exam_id = ANY($2) OR exam_id IS NULL)
+            "#
+        ))
+        .bind(user_id)
+        .bind(exam_ids)
+        .fetch_all(&self.db.pool)
+        .await?;
+
+        let mut per_exam = HashMap::new();
+        let mut global = None;
+        for row in rows {
+            let accommodation = self.row_to_accommodation(row)?;
+            match accommodation.exam_id {
+                Some(exam_id) => {
+                    per_exam.insert(exam_id, accommodation);
+                }
+                None => global = Some(accommodation),
+            }
+        }
+
+        if let Some(global) = global {
+            for exam_id in exam_ids {
+                per_exam.entry(*exam_id).or_insert_with(|| global.clone());
+            }
+        }
+
+        Ok(per_exam)
+    }
+
+    /// Teachers manage accommodations of students in their own classes;
+    /// `teacher_id` is `None` for admins and for students reading their own
+    async fn ensure_teaches_student(&self, user_id: Uuid, teacher_id: Option<Uuid>) -> AppResult<()> {
+        let Some(teacher_id) = teacher_id else {
+            return Ok(());
+        };
+
+        let row = sqlx::query(
+            r#"
+            SELECT 1 FROM class_members cm
+            JOIN classes c ON c.id = cm.class_id AND c.deleted_at IS NULL
+            WHERE cm.user_id = $1 AND c.created_by = $2
+            LIMIT 1
+            "#,
+        )
+        .bind(user_id)
+        .bind(teacher_id)
+        .fetch_optional(&self.db.pool)
+        .await?;
+
+        if row.is_none() {
+            return Err(AppError::Forbidden);
+        }
+
+        Ok(())
+    }
+
+    fn row_to_accommodation(&self, row: PgRow) -> AppResult<Accommodation> {
+        Ok(Accommodation {
+            id: row.get("id"),
+            user_id: row.get("user_id"),
+            exam_id: row.get("exam_id"),
+            time_multiplier: row.get("time_multiplier"),
+            extra_minutes: row.get("extra_minutes"),
+            extended_end_time: row.get("extended_end_time"),
+            notes: row.get("notes"),
+            created_by: row.get("created_by"),
+            created_at: row.get("created_at"),
+        })
+    }
+}
@@ -0,0 +1,164 @@
+use std::sync::Arc;
+
+use crate::database::Database;
+use crate::errors::{AppError, AppResult};
+use crate::models::attachment::{check_upload, Attachment, DownloadUrlResponse};
+use crate::services::question::QuestionService;
+use crate::storage::BlobStore;
+use chrono::{Duration, Utc};
+use sqlx::{postgres::PgRow, Row};
+use uuid::Uuid;
+
+const ATTACHMENT_COLUMNS: &str = "id, storage_key, file_name, content_type, size_bytes, uploaded_by, created_at";
+
+pub struct AttachmentService {
+    db: Database,
+    store: Arc<dyn BlobStore>,
+}
+
+impl AttachmentService {
+    pub fn new(db: Database, store: Arc<dyn BlobStore>) -> Self {
+        Self { db, store }
+    }
+
+    /// Store an uploaded file after checking its size and content type
+    pub async fn upload(
+        &self,
+        uploaded_by: Uuid,
+        file_name: String,
+        declared_type: Option<&str>,
+        bytes: Vec<u8>,
+        max_bytes: usize,
+    ) -> AppResult<Attachment> {
+        if bytes.len() > max_bytes {
+            return Err(AppError::PayloadTooLarge(format!(
+                "Attachments can be at most {} bytes",
+                max_bytes
+            )));
+        }
+        let content_type = check_upload(declared_type, &bytes, max_bytes).map_err(AppError::BadRequest)?;
+
+        let storage_key = Uuid::new_v4().simple().to_string();
+        let size_bytes = bytes.len() as i64;
+        self.store.put(&storage_key, content_type, bytes).await?;
+
+        let row = sqlx::query(&format!(
+            r#"
+            INSERT INTO attachments (storage_key, file_name, content_type, size_bytes, uploaded_by)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING {ATTACHMENT_COLUMNS}
+            "#
+        ))
+        .bind(&storage_key)
+        .bind(file_name)
+        .bind(content_type)
+        .bind(size_bytes)
+        .bind(uploaded_by)
+        .fetch_one(&self.db.pool)
+        .await;
+
+        match row {
+            Ok(row) => Ok(self.row_to_attachment(row)),
+            Err(e) => {
+                // Don't leave a blob behind that nothing refers to
+                if let Err(delete_error) = self.store.delete(&storage_key).await {
+                    tracing::warn!("Failed to remove orphaned blob {}: {}", storage_key, delete_error);
+                }
+                Err(e.into())
+            }
+        }
+    }
+
+    pub async fn get_attachment(&self, attachment_id: Uuid) -> AppResult<Attachment> {
+        let row = sqlx::query(&format!("SELECT {ATTACHMENT_COLUMNS} FROM attachments WHERE id = $1"))
+            .bind(attachment_id)
+            .fetch_optional(&self.db.pool)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Attachment not found".to_string()))?;
+
+        Ok(self.row_to_attachment(row))
+    }
+
+    /// A download link valid for `ttl_secs`. Students only get one for media
+    /// of a question in an attempt they have in progress.
+    pub async fn get_download_url(
+        &self,
+        attachment_id: Uuid,
+        student_id: Option<Uuid>,
+        ttl_secs: i64,
+    ) -> AppResult<DownloadUrlResponse> {
+        let attachment = self.get_attachment(attachment_id).await?;
+
+        if let Some(student_id) = student_id
+            && !self.is_in_active_attempt(attachment_id, student_id).await?
+        {
+            return Err(AppError::Forbidden);
+        }
+
+        let expires_at = Utc::now() + Duration::seconds(ttl_secs);
+        Ok(DownloadUrlResponse {
+            attachment_id,
+            url: self.store.download_url(&attachment.storage_key, expires_at),
+            content_type: attachment.content_type,
+            expires_at,
+        })
+    }
+
+    /// Content of the attachment stored under `storage_key`, for stores that
+    /// serve downloads through the API. The caller checks the signed link.
+    pub async fn get_content(&self, storage_key: &str) -> AppResult<(String, Vec<u8>)> {
+        let row = sqlx::query("SELECT content_type FROM attachments WHERE storage_key = $1")
+            .bind(storage_key)
+            .fetch_optional(&self.db.pool)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Attachment not found".to_string()))?;
+
+        let bytes = self.store.get(storage_key).await?;
+        Ok((row.get("content_type"), bytes))
+    }
+
+    /// Whether the attachment belongs to a question, or one of its choices,
+    /// as pinned in an unsubmitted attempt of the student
+    async fn is_in_active_attempt(&self, attachment_id: Uuid, student_id: Uuid) -> AppResult<bool> {
+        let pinned: Vec<(Uuid, i32)> = sqlx::query(
+            r#"
+            SELECT aq.question_id, aq.revision
+            FROM attempt_questions aq
+            JOIN exam_attempts a ON a.id = aq.attempt_id
+            WHERE a.user_id = $1 AND a.submitted_at IS NULL
+            "#,
+        )
+        .bind(student_id)
+        .fetch_all(&self.db.pool)
+        .await?
+        .into_iter()
+        .map(|row| (row.get("question_id"), row.get("revision")))
+        .collect();
+
+        let question_service = QuestionService::new(self.db.clone());
+        for (question_id, revision) in pinned {
+            let question = question_service.get_revision(question_id, revision).await?.question;
+            let attached = question.attachment_ids.contains(&attachment_id)
+                || question
+                    .choices
+                    .iter()
+                    .any(|choice| choice.attachment_ids.contains(&attachment_id));
+            if attached {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    fn row_to_attachment(&self, row: PgRow) -> Attachment {
+        Attachment {
+            id: row.get("id"),
+            storage_key: row.get("storage_key"),
+            file_name: row.get("file_name"),
+            content_type: row.get("content_type"),
+            size_bytes: row.get("size_bytes"),
+            uploaded_by: row.get("uploaded_by"),
+            created_at: row.get("created_at"),
+        }
+    }
+}
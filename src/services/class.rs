@@ -27,7 +27,7 @@ impl ClassService {
             RETURNING id, name, created_by, created_at
         "#,
         )
-        .bind(&request.name)
+        .bind(request.name)
         .bind(Utc::now())
         .bind(user_id)
         .fetch_one(&self.db.pool)
@@ -40,7 +40,7 @@ impl ClassService {
 
     pub async fn get_classes(&self, pagination: &pagination::Pagination) -> AppResult<Vec<ClassResponse>> {
         let rows = sqlx::query(
-            r#"SELECT id, name, created_by, created_at FROM classes ORDER BY created_at DESC LIMIT $1 OFFSET $2"#
+            r#"SELECT id, name, created_by, created_at FROM classes WHERE deleted_at IS NULL ORDER BY created_at DESC LIMIT $1 OFFSET $2"#
         )
         .bind(pagination.limit_or_default(20))
         .bind(pagination.skip_or_default())
@@ -56,7 +56,7 @@ impl ClassService {
     }
 
     pub async fn get_class_by_id(&self, class_id: Uuid) -> AppResult<ClassResponse> {
-        let row = sqlx::query(r#"SELECT id, name, created_by, created_at FROM classes WHERE id = $1"#)
+        let row = sqlx::query(r#"SELECT id, name, created_by, created_at FROM classes WHERE id = $1 AND deleted_at IS NULL"#)
             .bind(class_id)
             .fetch_optional(&self.db.pool)
             .await?
@@ -72,9 +72,9 @@ impl ClassService {
         request: UpdateClassRequest,
     ) -> AppResult<ClassResponse> {
         let row = sqlx::query(
-            r#"UPDATE classes SET name = $1 WHERE id = $2 RETURNING id, name, created_by, created_at"#,
+            r#"UPDATE classes SET name = $1 WHERE id = $2 AND deleted_at IS NULL RETURNING id, name, created_by, created_at"#,
         )
-        .bind(&request.name)
+        .bind(request.name)
         .bind(class_id)
         .fetch_one(&self.db.pool)
         .await?;
@@ -83,9 +83,10 @@ impl ClassService {
         Ok(class.into())
     }
 
-    pub async fn delete_class(&self, class_id: Uuid) -> AppResult<()> {
-        sqlx::query(r#"DELETE FROM classes WHERE id = $1"#)
+    pub async fn delete_class(&self, class_id: Uuid, deleted_by: Uuid) -> AppResult<()> {
+        sqlx::query(r#"UPDATE classes SET deleted_at = NOW(), deleted_by = $2 WHERE id = $1 AND deleted_at IS NULL"#)
             .bind(class_id)
+            .bind(deleted_by)
             .execute(&self.db.pool)
             .await?;
         Ok(())
@@ -95,8 +96,8 @@ impl ClassService {
         sqlx::query(
             r#"INSERT INTO class_members (user_id, class_id, created_at) VALUES ($1, $2, $3) ON CONFLICT DO NOTHING"#,
         )
-        .bind(&request.user_id)
-        .bind(&request.class_id)
+        .bind(request.user_id)
+        .bind(request.class_id)
         .bind(Utc::now())
         .execute(&self.db.pool)
         .await?;
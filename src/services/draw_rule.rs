@@ -0,0 +1,161 @@
+use crate::database::Database;
+use crate::errors::{AppError, AppResult};
+use crate::models::draw_rule::{
+    draw_questions, CreateDrawRuleRequest, DrawRule, DrawRuleResponse, PoolQuestion,
+};
+use sqlx::{postgres::PgRow, Row};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+const DRAW_RULE_COLUMNS: &str = "id, exam_id, section_id, pool, difficulty, draw_count, created_at";
+
+pub struct DrawRuleService {
+    db: Database,
+}
+
+impl DrawRuleService {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    /// Add a draw rule to an exam
+    pub async fn create_draw_rule(
+        &self,
+        exam_id: Uuid,
+        request: CreateDrawRuleRequest,
+    ) -> AppResult<DrawRuleResponse> {
+        sqlx::query("SELECT 1 FROM exams WHERE id = $1 AND deleted_at IS NULL")
+            .bind(exam_id)
+            .fetch_optional(&self.db.pool)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Exam not found".to_string()))?;
+
+        if let Some(section_id) = request.section_id {
+            let section = sqlx::query("SELECT 1 FROM exam_sections WHERE id = $1 AND exam_id = $2")
+                .bind(section_id)
+                .bind(exam_id)
+                .fetch_optional(&self.db.pool)
+                .await?;
+            if section.is_none() {
+                return Err(AppError::BadRequest("Section does not belong to this exam".to_string()));
+            }
+        }
+
+        let row = sqlx::query(&format!(
+            r#"
+            INSERT INTO exam_draw_rules (exam_id, section_id, pool, difficulty, draw_count)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING {DRAW_RULE_COLUMNS}
+            "#
+        ))
+        .bind(exam_id)
+        .bind(request.section_id)
+        .bind(&request.pool)
+        .bind(&request.difficulty)
+        .bind(request.draw_count)
+        .fetch_one(&self.db.pool)
+        .await?;
+
+        let rule = self.row_to_draw_rule(row)?;
+        let available = self.count_available(&rule).await?;
+        Ok(DrawRuleResponse::new(rule, available))
+    }
+
+    /// Draw rules of an exam with the number of questions each can draw from
+    pub async fn get_draw_rules(&self, exam_id: Uuid) -> AppResult<Vec<DrawRuleResponse>> {
+        let mut responses = Vec::new();
+        for rule in self.get_rules(exam_id).await? {
+            let available = self.count_available(&rule).await?;
+            responses.push(DrawRuleResponse::new(rule, available));
+        }
+        Ok(responses)
+    }
+
+    pub async fn delete_draw_rule(&self, rule_id: Uuid) -> AppResult<()> {
+        let result = sqlx::query("DELETE FROM exam_draw_rules WHERE id = $1")
+            .bind(rule_id)
+            .execute(&self.db.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Draw rule not found".to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Draw a fresh question set for a new attempt, see `draw_questions`.
+    /// Linked bank questions take part with their per-exam section.
+    pub async fn draw_for_attempt(&self, exam_id: Uuid) -> AppResult<Vec<Uuid>> {
+        let questions: Vec<PoolQuestion> = sqlx::query(
+            "SELECT id, section_id, pool, difficulty FROM exam_questions WHERE exam_id = $1 AND deleted_at IS NULL ORDER BY id",
+        )
+        .bind(exam_id)
+        .fetch_all(&self.db.pool)
+        .await?
+        .into_iter()
+        .map(|row| PoolQuestion {
+            id: row.get("id"),
+            section_id: row.get("section_id"),
+            pool: row.get("pool"),
+            difficulty: row.get("difficulty"),
+        })
+        .collect();
+
+        let section_draw_counts: HashMap<Uuid, i32> = sqlx::query(
+            "SELECT id, draw_count FROM exam_sections WHERE exam_id = $1 AND draw_count IS NOT NULL",
+        )
+        .bind(exam_id)
+        .fetch_all(&self.db.pool)
+        .await?
+        .into_iter()
+        .map(|row| (row.get("id"), row.get("draw_count")))
+        .collect();
+
+        let rules = self.get_rules(exam_id).await?;
+        Ok(draw_questions(&questions, &rules, &section_draw_counts, &mut rand::thread_rng()))
+    }
+
+    async fn get_rules(&self, exam_id: Uuid) -> AppResult<Vec<DrawRule>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {DRAW_RULE_COLUMNS} FROM exam_draw_rules WHERE exam_id = $1 ORDER BY created_at"
+        ))
+        .bind(exam_id)
+        .fetch_all(&self.db.pool)
+        .await?;
+
+        rows.into_iter().map(|row| self.row_to_draw_rule(row)).collect()
+    }
+
+    async fn count_available(&self, rule: &DrawRule) -> AppResult<i64> {
+        let available: i64 = sqlx::query(
+            r#"
+            SELECT COUNT(*) AS available FROM exam_questions
+            WHERE exam_id = $1 AND deleted_at IS NULL
+              AND ($2::uuid IS NULL OR section_id = $2)
+              AND ($3::text IS NULL OR pool = $3)
+              AND ($4::text IS NULL OR difficulty = $4)
+            "#,
+        )
+        .bind(rule.exam_id)
+        .bind(rule.section_id)
+        .bind(&rule.pool)
+        .bind(&rule.difficulty)
+        .fetch_one(&self.db.pool)
+        .await?
+        .get("available");
+        Ok(available)
+    }
+
+    fn row_to_draw_rule(&self, row: PgRow) -> AppResult<DrawRule> {
+        Ok(DrawRule {
+            id: row.get("id"),
+            exam_id: row.get("exam_id"),
+            section_id: row.get("section_id"),
+            pool: row.get("pool"),
+            difficulty: row.get("difficulty"),
+            draw_count: row.get("draw_count"),
+            created_at: row.get("created_at"),
+        })
+    }
+}
@@ -1,13 +1,22 @@
 use crate::database::Database;
 use crate::errors::{AppError, AppResult};
 use crate::models::exams::{
-    CreateExamAssignmentRequest, CreateExamRequest, DeleteExamAssignmentRequest, Exam,
-    ExamAssignments, ExamAssignmentsResponse, ExamResponse, UpdateExamRequest,
+    AssignedExamResponse, CloneExamRequest, CloneExamResponse, CreateExamAssignmentRequest, CreateExamRequest,
+    DeleteExamAssignmentRequest, Exam, ExamAssignments, ExamAssignmentsResponse, ExamFilter,
+    ExamAvailability, ExamResponse, ExamWindow, PassMarkType, UpdateExamRequest,
 };
-use sqlx::Row;
+use crate::services::accommodation::AccommodationService;
+use crate::services::grade_scale::GradeScaleService;
+use crate::services::prerequisite::PrerequisiteService;
+use crate::services::exam_attempt::{ExamAttemptService, MAX_ATTEMPTS_PER_EXAM};
+use chrono::Utc;
 use sqlx::postgres::PgRow;
+use sqlx::{Postgres, QueryBuilder, Row};
+use std::collections::HashMap;
 use uuid::Uuid;
 
+const EXAM_COLUMNS: &str = "id, title, description, created_by, duration_minutes, start_time, end_time, is_active, category, difficulty, is_template, pass_mark, pass_mark_type, grade_scale_id, shuffle_questions, shuffle_choices, wrong_answer_penalty, score_floor, review_policy";
+
 pub struct ExamService {
     db: Database,
 }
@@ -18,21 +27,35 @@ impl ExamService {
     }
 
     pub async fn create_exam(&self, request: CreateExamRequest, created_by: Uuid) -> AppResult<ExamResponse> {
-        let row = sqlx::query(
+        let pass_mark_type = request.pass_mark_type.unwrap_or_default();
+        self.check_grading(request.pass_mark, pass_mark_type, request.grade_scale_id).await?;
+
+        let row = sqlx::query(&format!(
             r#"
-INSERT INTO exams (title, description, created_by, duration_minutes, start_time, end_time, is_active, category, difficulty)
-VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9) 
-RETURNING id, title, description, created_by, duration_minutes, start_time, end_time, is_active, category, difficulty
-            "#)
-            .bind(&request.title)
-            .bind(&request.description)
-            .bind(&created_by)
-            .bind(&request.duration_minutes)
-            .bind(&request.start_time)
-            .bind(&request.end_time)
+INSERT INTO exams (title, description, created_by, duration_minutes, start_time, end_time, is_active, category, difficulty, is_template,
+                   pass_mark, pass_mark_type, grade_scale_id, shuffle_questions, shuffle_choices, wrong_answer_penalty,
+                   score_floor, review_policy)
+VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18)
+RETURNING {EXAM_COLUMNS}
+            "#))
+            .bind(request.title)
+            .bind(request.description)
+            .bind(created_by)
+            .bind(request.duration_minutes)
+            .bind(request.start_time)
+            .bind(request.end_time)
             .bind(true)
-            .bind(&request.category)
-            .bind(&request.difficulty)
+            .bind(request.category)
+            .bind(request.difficulty)
+            .bind(request.is_template.unwrap_or(false))
+            .bind(request.pass_mark)
+            .bind(pass_mark_type.as_str())
+            .bind(request.grade_scale_id)
+            .bind(request.shuffle_questions.unwrap_or(false))
+            .bind(request.shuffle_choices.unwrap_or(false))
+            .bind(request.wrong_answer_penalty.unwrap_or(0.0))
+            .bind(request.score_floor.unwrap_or_default().as_str())
+            .bind(request.review_policy.unwrap_or_default().as_str())
             .fetch_one(&self.db.pool)
             .await?;
 
@@ -57,37 +80,91 @@ RETURNING id, title, description, created_by, duration_minutes, start_time, end_
     //     Ok(exams)
     // }
 
-    pub async fn get_exams(&self, pagination: &crate::utils::pagination::Pagination) -> AppResult<Vec<ExamResponse>> {
-        let rows = sqlx::query(
-            r#"
-SELECT id, title, description, created_by, duration_minutes, start_time, end_time, is_active, category, difficulty
-FROM exams
-ORDER BY start_time DESC
-LIMIT $1 OFFSET $2
-    "#,
-        )
-        .bind(pagination.limit_or_default(20))
-        .bind(pagination.skip_or_default())
-        .fetch_all(&self.db.pool)
-        .await?;
+    /// Search exams and return one page of results with the total match count.
+    /// When `student_id` is set only active exams assigned to the student's
+    /// classes are returned.
+    pub async fn get_exams(
+        &self,
+        pagination: &crate::utils::pagination::Pagination,
+        filter: &ExamFilter,
+        student_id: Option<Uuid>,
+    ) -> AppResult<(Vec<ExamResponse>, i64)> {
+        let mut count_query = QueryBuilder::<Postgres>::new("SELECT COUNT(*) AS total FROM exams");
+        Self::push_exam_filters(&mut count_query, filter, student_id);
+        let total: i64 = count_query
+            .build()
+            .fetch_one(&self.db.pool)
+            .await?
+            .get("total");
+
+        let mut query = QueryBuilder::<Postgres>::new(format!("SELECT {EXAM_COLUMNS} FROM exams"));
+        Self::push_exam_filters(&mut query, filter, student_id);
+        query.push(format!(" ORDER BY {}, id", filter.sort.order_by()));
+        query.push(" LIMIT ").push_bind(pagination.limit_or_default(20));
+        query.push(" OFFSET ").push_bind(pagination.skip_or_default());
+
+        let rows = query.build().fetch_all(&self.db.pool).await?;
 
-        let exams: Vec<ExamResponse> = rows
+        let exams = rows
             .into_iter()
-            .map(|row| self.row_to_exam(row).unwrap().into())
-            .collect();
-        Ok(exams)
+            .map(|row| Ok(self.row_to_exam(row)?.into()))
+            .collect::<AppResult<Vec<ExamResponse>>>()?;
+        Ok((exams, total))
+    }
+
+    fn push_exam_filters(
+        query: &mut QueryBuilder<'_, Postgres>,
+        filter: &ExamFilter,
+        student_id: Option<Uuid>,
+    ) {
+        query.push(" WHERE deleted_at IS NULL AND is_template = false");
+
+        if let Some(category) = &filter.category {
+            query.push(" AND category = ").push_bind(category.clone());
+        }
+        if let Some(difficulty) = &filter.difficulty {
+            query.push(" AND difficulty = ").push_bind(difficulty.clone());
+        }
+        if let Some(created_by) = filter.created_by {
+            query.push(" AND created_by = ").push_bind(created_by);
+        }
+        match filter.status {
+            Some(ExamWindow::Active) => query.push(" AND start_time <= NOW() AND end_time >= NOW()"),
+            Some(ExamWindow::Upcoming) => query.push(" AND start_time > NOW()"),
+            Some(ExamWindow::Past) => query.push(" AND end_time < NOW()"),
+            None => query,
+        };
+        if let Some(q) = filter.q.as_deref().map(str::trim).filter(|q| !q.is_empty()) {
+            query
+                .push(" AND to_tsvector('simple', title) @@ plainto_tsquery('simple', ")
+                .push_bind(q.to_string())
+                .push(")");
+        }
+        if let Some(student_id) = student_id {
+            query
+                .push(
+                    r#" AND is_active = true AND EXISTS (
+                        SELECT 1 FROM exam_assignments ea
+                        JOIN class_members cm ON ea.class_id = cm.class_id
+                        JOIN classes c ON c.id = ea.class_id AND c.deleted_at IS NULL
+                        WHERE ea.exam_id = exams.id AND cm.user_id = "#,
+                )
+                .push_bind(student_id)
+                .push(")");
+        }
     }
 
     pub async fn get_exam_by_id(&self, id: Uuid) -> AppResult<ExamResponse> {
-        let row = sqlx::query(
+        let row = sqlx::query(&format!(
             r#"
-            SELECT id, title, description, created_by, duration_minutes, start_time, end_time, is_active, category, difficulty
+            SELECT {EXAM_COLUMNS}
             FROM exams
-            WHERE id = $1
-            "#)
+            WHERE id = $1 AND deleted_at IS NULL
+            "#))
             .bind(id)
-            .fetch_one(&self.db.pool)
-            .await?;
+            .fetch_optional(&self.db.pool)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Exam not found".to_string()))?;
 
         let exam = self.row_to_exam(row)?;
 
@@ -99,33 +176,385 @@ LIMIT $1 OFFSET $2
         id: Uuid,
         request: UpdateExamRequest,
     ) -> AppResult<ExamResponse> {
-        let row = sqlx::query(
+        // Omitted grading fields keep their values, so the pass mark is
+        // checked against the type it ends up with
+        let current = self.get_exam_by_id(id).await?;
+        let pass_mark = if request.clear_pass_mark { None } else { request.pass_mark.or(current.pass_mark) };
+        let pass_mark_type = request.pass_mark_type.unwrap_or(current.pass_mark_type);
+        self.check_grading(pass_mark, pass_mark_type, request.grade_scale_id).await?;
+
+        let row = sqlx::query(&format!(
         r#"
         UPDATE exams
-        SET title = $1, description = $2, duration_minutes = $3, start_time = $4, end_time = $5, is_active = $6, category = $7, difficulty = $8
-        WHERE id = $9
-        RETURNING id, title, description, created_by, duration_minutes, start_time, end_time, is_active, category, difficulty
+        SET title = $1, description = $2, duration_minutes = $3, start_time = $4, end_time = $5, is_active = $6, category = $7, difficulty = $8,
+            is_template = COALESCE($9, is_template), pass_mark = CASE WHEN $19 THEN NULL ELSE COALESCE($11, pass_mark) END,
+            pass_mark_type = COALESCE($12, pass_mark_type),
+            grade_scale_id = CASE WHEN $20 THEN NULL ELSE COALESCE($13, grade_scale_id) END,
+            shuffle_questions = COALESCE($14, shuffle_questions), shuffle_choices = COALESCE($15, shuffle_choices),
+            wrong_answer_penalty = COALESCE($16, wrong_answer_penalty), score_floor = COALESCE($17, score_floor),
+            review_policy = COALESCE($18, review_policy)
+        WHERE id = $10 AND deleted_at IS NULL
+        RETURNING {EXAM_COLUMNS}
         "#
-    )
-    .bind(&request.title)
-    .bind(&request.description)
-    .bind(&request.duration_minutes)
-    .bind(&request.start_time)
-    .bind(&request.end_time)
-    .bind(&request.is_active)
-    .bind(&request.category)
-    .bind(&request.difficulty)
+    ))
+    .bind(request.title)
+    .bind(request.description)
+    .bind(request.duration_minutes)
+    .bind(request.start_time)
+    .bind(request.end_time)
+    .bind(request.is_active)
+    .bind(request.category)
+    .bind(request.difficulty)
+    .bind(request.is_template)
     .bind(id)
-    .fetch_one(&self.db.pool)
-    .await?;
+    .bind(request.pass_mark)
+    .bind(request.pass_mark_type.map(|pass_mark_type| pass_mark_type.as_str()))
+    .bind(request.grade_scale_id)
+    .bind(request.shuffle_questions)
+    .bind(request.shuffle_choices)
+    .bind(request.wrong_answer_penalty)
+    .bind(request.score_floor.map(|floor| floor.as_str()))
+    .bind(request.review_policy.map(|policy| policy.as_str()))
+    .bind(request.clear_pass_mark)
+    .bind(request.clear_grade_scale)
+    .fetch_optional(&self.db.pool)
+    .await?
+    .ok_or_else(|| AppError::NotFound("Exam not found".to_string()))?;
 
         let exam = self.row_to_exam(row)?;
         Ok(exam.into())
     }
 
-    pub async fn delete_exam(&self, id: Uuid) -> AppResult<()> {
-        let result = sqlx::query(r#"DELETE FROM exams WHERE id = $1"#)
+    /// Exams assigned to a student's classes, with availability and the
+    /// student's attempts for each
+    pub async fn get_assigned_exams(&self, user_id: Uuid) -> AppResult<Vec<AssignedExamResponse>> {
+        let rows = sqlx::query(&format!(
+            r#"
+            SELECT {EXAM_COLUMNS},
+                (SELECT COUNT(*) FROM exam_attempts a
+                 WHERE a.exam_id = exams.id AND a.user_id = $1) AS attempts_used,
+                (SELECT MAX(a.score_total) FROM exam_attempts a
+                 WHERE a.exam_id = exams.id AND a.user_id = $1 AND a.submitted_at IS NOT NULL) AS best_score,
+                COALESCE(
+                    (SELECT a.max_score::BIGINT FROM exam_attempts a
+                     WHERE a.exam_id = exams.id AND a.user_id = $1
+                     ORDER BY a.started_at DESC LIMIT 1),
+                    (SELECT COALESCE(SUM(q.score), 0)::BIGINT FROM exam_questions q
+                     WHERE q.exam_id = exams.id AND q.deleted_at IS NULL)
+                ) AS max_score,
+                active.id AS active_attempt_id,
+                active.started_at AS active_started_at
+            FROM exams
+            LEFT JOIN LATERAL (
+                SELECT a.id, a.started_at FROM exam_attempts a
+                WHERE a.exam_id = exams.id AND a.user_id = $1 AND a.submitted_at IS NULL
+                ORDER BY a.started_at DESC
+                LIMIT 1
+            ) active ON true
+            WHERE deleted_at IS NULL AND is_active = true AND is_template = false
+              AND EXISTS (
+                SELECT 1 FROM exam_assignments ea
+                JOIN class_members cm ON ea.class_id = cm.class_id
+                JOIN classes c ON c.id = ea.class_id AND c.deleted_at IS NULL
+                WHERE ea.exam_id = exams.id AND cm.user_id = $1
+              )
+            ORDER BY start_time
+            "#
+        ))
+        .bind(user_id)
+        .fetch_all(&self.db.pool)
+        .await?;
+
+        let exam_ids: Vec<Uuid> = rows.iter().map(|row| row.get("id")).collect();
+        let mut accommodations = AccommodationService::new(self.db.clone())
+            .get_effective_accommodations(user_id, &exam_ids)
+            .await?;
+        let mut unmet_prerequisites = PrerequisiteService::new(self.db.clone())
+            .unmet_prerequisites_for_exams(&exam_ids, user_id)
+            .await?;
+        let now = Utc::now();
+        let mut assigned = Vec::with_capacity(rows.len());
+
+        for row in rows {
+            let attempts_used: i64 = row.get("attempts_used");
+            let best_score: Option<i32> = row.get("best_score");
+            let max_score: i64 = row.get("max_score");
+            let active_attempt_id: Option<Uuid> = row.get("active_attempt_id");
+            let active_started_at: Option<chrono::DateTime<Utc>> = row.get("active_started_at");
+            let exam = self.row_to_exam(row)?;
+
+            let accommodation = accommodations.remove(&exam.id);
+            let window_end = accommodation
+                .as_ref()
+                .map_or(exam.end_time, |a| a.apply_to_end_time(exam.end_time));
+            let availability = ExamAvailability::at(now, exam.start_time, window_end);
+
+            let time_remaining_seconds = match (active_started_at, availability) {
+                (Some(started_at), _) => {
+                    let deadline = ExamAttemptService::attempt_deadline(
+                        started_at,
+                        exam.duration_minutes,
+                        exam.end_time,
+                        accommodation.as_ref(),
+                    );
+                    Some((deadline - now).num_seconds().max(0))
+                }
+                (None, ExamAvailability::Open) => Some((window_end - now).num_seconds()),
+                (None, _) => None,
+            };
+
+            let lock_reasons = unmet_prerequisites.remove(&exam.id).unwrap_or_default();
+
+            assigned.push(AssignedExamResponse {
+                locked: !lock_reasons.is_empty(),
+                lock_reasons,
+                exam: exam.into(),
+                availability,
+                window_end,
+                time_remaining_seconds,
+                attempts_used,
+                attempts_allowed: MAX_ATTEMPTS_PER_EXAM,
+                active_attempt_id,
+                best_score,
+                max_score,
+            });
+        }
+
+        Ok(assigned)
+    }
+
+    /// Get template exams for the template gallery
+    pub async fn get_templates(&self, pagination: &crate::utils::pagination::Pagination) -> AppResult<Vec<ExamResponse>> {
+        let rows = sqlx::query(&format!(
+            r#"
+            SELECT {EXAM_COLUMNS}
+            FROM exams
+            WHERE deleted_at IS NULL AND is_template = true
+            ORDER BY title
+            LIMIT $1 OFFSET $2
+            "#
+        ))
+        .bind(pagination.limit_or_default(20))
+        .bind(pagination.skip_or_default())
+        .fetch_all(&self.db.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| Ok(self.row_to_exam(row)?.into()))
+            .collect()
+    }
+
+    /// Copy an exam with its sections, questions, choices, draw rules and optionally its class
+    /// assignments into a new exam, all in one transaction
+    pub async fn clone_exam(
+        &self,
+        source_exam_id: Uuid,
+        request: CloneExamRequest,
+        created_by: Uuid,
+    ) -> AppResult<CloneExamResponse> {
+        if request.end_time <= request.start_time {
+            return Err(AppError::BadRequest("end_time must be after start_time".to_string()));
+        }
+
+        let mut tx = self.db.pool.begin().await?;
+
+        let source_row = sqlx::query(&format!("SELECT {EXAM_COLUMNS} FROM exams WHERE id = $1 AND deleted_at IS NULL"))
+            .bind(source_exam_id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Exam not found".to_string()))?;
+        let source = self.row_to_exam(source_row)?;
+
+        let exam_row = sqlx::query(&format!(
+            r#"
+            INSERT INTO exams (title, description, created_by, duration_minutes, start_time, end_time, is_active, category, difficulty, is_template,
+                               pass_mark, pass_mark_type, grade_scale_id, shuffle_questions, shuffle_choices,
+                               wrong_answer_penalty, score_floor, review_policy)
+            VALUES ($1, $2, $3, $4, $5, $6, true, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17)
+            RETURNING {EXAM_COLUMNS}
+            "#
+        ))
+        .bind(request.title.unwrap_or(source.title))
+        .bind(source.description)
+        .bind(created_by)
+        .bind(request.duration_minutes.unwrap_or(source.duration_minutes))
+        .bind(request.start_time)
+        .bind(request.end_time)
+        .bind(source.category)
+        .bind(source.difficulty)
+        .bind(request.as_template)
+        .bind(source.pass_mark)
+        .bind(source.pass_mark_type.as_str())
+        .bind(source.grade_scale_id)
+        .bind(source.shuffle_questions)
+        .bind(source.shuffle_choices)
+        .bind(source.wrong_answer_penalty)
+        .bind(source.score_floor.as_str())
+        .bind(source.review_policy.as_str())
+        .fetch_one(&mut *tx)
+        .await?;
+        let exam = self.row_to_exam(exam_row)?;
+
+        let section_ids: Vec<Uuid> = sqlx::query("SELECT id FROM exam_sections WHERE exam_id = $1")
+            .bind(source_exam_id)
+            .fetch_all(&mut *tx)
+            .await?
+            .into_iter()
+            .map(|row| row.get("id"))
+            .collect();
+
+        let mut section_map = HashMap::new();
+        for section_id in section_ids {
+            let new_section = sqlx::query(
+                r#"
+                INSERT INTO exam_sections (exam_id, title, instructions, position, time_limit_minutes, draw_count)
+                SELECT $1, title, instructions, position, time_limit_minutes, draw_count
+                FROM exam_sections
+                WHERE id = $2
+                RETURNING id
+                "#,
+            )
+            .bind(exam.id)
+            .bind(section_id)
+            .fetch_one(&mut *tx)
+            .await?;
+            section_map.insert(section_id, new_section.get::<Uuid, _>("id"));
+        }
+
+        let questions: Vec<(Uuid, Option<Uuid>)> = sqlx::query(
+            "SELECT id, section_id FROM questions WHERE exam_id = $1 AND deleted_at IS NULL ORDER BY id",
+        )
+            .bind(source_exam_id)
+            .fetch_all(&mut *tx)
+            .await?
+            .into_iter()
+            .map(|row| (row.get("id"), row.get("section_id")))
+            .collect();
+
+        for (question_id, section_id) in &questions {
+            let new_question = sqlx::query(
+                r#"
+                INSERT INTO questions (exam_id, question_text, question_type, content_format, correct_answer, score, section_id, pool, difficulty,
+                                       tags, topic, scoring_policy, accepted_answers, numeric_answer, matching_pairs,
+                                       ordering_items, cloze_gaps, explanation, attachment_ids)
+                SELECT $1, question_text, question_type, content_format, correct_answer, score, $3, pool, difficulty, tags, topic,
+                       scoring_policy, accepted_answers, numeric_answer, matching_pairs, ordering_items, cloze_gaps,
+                       explanation, attachment_ids
+                FROM questions
+                WHERE id = $2
+                RETURNING id
+                "#,
+            )
+            .bind(exam.id)
+            .bind(question_id)
+            .bind(section_id.and_then(|id| section_map.get(&id).copied()))
+            .fetch_one(&mut *tx)
+            .await?;
+            let new_question_id: Uuid = new_question.get("id");
+
+            sqlx::query(
+                r#"
+                INSERT INTO choices (question_id, choice_text, is_correct, pin_to_bottom, feedback, attachment_ids, created_at)
+                SELECT $1, choice_text, is_correct, pin_to_bottom, feedback, attachment_ids, created_at
+                FROM choices
+                WHERE question_id = $2
+                "#,
+            )
+            .bind(new_question_id)
+            .bind(question_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        let draw_rules: Vec<(Uuid, Option<Uuid>)> =
+            sqlx::query("SELECT id, section_id FROM exam_draw_rules WHERE exam_id = $1 ORDER BY created_at")
+                .bind(source_exam_id)
+                .fetch_all(&mut *tx)
+                .await?
+                .into_iter()
+                .map(|row| (row.get("id"), row.get("section_id")))
+                .collect();
+
+        for (rule_id, section_id) in &draw_rules {
+            sqlx::query(
+                r#"
+                INSERT INTO exam_draw_rules (exam_id, section_id, pool, difficulty, draw_count, created_at)
+                SELECT $1, $3, pool, difficulty, draw_count, created_at
+                FROM exam_draw_rules
+                WHERE id = $2
+                "#,
+            )
+            .bind(exam.id)
+            .bind(rule_id)
+            .bind(section_id.and_then(|id| section_map.get(&id).copied()))
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        // Bank questions stay shared: the copy links the same questions
+        let links: Vec<(Uuid, Option<Uuid>)> =
+            sqlx::query("SELECT question_id, section_id FROM exam_question_links WHERE exam_id = $1")
+                .bind(source_exam_id)
+                .fetch_all(&mut *tx)
+                .await?
+                .into_iter()
+                .map(|row| (row.get("question_id"), row.get("section_id")))
+                .collect();
+
+        for (question_id, section_id) in &links {
+            sqlx::query(
+                r#"
+                INSERT INTO exam_question_links (exam_id, question_id, section_id, score_override)
+                SELECT $1, question_id, $3, score_override
+                FROM exam_question_links
+                WHERE exam_id = $4 AND question_id = $2
+                "#,
+            )
+            .bind(exam.id)
+            .bind(question_id)
+            .bind(section_id.and_then(|id| section_map.get(&id).copied()))
+            .bind(source_exam_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        // Templates are never assigned to classes
+        let assignments_copied = if request.include_assignments && !request.as_template {
+            sqlx::query(
+                r#"
+                INSERT INTO exam_assignments (exam_id, class_id, created_at)
+                SELECT $1, class_id, $2
+                FROM exam_assignments
+                WHERE exam_id = $3
+                "#,
+            )
+            .bind(exam.id)
+            .bind(chrono::Utc::now())
+            .bind(source_exam_id)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected()
+        } else {
+            0
+        };
+
+        tx.commit().await?;
+
+        Ok(CloneExamResponse {
+            exam: exam.into(),
+            source_exam_id,
+            questions_copied: questions.len(),
+            assignments_copied,
+        })
+    }
+
+    /// Move an exam to the trash. It can be restored until it is purged.
+    pub async fn delete_exam(&self, id: Uuid, deleted_by: Uuid) -> AppResult<()> {
+        let result = sqlx::query(
+            r#"UPDATE exams SET deleted_at = NOW(), deleted_by = $2 WHERE id = $1 AND deleted_at IS NULL"#,
+        )
             .bind(id)
+            .bind(deleted_by)
             .execute(&self.db.pool)
             .await?;
 
@@ -140,6 +569,13 @@ LIMIT $1 OFFSET $2
         &self,
         request: CreateExamAssignmentRequest,
     ) -> AppResult<ExamAssignmentsResponse> {
+        let exam = self.get_exam_by_id(request.exam_id).await?;
+        if exam.is_template {
+            return Err(AppError::BadRequest(
+                "Template exams cannot be assigned to classes".to_string(),
+            ));
+        }
+
         let row = sqlx::query(
             r#"
         INSERT INTO exam_assignments (class_id, exam_id, created_at)
@@ -147,8 +583,8 @@ LIMIT $1 OFFSET $2
         RETURNING exam_id, class_id, created_at
         "#,
         )
-        .bind(&request.class_id)
-        .bind(&request.exam_id)
+        .bind(request.class_id)
+        .bind(request.exam_id)
         .bind(chrono::Utc::now())
         .fetch_one(&self.db.pool)
         .await?;
@@ -163,8 +599,8 @@ LIMIT $1 OFFSET $2
                 WHERE exam_id = $1 AND class_id = $2
             "#,
         )
-        .bind(&request.exam_id)
-        .bind(&request.class_id)
+        .bind(request.exam_id)
+        .bind(request.class_id)
         .execute(&self.db.pool)
         .await?;
 
@@ -175,6 +611,22 @@ LIMIT $1 OFFSET $2
         Ok(())
     }
 
+    /// Validate the pass mark and grade scale of an exam
+    async fn check_grading(
+        &self,
+        pass_mark: Option<i32>,
+        pass_mark_type: PassMarkType,
+        grade_scale_id: Option<Uuid>,
+    ) -> AppResult<()> {
+        if pass_mark_type == PassMarkType::Percent && pass_mark.is_some_and(|mark| mark > 100) {
+            return Err(AppError::BadRequest("A percentage pass mark cannot exceed 100".to_string()));
+        }
+        if let Some(scale_id) = grade_scale_id {
+            GradeScaleService::new(self.db.clone()).ensure_exists(scale_id).await?;
+        }
+        Ok(())
+    }
+
     fn row_to_exam(&self, row: PgRow) -> Result<Exam, sqlx::Error> {
         Ok(Exam {
             id: row.get("id"),
@@ -187,6 +639,15 @@ LIMIT $1 OFFSET $2
             is_active: row.get("is_active"),
             category: row.get("category"),
             difficulty: row.get("difficulty"),
+            is_template: row.get("is_template"),
+            pass_mark: row.get("pass_mark"),
+            pass_mark_type: row.get::<String, _>("pass_mark_type").parse().unwrap_or_default(),
+            grade_scale_id: row.get("grade_scale_id"),
+            shuffle_questions: row.get("shuffle_questions"),
+            shuffle_choices: row.get("shuffle_choices"),
+            wrong_answer_penalty: row.get("wrong_answer_penalty"),
+            score_floor: row.get::<String, _>("score_floor").parse().unwrap_or_default(),
+            review_policy: row.get::<String, _>("review_policy").parse().unwrap_or_default(),
         })
     }
 
@@ -1,19 +1,54 @@
 use crate::database::Database;
 use crate::errors::{AppError, AppResult};
+use crate::models::accommodation::Accommodation;
 use crate::models::attempt::{
-    Answer, AnswerResponse, ExamAttempt, ExamAttemptResponse,
-    ExamAttemptWithAnswers, StartExamAttemptRequest, SubmitExamAttemptRequest,
+    merge_answers, Answer, AnswerDraft, AnswerFeedback, AnswerResponse, AnswerSubmission, AttemptQuestionOrder, ExamAttempt,
+    ExamAttemptResponse, ExamAttemptWithAnswers, SaveAnswerRequest, StartExamAttemptRequest, SubmitExamAttemptRequest,
 };
 use crate::models::progress::{CourseType, CreateProgressRequest, ProgressStatus, UpdateProgressRequest};
+use crate::models::section::{AttemptSection, AttemptSectionResponse};
+use crate::models::exams::{NegativeMarking, PassMarkType, ReviewPolicy};
+use crate::models::grade_scale::{grade_for, score_percent};
+use crate::models::grading::grade_answer;
+use crate::models::question::{QuestionResponse, QuestionType};
+use crate::services::access::ExamAccessService;
+use crate::services::accommodation::AccommodationService;
+use crate::services::draw_rule::DrawRuleService;
+use crate::services::grade_scale::GradeScaleService;
+use crate::services::prerequisite::PrerequisiteService;
 use crate::services::progress::ProgressService;
-use chrono::Utc;
-use sqlx::{postgres::PgRow, Row};
+use crate::services::question::QuestionService;
+use crate::services::section::SectionService;
+use crate::utils::shuffle::seeded_shuffle;
+use chrono::{DateTime, Duration, Utc};
+use sqlx::{postgres::PgRow, Postgres, Row, Transaction};
+use std::collections::HashMap;
+use std::net::IpAddr;
 use uuid::Uuid;
 
+const ATTEMPT_COLUMNS: &str =
+    "id, user_id, exam_id, started_at, submitted_at, score_total, max_score, shuffle_seed, client_ip, submitted_ip, passed, grade, auto_submitted";
+
+const DRAFT_COLUMNS: &str = "question_id, answer_text, choice_ids, response, version, saved_at";
+
+/// Number of attempts a student may make on one exam
+pub const MAX_ATTEMPTS_PER_EXAM: i64 = 1;
+
 pub struct ExamAttemptService {
     db: Database,
 }
 
+/// Exam settings an attempt is timed and graded by
+struct ExamGrading {
+    end_time: DateTime<Utc>,
+    duration_minutes: i32,
+    title: String,
+    pass_mark: Option<i32>,
+    pass_mark_type: PassMarkType,
+    grade_scale_id: Option<Uuid>,
+    negative_marking: NegativeMarking,
+}
+
 impl ExamAttemptService {
     pub fn new(db: Database) -> Self {
         Self { db }
@@ -24,25 +59,42 @@ impl ExamAttemptService {
         &self,
         request: StartExamAttemptRequest,
         user_id: Uuid,
+        client_ip: IpAddr,
     ) -> AppResult<ExamAttemptResponse> {
         // Check if exam exists and is active
         let exam_row = sqlx::query(
-            "SELECT id, title FROM exams WHERE id = $1 AND is_active = true AND start_time <= NOW() AND end_time >= NOW()"
+            "SELECT id, title, duration_minutes, start_time, end_time, shuffle_questions FROM exams WHERE id = $1 AND is_active = true AND is_template = false AND deleted_at IS NULL"
         )
         .bind(request.exam_id)
         .fetch_optional(&self.db.pool)
-        .await?;
+        .await?
+        .ok_or_else(|| AppError::NotFound("Exam not found or not active".to_string()))?;
 
-        let exam_title = match exam_row {
-            Some(row) => row.get::<String, _>("title"),
-            None => return Err(AppError::NotFound("Exam not found or not active".to_string())),
-        };
+        let exam_title: String = exam_row.get("title");
+        let duration_minutes: i32 = exam_row.get("duration_minutes");
+        let start_time: DateTime<Utc> = exam_row.get("start_time");
+        let end_time: DateTime<Utc> = exam_row.get("end_time");
+        let shuffle_questions: bool = exam_row.get("shuffle_questions");
+
+        // The exam window may be extended for students with an accommodation
+        let accommodation = AccommodationService::new(self.db.clone())
+            .get_effective_accommodation(user_id, request.exam_id)
+            .await?;
+        let window_end = accommodation
+            .as_ref()
+            .map_or(end_time, |a| a.apply_to_end_time(end_time));
+
+        let now = Utc::now();
+        if now < start_time || now > window_end {
+            return Err(AppError::NotFound("Exam not found or not active".to_string()));
+        }
 
         // Check if user has access to this exam (through class assignments)
         let has_access = sqlx::query(
             r#"
             SELECT 1 FROM exam_assignments ea
             JOIN class_members cm ON ea.class_id = cm.class_id
+            JOIN classes c ON c.id = ea.class_id AND c.deleted_at IS NULL
             WHERE ea.exam_id = $1 AND cm.user_id = $2
             "#
         )
@@ -55,34 +107,93 @@ impl ExamAttemptService {
             return Err(AppError::Forbidden);
         }
 
-        // Check if user already has an attempt for this exam
-        let existing_attempt = sqlx::query(
-            "SELECT id FROM exam_attempts WHERE exam_id = $1 AND user_id = $2"
+        let unmet = PrerequisiteService::new(self.db.clone())
+            .unmet_prerequisites(request.exam_id, user_id)
+            .await?;
+        if !unmet.is_empty() {
+            return Err(AppError::ExamLocked(unmet));
+        }
+
+        ExamAccessService::new(self.db.clone())
+            .check_access(request.exam_id, user_id, client_ip, request.access_code.as_deref(), None)
+            .await?;
+
+        // Check if user already used all attempts for this exam
+        let attempts_used: i64 = sqlx::query(
+            "SELECT COUNT(*) AS attempts_used FROM exam_attempts WHERE exam_id = $1 AND user_id = $2"
         )
         .bind(request.exam_id)
         .bind(user_id)
-        .fetch_optional(&self.db.pool)
-        .await?;
+        .fetch_one(&self.db.pool)
+        .await?
+        .get("attempts_used");
 
-        if existing_attempt.is_some() {
+        if attempts_used >= MAX_ATTEMPTS_PER_EXAM {
             return Err(AppError::Conflict("User already has an attempt for this exam".to_string()));
         }
 
+        // Draw the questions of this attempt; they stay fixed until it is submitted
+        let mut question_ids = DrawRuleService::new(self.db.clone())
+            .draw_for_attempt(request.exam_id)
+            .await?;
+        let shuffle_seed: i64 = rand::random();
+        if shuffle_questions {
+            seeded_shuffle(&mut question_ids, shuffle_seed as u64);
+        }
+
+        let mut tx = self.db.pool.begin().await?;
+
         // Create new exam attempt
-        let row = sqlx::query(
+        let attempt_id: Uuid = sqlx::query(
             r#"
-            INSERT INTO exam_attempts (user_id, exam_id, started_at)
-            VALUES ($1, $2, $3)
-            RETURNING id, user_id, exam_id, started_at, submitted_at, score_total
+            INSERT INTO exam_attempts (user_id, exam_id, started_at, client_ip, access_code, shuffle_seed)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING id
             "#
         )
         .bind(user_id)
         .bind(request.exam_id)
-        .bind(Utc::now())
-        .fetch_one(&self.db.pool)
+        .bind(now)
+        .bind(client_ip.to_string())
+        .bind(request.access_code.map(|code| code.trim().to_uppercase()))
+        .bind(shuffle_seed)
+        .fetch_one(&mut *tx)
+        .await?
+        .get("id");
+
+        for (position, question_id) in question_ids.iter().enumerate() {
+            // Pin the revision the student gets to see and the score it is
+            // worth in this exam
+            sqlx::query(
+                r#"
+                INSERT INTO attempt_questions (attempt_id, question_id, position, revision, score)
+                SELECT $1, $2, $3, revision, COALESCE(score, 0) FROM exam_questions WHERE id = $2 AND exam_id = $4
+                "#,
+            )
+            .bind(attempt_id)
+            .bind(question_id)
+            .bind(position as i32)
+            .bind(request.exam_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        let row = sqlx::query(&format!(
+            r#"
+            UPDATE exam_attempts
+            SET max_score = (SELECT COALESCE(SUM(score), 0)::INT FROM attempt_questions WHERE attempt_id = $1)
+            WHERE id = $1
+            RETURNING {ATTEMPT_COLUMNS}
+            "#
+        ))
+        .bind(attempt_id)
+        .fetch_one(&mut *tx)
         .await?;
 
+        tx.commit().await?;
+
         let attempt = self.row_to_exam_attempt(row)?;
+        let deadline = Self::attempt_deadline(now, duration_minutes, end_time, accommodation.as_ref());
         
         // Create progress entry for starting the exam
         if let Err(e) = self.create_exam_progress(user_id, exam_title).await {
@@ -90,19 +201,20 @@ impl ExamAttemptService {
             eprintln!("Failed to create progress entry: {}", e);
         }
         
-        Ok(attempt.into())
+        Ok(ExamAttemptResponse::from(attempt).with_deadline(deadline))
     }
 
     /// Submit answers for an exam attempt
     pub async fn submit_exam_attempt(
         &self,
-        request: SubmitExamAttemptRequest,
+        mut request: SubmitExamAttemptRequest,
         user_id: Uuid,
+        client_ip: IpAddr,
     ) -> AppResult<ExamAttemptResponse> {
         // Get the attempt and verify ownership
-        let attempt_row = sqlx::query(
-            "SELECT id, user_id, exam_id, started_at, submitted_at, score_total FROM exam_attempts WHERE id = $1 AND user_id = $2"
-        )
+        let attempt_row = sqlx::query(&format!(
+            "SELECT {ATTEMPT_COLUMNS} FROM exam_attempts WHERE id = $1 AND user_id = $2"
+        ))
         .bind(request.attempt_id)
         .bind(user_id)
         .fetch_optional(&self.db.pool)
@@ -117,128 +229,295 @@ impl ExamAttemptService {
         }
 
         // Check if exam time has expired
-        let exam_row = sqlx::query(
-            "SELECT end_time, duration_minutes, title FROM exams WHERE id = $1"
-        )
-        .bind(attempt.exam_id)
-        .fetch_one(&self.db.pool)
-        .await?;
+        let exam = self.get_exam_grading(attempt.exam_id).await?;
 
-        let end_time: chrono::DateTime<Utc> = exam_row.get("end_time");
-        let duration_minutes: i32 = exam_row.get("duration_minutes");
-        let exam_title: String = exam_row.get("title");
-        
-        let now = Utc::now();
-        let max_end_time = attempt.started_at.unwrap() + chrono::Duration::minutes(duration_minutes as i64);
-        
-        if now > end_time || now > max_end_time {
+        let accommodation = AccommodationService::new(self.db.clone())
+            .get_effective_accommodation(user_id, attempt.exam_id)
+            .await?;
+
+        let deadline = Self::attempt_deadline(
+            attempt.started_at.unwrap(),
+            exam.duration_minutes,
+            exam.end_time,
+            accommodation.as_ref(),
+        );
+
+        if Utc::now() > deadline {
             return Err(AppError::BadRequest("Exam time has expired".to_string()));
         }
 
-        // Get maximum possible score for the exam
-        let max_score_row = sqlx::query(
-            "SELECT COALESCE(SUM(score), 0) as max_score FROM questions WHERE exam_id = $1"
-        )
-        .bind(attempt.exam_id)
-        .fetch_one(&self.db.pool)
+        // The code the attempt was started with stays valid after a rotation
+        let start_code: Option<String> = sqlx::query("SELECT access_code FROM exam_attempts WHERE id = $1")
+            .bind(attempt.id)
+            .fetch_one(&self.db.pool)
+            .await?
+            .get("access_code");
+        ExamAccessService::new(self.db.clone())
+            .check_access(
+                attempt.exam_id,
+                user_id,
+                client_ip,
+                request.access_code.as_deref(),
+                start_code.as_deref(),
+            )
+            .await?;
+
+        // Questions of a closed section keep the answers saved while it was
+        // open; answers to them in the submission are ignored
+        let closed_sections = self.get_closed_sections(&attempt, Utc::now()).await?;
+        if !closed_sections.is_empty() {
+            let question_ids: Vec<Uuid> = request.answers.iter().map(|answer| answer.question_id).collect();
+            let closed_questions: Vec<Uuid> = sqlx::query(
+                "SELECT id FROM exam_questions WHERE exam_id = $1 AND id = ANY($2) AND section_id = ANY($3)",
+            )
+            .bind(attempt.exam_id)
+            .bind(&question_ids)
+            .bind(&closed_sections)
+            .fetch_all(&self.db.pool)
+            .await?
+            .into_iter()
+            .map(|row| row.get("id"))
+            .collect();
+            request
+                .answers
+                .retain(|answer| !closed_questions.contains(&answer.question_id));
+        }
+
+        // Saved answers count unless the submission answers the question again
+        let saved = self.get_saved_answers(attempt.id).await?;
+        let answers = merge_answers(saved.into_iter().map(Into::into).collect(), request.answers);
+
+        let updated_attempt = self
+            .finalize_attempt(&attempt, exam, answers, Some(client_ip.to_string()), false)
+            .await?;
+
+        Ok(updated_attempt.into())
+    }
+
+    /// Submit every attempt that is still open after its deadline with the
+    /// answers saved so far. Returns the number of attempts submitted.
+    pub async fn auto_submit_expired(&self) -> AppResult<usize> {
+        // Accommodations only extend the deadline, so this finds every
+        // candidate; the exact deadline is checked per attempt
+        let rows = sqlx::query(&format!(
+            r#"
+            SELECT {ATTEMPT_COLUMNS}
+            FROM exam_attempts
+            WHERE submitted_at IS NULL
+              AND started_at IS NOT NULL
+              AND exam_id IN (
+                  SELECT e.id FROM exams e
+                  WHERE e.end_time < NOW()
+                     OR exam_attempts.started_at + make_interval(mins => e.duration_minutes) < NOW()
+              )
+            ORDER BY started_at
+            "#
+        ))
+        .fetch_all(&self.db.pool)
         .await?;
-        
-        let max_score: i32 = max_score_row.get("max_score");
+
+        let now = Utc::now();
+        let mut submitted = 0;
+        for row in rows {
+            let attempt = self.row_to_exam_attempt(row)?;
+            let Some(deadline) = self.deadline_for_attempt(&attempt).await? else {
+                continue;
+            };
+            if now <= deadline {
+                continue;
+            }
+
+            let exam = self.get_exam_grading(attempt.exam_id).await?;
+            let answers = self
+                .get_saved_answers(attempt.id)
+                .await?
+                .into_iter()
+                .map(Into::into)
+                .collect();
+
+            // One attempt that cannot be graded must not hold up the others
+            match self.finalize_attempt(&attempt, exam, answers, None, true).await {
+                Ok(_) => submitted += 1,
+                Err(e) => tracing::error!("Failed to auto-submit exam attempt {}: {}", attempt.id, e),
+            }
+        }
+
+        Ok(submitted)
+    }
+
+    /// Grade the answers of an open attempt, record its score and update
+    /// the student's progress. Fails if the attempt was submitted meanwhile.
+    async fn finalize_attempt(
+        &self,
+        attempt: &ExamAttempt,
+        exam: ExamGrading,
+        answers: Vec<AnswerSubmission>,
+        submitted_ip: Option<String>,
+        auto_submitted: bool,
+    ) -> AppResult<ExamAttempt> {
+        let now = Utc::now();
+
+        // Maximum possible score of the questions drawn for this attempt
+        let max_score = attempt.max_score.unwrap_or(0);
 
         // Start transaction
         let mut tx = self.db.pool.begin().await?;
 
         // Insert answers and calculate score
+        let question_service = QuestionService::new(self.db.clone());
         let mut total_score = 0i32;
+        let mut total_penalty = 0f64;
 
-        for answer_submission in request.answers {
-            // Get question details
-            let question_row = sqlx::query(
-                "SELECT id, correct_answer, score, question_type FROM questions WHERE id = $1 AND exam_id = $2"
-            )
-            .bind(answer_submission.question_id)
-            .bind(attempt.exam_id)
-            .fetch_optional(&mut *tx)
-            .await?
-            .ok_or_else(|| AppError::NotFound("Question not found".to_string()))?;
-
-            let correct_answer: Option<String> = question_row.get("correct_answer");
+        for answer_submission in answers {
+            // Grade against the revision the student saw, worth the score
+            // the question had in this exam when the attempt started
+            let question_row =
+                sqlx::query("SELECT score, revision FROM attempt_questions WHERE attempt_id = $1 AND question_id = $2")
+                    .bind(attempt.id)
+                    .bind(answer_submission.question_id)
+                    .fetch_optional(&mut *tx)
+                    .await?
+                    .ok_or_else(|| AppError::NotFound("Question not found".to_string()))?;
             let question_score: i32 = question_row.get("score");
-            let question_type: String = question_row.get("question_type");
-
-            // Determine if answer is correct and calculate score
-            let (is_correct, score_awarded) = match question_type.as_str() {
-                "multiple_choice" | "true_false" => {
-                    if let Some(ref correct) = correct_answer {
-                        let is_correct = answer_submission.answer_text.trim().eq_ignore_ascii_case(correct.trim());
-                        (Some(is_correct), if is_correct { question_score } else { 0 })
-                    } else {
-                        (None, 0)
-                    }
-                }
-                "essay" => {
-                    // Essay questions need manual grading
-                    (None, 0)
-                }
-                _ => (None, 0)
-            };
+            let question = question_service
+                .get_revision(answer_submission.question_id, question_row.get("revision"))
+                .await?
+                .question;
 
+            // An answer that does not fit the question is stored ungraded
+            // rather than holding up the submission
+            let graded = grade_answer(&question, question_score, &answer_submission).unwrap_or_default();
+            let (is_correct, score_awarded) = (graded.is_correct, graded.score_awarded);
+
+            let penalty = exam.negative_marking.penalty(is_correct, answer_submission.is_blank(), score_awarded, question_score);
             total_score += score_awarded;
+            total_penalty += penalty;
 
             // Insert answer
             sqlx::query(
                 r#"
-                INSERT INTO answers (attempt_id, question_id, answer_text, is_correct, score_awarded)
-                VALUES ($1, $2, $3, $4, $5)
+                INSERT INTO answers (attempt_id, question_id, answer_text, choice_ids, is_correct, score_awarded,
+                                     incorrect_reason, response, gap_results, penalty)
+                VALUES ($1, $2, $3, $6, $4, $5, $7, $8, $9, $10)
                 "#
             )
-            .bind(request.attempt_id)
+            .bind(attempt.id)
             .bind(answer_submission.question_id)
             .bind(&answer_submission.answer_text)
             .bind(is_correct)
             .bind(score_awarded)
+            .bind(graded.choice_ids)
+            .bind(graded.incorrect_reason)
+            .bind(
+                answer_submission
+                    .response
+                    .as_ref()
+                    .filter(|_| {
+                        matches!(
+                            question.question_type,
+                            QuestionType::Matching | QuestionType::Ordering | QuestionType::Cloze
+                        )
+                    })
+                    .map(|response| serde_json::to_value(response).unwrap()),
+            )
+            .bind(graded.gap_results.map(|results| serde_json::to_value(results).unwrap()))
+            .bind(penalty)
             .execute(&mut *tx)
             .await?;
         }
+        let total_score = exam.negative_marking.total(total_score, total_penalty);
+
+        let passed = exam.pass_mark.map(|mark| exam.pass_mark_type.is_passed(mark, total_score, max_score));
+        let grade = match exam.grade_scale_id {
+            Some(scale_id) => {
+                let bands = GradeScaleService::new(self.db.clone()).get_bands(scale_id).await?;
+                grade_for(&bands, score_percent(total_score, max_score))
+            }
+            None => None,
+        };
+
+        // Close the section the student was working on
+        sqlx::query("UPDATE attempt_sections SET finished_at = $1 WHERE attempt_id = $2 AND finished_at IS NULL")
+            .bind(now)
+            .bind(attempt.id)
+            .execute(&mut *tx)
+            .await?;
+
+        // The saved answers are final now
+        sqlx::query("DELETE FROM answer_drafts WHERE attempt_id = $1")
+            .bind(attempt.id)
+            .execute(&mut *tx)
+            .await?;
 
-        // Update attempt with submission time and score
-        let updated_row = sqlx::query(
+        // Update attempt with submission time and score. A concurrent submit
+        // of the same attempt rolls this one back.
+        let updated_row = sqlx::query(&format!(
             r#"
             UPDATE exam_attempts
-            SET submitted_at = $1, score_total = $2
-            WHERE id = $3
-            RETURNING id, user_id, exam_id, started_at, submitted_at, score_total
+            SET submitted_at = $1, score_total = $2, submitted_ip = $4, passed = $5, grade = $6, auto_submitted = $7
+            WHERE id = $3 AND submitted_at IS NULL
+            RETURNING {ATTEMPT_COLUMNS}
             "#
-        )
-        .bind(Utc::now())
+        ))
+        .bind(now)
         .bind(total_score)
-        .bind(request.attempt_id)
-        .fetch_one(&mut *tx)
-        .await?;
+        .bind(attempt.id)
+        .bind(submitted_ip)
+        .bind(passed)
+        .bind(&grade)
+        .bind(auto_submitted)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::BadRequest("Exam attempt already submitted".to_string()))?;
 
         tx.commit().await?;
 
         let updated_attempt = self.row_to_exam_attempt(updated_row)?;
-        
+
         // Update progress entry for completing the exam
-        if let Err(e) = self.update_exam_progress(user_id, exam_title, total_score, max_score).await {
+        if let Err(e) = self.update_exam_progress(attempt.user_id, exam.title, total_score, max_score, passed).await {
             // Log the error but don't fail the exam submission
             eprintln!("Failed to update progress entry: {}", e);
         }
-        
-        Ok(updated_attempt.into())
+
+        Ok(updated_attempt)
+    }
+
+    async fn get_exam_grading(&self, exam_id: Uuid) -> AppResult<ExamGrading> {
+        let exam_row = sqlx::query(
+            "SELECT end_time, duration_minutes, title, pass_mark, pass_mark_type, grade_scale_id, wrong_answer_penalty, score_floor FROM exams WHERE id = $1"
+        )
+        .bind(exam_id)
+        .fetch_one(&self.db.pool)
+        .await?;
+
+        Ok(ExamGrading {
+            end_time: exam_row.get("end_time"),
+            duration_minutes: exam_row.get("duration_minutes"),
+            title: exam_row.get("title"),
+            pass_mark: exam_row.get("pass_mark"),
+            pass_mark_type: exam_row
+                .get::<String, _>("pass_mark_type")
+                .parse()
+                .unwrap_or_default(),
+            grade_scale_id: exam_row.get("grade_scale_id"),
+            negative_marking: NegativeMarking {
+                wrong_answer_penalty: exam_row.get("wrong_answer_penalty"),
+                score_floor: exam_row.get::<String, _>("score_floor").parse().unwrap_or_default(),
+            },
+        })
     }
 
     /// Get all attempts for a user
     pub async fn get_user_attempts(&self, user_id: Uuid) -> AppResult<Vec<ExamAttemptResponse>> {
-        let rows = sqlx::query(
+        let rows = sqlx::query(&format!(
             r#"
-            SELECT id, user_id, exam_id, started_at, submitted_at, score_total
+            SELECT {ATTEMPT_COLUMNS}
             FROM exam_attempts
             WHERE user_id = $1
             ORDER BY started_at DESC
             "#
-        )
+        ))
         .bind(user_id)
         .fetch_all(&self.db.pool)
         .await?;
@@ -258,12 +537,12 @@ impl ExamAttemptService {
     pub async fn get_attempt_with_answers(
         &self,
         attempt_id: Uuid,
-        user_id: Uuid,
+        user_id: Option<Uuid>,
     ) -> AppResult<ExamAttemptWithAnswers> {
-        // Get attempt
-        let attempt_row = sqlx::query(
-            "SELECT id, user_id, exam_id, started_at, submitted_at, score_total FROM exam_attempts WHERE id = $1 AND user_id = $2"
-        )
+        // Get attempt; without a user any attempt can be reviewed
+        let attempt_row = sqlx::query(&format!(
+            "SELECT {ATTEMPT_COLUMNS} FROM exam_attempts WHERE id = $1 AND ($2::uuid IS NULL OR user_id = $2)"
+        ))
         .bind(attempt_id)
         .bind(user_id)
         .fetch_optional(&self.db.pool)
@@ -275,7 +554,8 @@ impl ExamAttemptService {
         // Get answers
         let answer_rows = sqlx::query(
             r#"
-            SELECT id, attempt_id, question_id, answer_text, is_correct, score_awarded
+            SELECT id, attempt_id, question_id, answer_text, choice_ids, is_correct, score_awarded, incorrect_reason,
+                   response, gap_results, penalty
             FROM answers
             WHERE attempt_id = $1
             ORDER BY question_id
@@ -285,7 +565,7 @@ impl ExamAttemptService {
         .fetch_all(&self.db.pool)
         .await?;
 
-        let answers: Vec<AnswerResponse> = answer_rows
+        let mut answers: Vec<AnswerResponse> = answer_rows
             .into_iter()
             .map(|row| {
                 let answer = self.row_to_answer(row)?;
@@ -293,22 +573,354 @@ impl ExamAttemptService {
             })
             .collect::<AppResult<Vec<_>>>()?;
 
+        // Teachers always see feedback; students once the review policy allows
+        let feedback_released = match user_id {
+            None => true,
+            Some(_) => {
+                let exam_row = sqlx::query("SELECT end_time, review_policy FROM exams WHERE id = $1")
+                    .bind(attempt.exam_id)
+                    .fetch_one(&self.db.pool)
+                    .await?;
+                let review_policy: ReviewPolicy =
+                    exam_row.get::<String, _>("review_policy").parse().unwrap_or_default();
+                review_policy.is_released(attempt.submitted_at.is_some(), exam_row.get("end_time"), Utc::now())
+            }
+        };
+        if feedback_released {
+            self.attach_feedback(attempt.id, &mut answers).await?;
+        }
+
+        let questions = self.get_question_order(&attempt).await?;
+
         Ok(ExamAttemptWithAnswers {
             attempt: attempt.into(),
             answers,
+            questions,
+            feedback_released,
         })
     }
 
+    /// Fill in each answer's question explanation and the feedback of the
+    /// choices it selected, from the revision the attempt is pinned to
+    async fn attach_feedback(&self, attempt_id: Uuid, answers: &mut [AnswerResponse]) -> AppResult<()> {
+        let revisions: HashMap<Uuid, i32> =
+            sqlx::query("SELECT question_id, revision FROM attempt_questions WHERE attempt_id = $1")
+                .bind(attempt_id)
+                .fetch_all(&self.db.pool)
+                .await?
+                .into_iter()
+                .map(|row| (row.get("question_id"), row.get("revision")))
+                .collect();
+
+        let question_service = QuestionService::new(self.db.clone());
+        for answer in answers.iter_mut() {
+            let Some(&revision) = revisions.get(&answer.question_id) else {
+                continue;
+            };
+            let question = question_service.get_revision(answer.question_id, revision).await?.question;
+            answer.feedback = Some(AnswerFeedback::for_answer(
+                &question,
+                answer.choice_ids.as_deref().unwrap_or_default(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Questions of an attempt with their choices, in the order the student
+    /// got them. Recomputed from the attempt's seed.
+    async fn get_question_order(&self, attempt: &ExamAttempt) -> AppResult<Vec<AttemptQuestionOrder>> {
+        let shuffle_choices: bool = sqlx::query("SELECT shuffle_choices FROM exams WHERE id = $1")
+            .bind(attempt.exam_id)
+            .fetch_one(&self.db.pool)
+            .await?
+            .get("shuffle_choices");
+        let choice_seed = shuffle_choices.then_some(attempt.shuffle_seed);
+
+        let rows = sqlx::query(
+            "SELECT question_id, position, revision FROM attempt_questions WHERE attempt_id = $1 ORDER BY position",
+        )
+        .bind(attempt.id)
+        .fetch_all(&self.db.pool)
+        .await?;
+
+        let question_service = QuestionService::new(self.db.clone());
+        let mut questions = Vec::with_capacity(rows.len());
+        for row in rows {
+            let question_id: Uuid = row.get("question_id");
+            let choice_ids = question_service
+                .get_student_choices(question_id, row.get("revision"), choice_seed)
+                .await?
+                .into_iter()
+                .map(|choice| choice.id)
+                .collect();
+            questions.push(AttemptQuestionOrder {
+                question_id,
+                position: row.get("position"),
+                choice_ids,
+            });
+        }
+        Ok(questions)
+    }
+
+    /// Move every attempt on a question to its current revision and regrade
+    /// the submitted ones, including their totals, pass and grade. Manually
+    /// graded essays keep their scores, and answers saved in open attempts
+    /// are fitted to the new revision. Runs in the transaction of the edit
+    /// that produced `question`. Returns the number of attempts regraded.
+    pub async fn regrade_question(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        question: &QuestionResponse,
+    ) -> AppResult<usize> {
+        let question_id = question.id;
+
+        // Attempts move to the current revision and the score the question
+        // now has in their exam
+        let moved_rows = sqlx::query(
+            r#"
+            UPDATE attempt_questions aq
+            SET revision = $2, score = COALESCE(q.score, 0)
+            FROM exam_attempts a, exam_questions q
+            WHERE aq.question_id = $1 AND a.id = aq.attempt_id AND q.id = aq.question_id AND q.exam_id = a.exam_id
+            RETURNING aq.attempt_id, a.exam_id, a.submitted_at IS NOT NULL AS submitted
+            "#,
+        )
+        .bind(question_id)
+        .bind(question.revision)
+        .fetch_all(&mut **tx)
+        .await?;
+
+        // Every submitted attempt on the question is rescored, answered or
+        // not, since its maximum score may have changed
+        let mut attempts: HashMap<Uuid, Uuid> = HashMap::new();
+        let mut open_attempts: Vec<Uuid> = Vec::new();
+        for row in moved_rows {
+            if row.get("submitted") {
+                attempts.insert(row.get("attempt_id"), row.get("exam_id"));
+            } else {
+                open_attempts.push(row.get("attempt_id"));
+            }
+        }
+
+        // Open attempts are graded out of the new maximum when submitted
+        sqlx::query(
+            r#"
+            UPDATE exam_attempts a
+            SET max_score = (SELECT COALESCE(SUM(score), 0)::INT FROM attempt_questions WHERE attempt_id = a.id)
+            WHERE a.id = ANY($1)
+            "#,
+        )
+        .bind(&open_attempts)
+        .execute(&mut **tx)
+        .await?;
+
+        // Answers saved in open attempts must still be submittable: deleted
+        // choices are deselected and answers that no longer fit are dropped
+        let draft_rows = sqlx::query(
+            r#"
+            SELECT d.attempt_id, d.answer_text, d.choice_ids, d.response, aq.score
+            FROM answer_drafts d
+            JOIN exam_attempts a ON a.id = d.attempt_id
+            JOIN attempt_questions aq ON aq.attempt_id = d.attempt_id AND aq.question_id = d.question_id
+            WHERE d.question_id = $1 AND a.submitted_at IS NULL
+            "#,
+        )
+        .bind(question_id)
+        .fetch_all(&mut **tx)
+        .await?;
+
+        for row in draft_rows {
+            let attempt_id: Uuid = row.get("attempt_id");
+            let saved_choice_ids: Vec<Uuid> = row.get("choice_ids");
+            let choice_ids: Vec<Uuid> = saved_choice_ids
+                .iter()
+                .copied()
+                .filter(|id| question.choices.iter().any(|choice| choice.id == *id))
+                .collect();
+            let answer = AnswerSubmission {
+                question_id,
+                answer_text: row.get("answer_text"),
+                choice_ids,
+                response: row
+                    .get::<Option<serde_json::Value>, _>("response")
+                    .and_then(|response| serde_json::from_value(response).ok()),
+            };
+
+            if grade_answer(question, row.get("score"), &answer).is_err() {
+                sqlx::query("DELETE FROM answer_drafts WHERE attempt_id = $1 AND question_id = $2")
+                    .bind(attempt_id)
+                    .bind(question_id)
+                    .execute(&mut **tx)
+                    .await?;
+            } else if answer.choice_ids != saved_choice_ids {
+                // The version moves on so a tab holding the old answer reloads it
+                sqlx::query(
+                    r#"
+                    UPDATE answer_drafts SET choice_ids = $3, version = version + 1
+                    WHERE attempt_id = $1 AND question_id = $2
+                    "#,
+                )
+                .bind(attempt_id)
+                .bind(question_id)
+                .bind(&answer.choice_ids)
+                .execute(&mut **tx)
+                .await?;
+            }
+        }
+
+        let answer_rows = sqlx::query(
+            r#"
+            SELECT ans.id, ans.attempt_id, ans.answer_text, ans.choice_ids, ans.response, a.exam_id, aq.score
+            FROM answers ans
+            JOIN exam_attempts a ON a.id = ans.attempt_id
+            JOIN attempt_questions aq ON aq.attempt_id = ans.attempt_id AND aq.question_id = ans.question_id
+            WHERE ans.question_id = $1 AND a.submitted_at IS NOT NULL
+            "#,
+        )
+        .bind(question_id)
+        .fetch_all(&mut **tx)
+        .await?;
+
+        let mut negative_marking: HashMap<Uuid, NegativeMarking> = HashMap::new();
+        for row in answer_rows {
+            let exam_id: Uuid = row.get("exam_id");
+            if matches!(question.question_type, QuestionType::Essay) {
+                continue;
+            }
+
+            let marking = match negative_marking.get(&exam_id) {
+                Some(marking) => *marking,
+                None => {
+                    let exam_row = sqlx::query("SELECT wrong_answer_penalty, score_floor FROM exams WHERE id = $1")
+                        .bind(exam_id)
+                        .fetch_one(&mut **tx)
+                        .await?;
+                    let marking = NegativeMarking {
+                        wrong_answer_penalty: exam_row.get("wrong_answer_penalty"),
+                        score_floor: exam_row.get::<String, _>("score_floor").parse().unwrap_or_default(),
+                    };
+                    negative_marking.insert(exam_id, marking);
+                    marking
+                }
+            };
+
+            // Choices the edit removed count as not selected
+            let choice_ids = row
+                .get::<Option<Vec<Uuid>>, _>("choice_ids")
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|id| question.choices.iter().any(|choice| choice.id == *id))
+                .collect();
+            let answer = AnswerSubmission {
+                question_id,
+                answer_text: row.get::<Option<String>, _>("answer_text").unwrap_or_default(),
+                choice_ids,
+                response: row
+                    .get::<Option<serde_json::Value>, _>("response")
+                    .and_then(|response| serde_json::from_value(response).ok()),
+            };
+            let question_score: i32 = row.get("score");
+            // An answer that no longer fits the question, e.g. after its type
+            // changed, is left for manual grading
+            let graded = grade_answer(question, question_score, &answer).unwrap_or_default();
+            let penalty = marking.penalty(graded.is_correct, answer.is_blank(), graded.score_awarded, question_score);
+
+            sqlx::query(
+                r#"
+                UPDATE answers
+                SET is_correct = $2, score_awarded = $3, choice_ids = $4, incorrect_reason = $5, gap_results = $6,
+                    penalty = $7
+                WHERE id = $1
+                "#,
+            )
+            .bind(row.get::<Uuid, _>("id"))
+            .bind(graded.is_correct)
+            .bind(graded.score_awarded)
+            .bind(graded.choice_ids)
+            .bind(graded.incorrect_reason)
+            .bind(graded.gap_results.map(|results| serde_json::to_value(results).unwrap()))
+            .bind(penalty)
+            .execute(&mut **tx)
+            .await?;
+        }
+
+        for (attempt_id, exam_id) in &attempts {
+            self.rescore_attempt(tx, *attempt_id, *exam_id).await?;
+        }
+
+        Ok(attempts.len())
+    }
+
+    /// Recompute the total, maximum score, pass and grade of a submitted
+    /// attempt from its answers
+    async fn rescore_attempt(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        attempt_id: Uuid,
+        exam_id: Uuid,
+    ) -> AppResult<()> {
+        let exam_row = sqlx::query(
+            "SELECT pass_mark, pass_mark_type, grade_scale_id, wrong_answer_penalty, score_floor FROM exams WHERE id = $1",
+        )
+        .bind(exam_id)
+        .fetch_one(&mut **tx)
+        .await?;
+        let pass_mark: Option<i32> = exam_row.get("pass_mark");
+        let pass_mark_type: PassMarkType = exam_row
+            .get::<String, _>("pass_mark_type")
+            .parse()
+            .unwrap_or_default();
+        let grade_scale_id: Option<Uuid> = exam_row.get("grade_scale_id");
+        let negative_marking = NegativeMarking {
+            wrong_answer_penalty: exam_row.get("wrong_answer_penalty"),
+            score_floor: exam_row.get::<String, _>("score_floor").parse().unwrap_or_default(),
+        };
+
+        let totals = sqlx::query(
+            r#"
+            SELECT COALESCE(SUM(score_awarded), 0)::INT AS score_awarded,
+                   COALESCE(SUM(penalty), 0)::DOUBLE PRECISION AS penalties,
+                   (SELECT COALESCE(SUM(score), 0)::INT FROM attempt_questions WHERE attempt_id = $1) AS max_score
+            FROM answers
+            WHERE attempt_id = $1
+            "#,
+        )
+        .bind(attempt_id)
+        .fetch_one(&mut **tx)
+        .await?;
+        let max_score: i32 = totals.get("max_score");
+        let total_score = negative_marking.total(totals.get("score_awarded"), totals.get("penalties"));
+
+        let passed = pass_mark.map(|mark| pass_mark_type.is_passed(mark, total_score, max_score));
+        let grade = match grade_scale_id {
+            Some(scale_id) => {
+                let bands = GradeScaleService::new(self.db.clone()).get_bands(scale_id).await?;
+                grade_for(&bands, score_percent(total_score, max_score))
+            }
+            None => None,
+        };
+
+        sqlx::query("UPDATE exam_attempts SET score_total = $2, max_score = $3, passed = $4, grade = $5 WHERE id = $1")
+            .bind(attempt_id)
+            .bind(total_score)
+            .bind(max_score)
+            .bind(passed)
+            .bind(&grade)
+            .execute(&mut **tx)
+            .await?;
+        Ok(())
+    }
+
     /// Get all attempts for an exam (for teachers/admins)
     pub async fn get_exam_attempts(&self, exam_id: Uuid) -> AppResult<Vec<ExamAttemptResponse>> {
-        let rows = sqlx::query(
+        let rows = sqlx::query(&format!(
             r#"
-            SELECT ea.id, ea.user_id, ea.exam_id, ea.started_at, ea.submitted_at, ea.score_total
-            FROM exam_attempts ea
-            WHERE ea.exam_id = $1
-            ORDER BY ea.started_at DESC
+            SELECT {ATTEMPT_COLUMNS}
+            FROM exam_attempts
+            WHERE exam_id = $1
+            ORDER BY started_at DESC
             "#
-        )
+        ))
         .bind(exam_id)
         .fetch_all(&self.db.pool)
         .await?;
@@ -330,13 +942,13 @@ impl ExamAttemptService {
         user_id: Uuid,
         exam_id: Uuid,
     ) -> AppResult<Option<ExamAttemptResponse>> {
-        let row = sqlx::query(
+        let row = sqlx::query(&format!(
             r#"
-            SELECT id, user_id, exam_id, started_at, submitted_at, score_total
+            SELECT {ATTEMPT_COLUMNS}
             FROM exam_attempts
             WHERE user_id = $1 AND exam_id = $2 AND submitted_at IS NULL
             "#
-        )
+        ))
         .bind(user_id)
         .bind(exam_id)
         .fetch_optional(&self.db.pool)
@@ -344,12 +956,354 @@ impl ExamAttemptService {
 
         if let Some(row) = row {
             let attempt = self.row_to_exam_attempt(row)?;
-            Ok(Some(attempt.into()))
+            let deadline = self.deadline_for_attempt(&attempt).await?;
+            let saved_answers = self.get_saved_answers(attempt.id).await?;
+            let response = ExamAttemptResponse::from(attempt).with_saved_answers(saved_answers);
+            Ok(Some(match deadline {
+                Some(deadline) => response.with_deadline(deadline),
+                None => response,
+            }))
         } else {
             Ok(None)
         }
     }
 
+    /// Save the answer to one question of an attempt in progress. Saving
+    /// again needs the version of the previous save, so a stale tab cannot
+    /// overwrite a newer answer.
+    pub async fn save_answer(
+        &self,
+        attempt_id: Uuid,
+        question_id: Uuid,
+        user_id: Uuid,
+        request: SaveAnswerRequest,
+        client_ip: IpAddr,
+    ) -> AppResult<AnswerDraft> {
+        let attempt = self.get_open_attempt(attempt_id, user_id).await?;
+        let now = Utc::now();
+        if let Some(deadline) = self.deadline_for_attempt(&attempt).await?
+            && now > deadline
+        {
+            return Err(AppError::BadRequest("Exam time has expired".to_string()));
+        }
+        // The access code was checked at start; the network is checked on every save
+        ExamAccessService::new(self.db.clone())
+            .check_network(attempt.exam_id, client_ip)
+            .await?;
+        let closed_sections = self.get_closed_sections(&attempt, now).await?;
+
+        let question_row = sqlx::query(
+            r#"
+            SELECT aq.score, q.section_id, aq.revision
+            FROM exam_questions q
+            JOIN attempt_questions aq ON aq.question_id = q.id
+            WHERE aq.attempt_id = $1 AND q.id = $2 AND q.exam_id = $3
+            "#,
+        )
+        .bind(attempt_id)
+        .bind(question_id)
+        .bind(attempt.exam_id)
+        .fetch_optional(&self.db.pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Question not found".to_string()))?;
+
+        let section_id: Option<Uuid> = question_row.get("section_id");
+        if section_id.is_some_and(|section_id| closed_sections.contains(&section_id)) {
+            return Err(AppError::BadRequest(
+                "Section is finished, past its time limit, or timed and not started".to_string(),
+            ));
+        }
+
+        // Reject an answer now that submitting it would fail later
+        let submission = AnswerSubmission {
+            question_id,
+            answer_text: request.answer_text,
+            choice_ids: request.choice_ids,
+            response: request.response,
+        };
+        let question = QuestionService::new(self.db.clone())
+            .get_revision(question_id, question_row.get("revision"))
+            .await?
+            .question;
+        grade_answer(&question, question_row.get("score"), &submission).map_err(AppError::BadRequest)?;
+
+        let row = sqlx::query(&format!(
+            r#"
+            INSERT INTO answer_drafts (attempt_id, question_id, answer_text, choice_ids, response, version, saved_at)
+            VALUES ($1, $2, $3, $4, $5, 1, $6)
+            ON CONFLICT (attempt_id, question_id) DO UPDATE
+            SET answer_text = EXCLUDED.answer_text, choice_ids = EXCLUDED.choice_ids, response = EXCLUDED.response,
+                version = answer_drafts.version + 1, saved_at = EXCLUDED.saved_at
+            WHERE answer_drafts.version = $7
+            RETURNING {DRAFT_COLUMNS}
+            "#
+        ))
+        .bind(attempt_id)
+        .bind(question_id)
+        .bind(&submission.answer_text)
+        .bind(&submission.choice_ids)
+        .bind(submission.response.as_ref().map(|response| serde_json::to_value(response).unwrap()))
+        .bind(now)
+        .bind(request.version)
+        .fetch_optional(&self.db.pool)
+        .await?;
+
+        match row {
+            Some(row) => self.row_to_draft(row),
+            None => {
+                let current: i32 =
+                    sqlx::query("SELECT version FROM answer_drafts WHERE attempt_id = $1 AND question_id = $2")
+                        .bind(attempt_id)
+                        .bind(question_id)
+                        .fetch_one(&self.db.pool)
+                        .await?
+                        .get("version");
+                Err(AppError::Conflict(format!(
+                    "The answer was saved elsewhere in the meantime; the current version is {}",
+                    current
+                )))
+            }
+        }
+    }
+
+    /// Answers saved during an attempt, in question order
+    async fn get_saved_answers(&self, attempt_id: Uuid) -> AppResult<Vec<AnswerDraft>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT d.question_id, d.answer_text, d.choice_ids, d.response, d.version, d.saved_at
+            FROM answer_drafts d
+            JOIN attempt_questions aq ON aq.attempt_id = d.attempt_id AND aq.question_id = d.question_id
+            WHERE d.attempt_id = $1
+            ORDER BY aq.position
+            "#,
+        )
+        .bind(attempt_id)
+        .fetch_all(&self.db.pool)
+        .await?;
+
+        rows.into_iter().map(|row| self.row_to_draft(row)).collect()
+    }
+
+    /// Record that the student opened a section of an attempt. Any other
+    /// section still open in the attempt is finished at the same moment.
+    pub async fn start_section(
+        &self,
+        attempt_id: Uuid,
+        section_id: Uuid,
+        user_id: Uuid,
+    ) -> AppResult<AttemptSectionResponse> {
+        let attempt = self.get_open_attempt(attempt_id, user_id).await?;
+        let section = SectionService::new(self.db.clone()).get_section_by_id(section_id).await?;
+        if section.exam_id != attempt.exam_id {
+            return Err(AppError::NotFound("Section not found".to_string()));
+        }
+
+        let existing = sqlx::query(
+            "SELECT attempt_id, section_id, started_at, finished_at FROM attempt_sections WHERE attempt_id = $1 AND section_id = $2"
+        )
+        .bind(attempt_id)
+        .bind(section_id)
+        .fetch_optional(&self.db.pool)
+        .await?;
+
+        let attempt_section = match existing {
+            Some(row) => {
+                let attempt_section = self.row_to_attempt_section(row)?;
+                if attempt_section.finished_at.is_some() {
+                    return Err(AppError::Conflict("Section already finished".to_string()));
+                }
+                if !attempt_section.accepts_answers(&section, Utc::now()) {
+                    // Time is up: close the section when its limit passed
+                    sqlx::query("UPDATE attempt_sections SET finished_at = $1 WHERE attempt_id = $2 AND section_id = $3")
+                        .bind(section.deadline(attempt_section.started_at))
+                        .bind(attempt_id)
+                        .bind(section_id)
+                        .execute(&self.db.pool)
+                        .await?;
+                    return Err(AppError::Conflict("Section time limit has passed".to_string()));
+                }
+                attempt_section
+            }
+            None => {
+                let now = Utc::now();
+                let mut tx = self.db.pool.begin().await?;
+
+                sqlx::query("UPDATE attempt_sections SET finished_at = $1 WHERE attempt_id = $2 AND finished_at IS NULL")
+                    .bind(now)
+                    .bind(attempt_id)
+                    .execute(&mut *tx)
+                    .await?;
+
+                let row = sqlx::query(
+                    r#"
+                    INSERT INTO attempt_sections (attempt_id, section_id, started_at)
+                    VALUES ($1, $2, $3)
+                    RETURNING attempt_id, section_id, started_at, finished_at
+                    "#
+                )
+                .bind(attempt_id)
+                .bind(section_id)
+                .bind(now)
+                .fetch_one(&mut *tx)
+                .await?;
+
+                tx.commit().await?;
+                self.row_to_attempt_section(row)?
+            }
+        };
+
+        let attempt_deadline = self.deadline_for_attempt(&attempt).await?;
+        let deadline = section
+            .deadline(attempt_section.started_at)
+            .map(|deadline| attempt_deadline.map_or(deadline, |d| deadline.min(d)));
+
+        Ok(AttemptSectionResponse::new(attempt_section, deadline))
+    }
+
+    /// Record that the student finished a section of an attempt
+    pub async fn finish_section(
+        &self,
+        attempt_id: Uuid,
+        section_id: Uuid,
+        user_id: Uuid,
+    ) -> AppResult<AttemptSectionResponse> {
+        self.get_open_attempt(attempt_id, user_id).await?;
+
+        let row = sqlx::query(
+            r#"
+            UPDATE attempt_sections
+            SET finished_at = $1
+            WHERE attempt_id = $2 AND section_id = $3 AND finished_at IS NULL
+            RETURNING attempt_id, section_id, started_at, finished_at
+            "#
+        )
+        .bind(Utc::now())
+        .bind(attempt_id)
+        .bind(section_id)
+        .fetch_optional(&self.db.pool)
+        .await?
+        .ok_or_else(|| AppError::BadRequest("Section is not in progress".to_string()))?;
+
+        Ok(AttemptSectionResponse::new(self.row_to_attempt_section(row)?, None))
+    }
+
+    /// Start and finish times of the sections of an attempt
+    pub async fn get_attempt_sections(
+        &self,
+        attempt_id: Uuid,
+        user_id: Uuid,
+    ) -> AppResult<Vec<AttemptSectionResponse>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT ats.attempt_id, ats.section_id, ats.started_at, ats.finished_at
+            FROM attempt_sections ats
+            JOIN exam_attempts ea ON ea.id = ats.attempt_id
+            WHERE ats.attempt_id = $1 AND ea.user_id = $2
+            ORDER BY ats.started_at
+            "#
+        )
+        .bind(attempt_id)
+        .bind(user_id)
+        .fetch_all(&self.db.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| Ok(AttemptSectionResponse::new(self.row_to_attempt_section(row)?, None)))
+            .collect()
+    }
+
+    /// Sections of an attempt whose questions do not take answers: finished
+    /// ones, ones past their time limit and timed ones never started
+    async fn get_closed_sections(&self, attempt: &ExamAttempt, now: DateTime<Utc>) -> AppResult<Vec<Uuid>> {
+        let sections = SectionService::new(self.db.clone()).get_sections(attempt.exam_id).await?;
+        if sections.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let attempt_sections = sqlx::query(
+            "SELECT attempt_id, section_id, started_at, finished_at FROM attempt_sections WHERE attempt_id = $1"
+        )
+        .bind(attempt.id)
+        .fetch_all(&self.db.pool)
+        .await?
+        .into_iter()
+        .map(|row| self.row_to_attempt_section(row))
+        .collect::<AppResult<Vec<_>>>()?;
+
+        let mut closed = Vec::new();
+        for section in &sections {
+            let accepts_answers = match attempt_sections.iter().find(|started| started.section_id == section.id) {
+                Some(attempt_section) => attempt_section.accepts_answers(section, now),
+                // The time limit runs from the start of the section, so a
+                // timed section has to be started before it is answered
+                None => section.time_limit_minutes.is_none(),
+            };
+            if !accepts_answers {
+                closed.push(section.id);
+            }
+        }
+
+        Ok(closed)
+    }
+
+    /// Attempt of the user that has not been submitted yet
+    async fn get_open_attempt(&self, attempt_id: Uuid, user_id: Uuid) -> AppResult<ExamAttempt> {
+        let row = sqlx::query(&format!(
+            "SELECT {ATTEMPT_COLUMNS} FROM exam_attempts WHERE id = $1 AND user_id = $2"
+        ))
+        .bind(attempt_id)
+        .bind(user_id)
+        .fetch_optional(&self.db.pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Exam attempt not found".to_string()))?;
+
+        let attempt = self.row_to_exam_attempt(row)?;
+        if attempt.submitted_at.is_some() {
+            return Err(AppError::BadRequest("Exam attempt already submitted".to_string()));
+        }
+
+        Ok(attempt)
+    }
+
+    /// Latest time an attempt can be submitted: the attempt duration counted
+    /// from `started_at`, capped by the close of the exam window. Both are
+    /// adjusted by the student's accommodation when there is one.
+    pub fn attempt_deadline(
+        started_at: DateTime<Utc>,
+        duration_minutes: i32,
+        end_time: DateTime<Utc>,
+        accommodation: Option<&Accommodation>,
+    ) -> DateTime<Utc> {
+        let (duration, window_end) = match accommodation {
+            Some(a) => (a.apply_to_duration(duration_minutes), a.apply_to_end_time(end_time)),
+            None => (Duration::minutes(duration_minutes as i64), end_time),
+        };
+
+        (started_at + duration).min(window_end)
+    }
+
+    async fn deadline_for_attempt(&self, attempt: &ExamAttempt) -> AppResult<Option<DateTime<Utc>>> {
+        let Some(started_at) = attempt.started_at else {
+            return Ok(None);
+        };
+
+        let exam_row = sqlx::query("SELECT end_time, duration_minutes FROM exams WHERE id = $1")
+            .bind(attempt.exam_id)
+            .fetch_one(&self.db.pool)
+            .await?;
+
+        let accommodation = AccommodationService::new(self.db.clone())
+            .get_effective_accommodation(attempt.user_id, attempt.exam_id)
+            .await?;
+
+        Ok(Some(Self::attempt_deadline(
+            started_at,
+            exam_row.get("duration_minutes"),
+            exam_row.get("end_time"),
+            accommodation.as_ref(),
+        )))
+    }
+
     // Helper methods
     fn row_to_exam_attempt(&self, row: PgRow) -> AppResult<ExamAttempt> {
         Ok(ExamAttempt {
@@ -359,6 +1313,22 @@ impl ExamAttemptService {
             started_at: row.get("started_at"),
             submitted_at: row.get("submitted_at"),
             score_total: row.get("score_total"),
+            max_score: row.get("max_score"),
+            shuffle_seed: row.get("shuffle_seed"),
+            client_ip: row.get("client_ip"),
+            submitted_ip: row.get("submitted_ip"),
+            passed: row.get("passed"),
+            grade: row.get("grade"),
+            auto_submitted: row.get("auto_submitted"),
+        })
+    }
+
+    fn row_to_attempt_section(&self, row: PgRow) -> AppResult<AttemptSection> {
+        Ok(AttemptSection {
+            attempt_id: row.get("attempt_id"),
+            section_id: row.get("section_id"),
+            started_at: row.get("started_at"),
+            finished_at: row.get("finished_at"),
         })
     }
 
@@ -368,11 +1338,37 @@ impl ExamAttemptService {
             attempt_id: row.get("attempt_id"),
             question_id: row.get("question_id"),
             answer_text: row.get("answer_text"),
+            choice_ids: row.get("choice_ids"),
+            incorrect_reason: row.get("incorrect_reason"),
+            response: row
+                .get::<Option<serde_json::Value>, _>("response")
+                .and_then(|response| serde_json::from_value(response).ok()),
+            penalty: row.get("penalty"),
+            gap_results: row
+                .get::<Option<serde_json::Value>, _>("gap_results")
+                .and_then(|results| serde_json::from_value(results).ok()),
             is_correct: row.get("is_correct"),
             score_awarded: row.get("score_awarded"),
         })
     }
 
+    fn row_to_draft(&self, row: PgRow) -> AppResult<AnswerDraft> {
+        let response = row
+            .get::<Option<serde_json::Value>, _>("response")
+            .map(serde_json::from_value)
+            .transpose()
+            .map_err(|e| AppError::BadRequest(format!("Failed to parse saved response: {}", e)))?;
+
+        Ok(AnswerDraft {
+            question_id: row.get("question_id"),
+            answer_text: row.get("answer_text"),
+            choice_ids: row.get("choice_ids"),
+            response,
+            version: row.get("version"),
+            saved_at: row.get("saved_at"),
+        })
+    }
+
     /// Create progress entry when exam attempt starts
     async fn create_exam_progress(&self, user_id: Uuid, exam_title: String) -> AppResult<()> {
         let progress_service = ProgressService::new(self.db.clone());
@@ -390,7 +1386,14 @@ impl ExamAttemptService {
     }
 
     /// Update progress when exam attempt is completed
-    async fn update_exam_progress(&self, user_id: Uuid, exam_title: String, total_score: i32, max_score: i32) -> AppResult<()> {
+    async fn update_exam_progress(
+        &self,
+        user_id: Uuid,
+        exam_title: String,
+        total_score: i32,
+        max_score: i32,
+        passed: Option<bool>,
+    ) -> AppResult<()> {
         let progress_service = ProgressService::new(self.db.clone());
         
         // Find the progress entry for this exam
@@ -408,7 +1411,11 @@ impl ExamAttemptService {
 
             let update_request = UpdateProgressRequest {
                 progress_percentage,
-                status: ProgressStatus::Completed,
+                status: if passed == Some(false) {
+                    ProgressStatus::Failed
+                } else {
+                    ProgressStatus::Completed
+                },
                 total_score: Some(total_score),
                 completed_at: Some(Utc::now()),
                 experience_points: Some(experience_points),
@@ -0,0 +1,187 @@
+use crate::database::Database;
+use crate::errors::{AppError, AppResult};
+use crate::models::grade_scale::{GradeBand, GradeScale, GradeScaleRequest, GradeScaleResponse};
+use sqlx::{postgres::PgRow, Postgres, Row, Transaction};
+use std::collections::HashSet;
+use uuid::Uuid;
+
+const GRADE_SCALE_COLUMNS: &str = "id, name, created_by, created_at";
+
+pub struct GradeScaleService {
+    db: Database,
+}
+
+impl GradeScaleService {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    /// Create a grade scale with its bands
+    pub async fn create_grade_scale(
+        &self,
+        request: GradeScaleRequest,
+        created_by: Uuid,
+    ) -> AppResult<GradeScaleResponse> {
+        Self::check_bands(&request)?;
+
+        let mut tx = self.db.pool.begin().await?;
+
+        let row = sqlx::query(&format!(
+            "INSERT INTO grade_scales (name, created_by) VALUES ($1, $2) RETURNING {GRADE_SCALE_COLUMNS}"
+        ))
+        .bind(&request.name)
+        .bind(created_by)
+        .fetch_one(&mut *tx)
+        .await?;
+        let scale = self.row_to_grade_scale(row)?;
+
+        Self::insert_bands(&mut tx, scale.id, &request).await?;
+        tx.commit().await?;
+
+        let bands = self.get_bands(scale.id).await?;
+        Ok(GradeScaleResponse::new(scale, bands))
+    }
+
+    pub async fn get_grade_scales(&self) -> AppResult<Vec<GradeScaleResponse>> {
+        let rows = sqlx::query(&format!("SELECT {GRADE_SCALE_COLUMNS} FROM grade_scales ORDER BY name"))
+            .fetch_all(&self.db.pool)
+            .await?;
+
+        let mut scales = Vec::with_capacity(rows.len());
+        for row in rows {
+            let scale = self.row_to_grade_scale(row)?;
+            let bands = self.get_bands(scale.id).await?;
+            scales.push(GradeScaleResponse::new(scale, bands));
+        }
+        Ok(scales)
+    }
+
+    pub async fn get_grade_scale_by_id(&self, scale_id: Uuid) -> AppResult<GradeScaleResponse> {
+        let row = sqlx::query(&format!("SELECT {GRADE_SCALE_COLUMNS} FROM grade_scales WHERE id = $1"))
+            .bind(scale_id)
+            .fetch_optional(&self.db.pool)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Grade scale not found".to_string()))?;
+        let scale = self.row_to_grade_scale(row)?;
+
+        let bands = self.get_bands(scale.id).await?;
+        Ok(GradeScaleResponse::new(scale, bands))
+    }
+
+    /// Rename a grade scale and replace its bands. Grades already awarded to
+    /// submitted attempts are not recalculated.
+    pub async fn update_grade_scale(
+        &self,
+        scale_id: Uuid,
+        request: GradeScaleRequest,
+    ) -> AppResult<GradeScaleResponse> {
+        Self::check_bands(&request)?;
+
+        let mut tx = self.db.pool.begin().await?;
+
+        let row = sqlx::query(&format!(
+            "UPDATE grade_scales SET name = $1 WHERE id = $2 RETURNING {GRADE_SCALE_COLUMNS}"
+        ))
+        .bind(&request.name)
+        .bind(scale_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Grade scale not found".to_string()))?;
+        let scale = self.row_to_grade_scale(row)?;
+
+        sqlx::query("DELETE FROM grade_scale_bands WHERE scale_id = $1")
+            .bind(scale_id)
+            .execute(&mut *tx)
+            .await?;
+        Self::insert_bands(&mut tx, scale_id, &request).await?;
+        tx.commit().await?;
+
+        let bands = self.get_bands(scale.id).await?;
+        Ok(GradeScaleResponse::new(scale, bands))
+    }
+
+    /// Delete a grade scale. Exams using it keep their pass mark but no
+    /// longer award grades.
+    pub async fn delete_grade_scale(&self, scale_id: Uuid) -> AppResult<()> {
+        let result = sqlx::query("DELETE FROM grade_scales WHERE id = $1")
+            .bind(scale_id)
+            .execute(&self.db.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Grade scale not found".to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Bands of a grade scale, highest first
+    pub async fn get_bands(&self, scale_id: Uuid) -> AppResult<Vec<GradeBand>> {
+        let rows = sqlx::query(
+            "SELECT label, min_percent FROM grade_scale_bands WHERE scale_id = $1 ORDER BY min_percent DESC",
+        )
+        .bind(scale_id)
+        .fetch_all(&self.db.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| GradeBand {
+                label: row.get("label"),
+                min_percent: row.get("min_percent"),
+            })
+            .collect())
+    }
+
+    /// Fail with NotFound when the grade scale does not exist
+    pub async fn ensure_exists(&self, scale_id: Uuid) -> AppResult<()> {
+        sqlx::query("SELECT id FROM grade_scales WHERE id = $1")
+            .bind(scale_id)
+            .fetch_optional(&self.db.pool)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Grade scale not found".to_string()))?;
+        Ok(())
+    }
+
+    fn check_bands(request: &GradeScaleRequest) -> AppResult<()> {
+        let mut labels = HashSet::new();
+        let mut thresholds = HashSet::new();
+        for band in &request.bands {
+            if !labels.insert(band.label.trim().to_lowercase()) {
+                return Err(AppError::BadRequest(format!("Duplicate grade label: {}", band.label)));
+            }
+            if !thresholds.insert(band.min_percent.to_bits()) {
+                return Err(AppError::BadRequest(format!(
+                    "Two grades start at {}%",
+                    band.min_percent
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    async fn insert_bands(
+        tx: &mut Transaction<'_, Postgres>,
+        scale_id: Uuid,
+        request: &GradeScaleRequest,
+    ) -> AppResult<()> {
+        for band in &request.bands {
+            sqlx::query("INSERT INTO grade_scale_bands (scale_id, label, min_percent) VALUES ($1, $2, $3)")
+                .bind(scale_id)
+                .bind(band.label.trim())
+                .bind(band.min_percent)
+                .execute(&mut **tx)
+                .await?;
+        }
+        Ok(())
+    }
+
+    fn row_to_grade_scale(&self, row: PgRow) -> AppResult<GradeScale> {
+        Ok(GradeScale {
+            id: row.get("id"),
+            name: row.get("name"),
+            created_by: row.get("created_by"),
+            created_at: row.get("created_at"),
+        })
+    }
+}
@@ -3,4 +3,13 @@ pub mod class;
 pub mod exam;
 pub mod exam_attempt;
 pub mod question;
-pub mod progress;
\ No newline at end of file
+pub mod progress;
+pub mod accommodation;
+pub mod trash;
+pub mod section;
+pub mod prerequisite;
+pub mod access;
+pub mod grade_scale;
+pub mod draw_rule;
+pub mod question_bank;
+pub mod attachment;
\ No newline at end of file
@@ -0,0 +1,222 @@
+use crate::database::Database;
+use crate::errors::{AppError, AppResult};
+use crate::models::prerequisite::{
+    CreatePrerequisiteRequest, ExamPrerequisite, PrerequisiteResponse,
+};
+use crate::models::progress::Achievement;
+use sqlx::{postgres::PgRow, Row};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+const PREREQUISITE_COLUMNS: &str =
+    "id, exam_id, required_exam_id, min_score_percent, required_achievement, created_at";
+
+pub struct PrerequisiteService {
+    db: Database,
+}
+
+impl PrerequisiteService {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    /// Add a prerequisite to an exam
+    pub async fn create_prerequisite(
+        &self,
+        exam_id: Uuid,
+        request: CreatePrerequisiteRequest,
+    ) -> AppResult<PrerequisiteResponse> {
+        match (request.required_exam_id, &request.required_achievement) {
+            (Some(required_exam_id), None) => {
+                if required_exam_id == exam_id {
+                    return Err(AppError::BadRequest("An exam cannot require itself".to_string()));
+                }
+
+                let required_exam = sqlx::query("SELECT 1 FROM exams WHERE id = $1 AND deleted_at IS NULL")
+                    .bind(required_exam_id)
+                    .fetch_optional(&self.db.pool)
+                    .await?;
+                if required_exam.is_none() {
+                    return Err(AppError::NotFound("Required exam not found".to_string()));
+                }
+
+                // Reject rules that would make the two exams lock each other
+                let cycle = sqlx::query(
+                    r#"
+                    WITH RECURSIVE chain AS (
+                        SELECT required_exam_id FROM exam_prerequisites
+                        WHERE exam_id = $1 AND required_exam_id IS NOT NULL
+                        UNION
+                        SELECT p.required_exam_id FROM exam_prerequisites p
+                        JOIN chain c ON p.exam_id = c.required_exam_id
+                        WHERE p.required_exam_id IS NOT NULL
+                    )
+                    SELECT 1 FROM chain WHERE required_exam_id = $2
+                    "#,
+                )
+                .bind(required_exam_id)
+                .bind(exam_id)
+                .fetch_optional(&self.db.pool)
+                .await?;
+                if cycle.is_some() {
+                    return Err(AppError::BadRequest(
+                        "Prerequisite would create a cycle between exams".to_string(),
+                    ));
+                }
+            }
+            (None, Some(achievement)) => {
+                if request.min_score_percent.is_some() {
+                    return Err(AppError::BadRequest(
+                        "min_score_percent can only be used with required_exam_id".to_string(),
+                    ));
+                }
+                let known = Achievement::get_default_achievements()
+                    .iter()
+                    .any(|a| &a.name == achievement);
+                if !known {
+                    return Err(AppError::BadRequest(format!("Unknown achievement: {}", achievement)));
+                }
+            }
+            _ => {
+                return Err(AppError::BadRequest(
+                    "Exactly one of required_exam_id or required_achievement must be set".to_string(),
+                ));
+            }
+        }
+
+        let exam = sqlx::query("SELECT 1 FROM exams WHERE id = $1 AND deleted_at IS NULL")
+            .bind(exam_id)
+            .fetch_optional(&self.db.pool)
+            .await?;
+        if exam.is_none() {
+            return Err(AppError::NotFound("Exam not found".to_string()));
+        }
+
+        let row = sqlx::query(&format!(
+            r#"
+            INSERT INTO exam_prerequisites (exam_id, required_exam_id, min_score_percent, required_achievement)
+            VALUES ($1, $2, $3, $4)
+            RETURNING {PREREQUISITE_COLUMNS}
+            "#
+        ))
+        .bind(exam_id)
+        .bind(request.required_exam_id)
+        .bind(request.min_score_percent)
+        .bind(&request.required_achievement)
+        .fetch_one(&self.db.pool)
+        .await?;
+
+        Ok(self.row_to_prerequisite(row)?.into())
+    }
+
+    pub async fn get_prerequisites(&self, exam_id: Uuid) -> AppResult<Vec<ExamPrerequisite>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {PREREQUISITE_COLUMNS} FROM exam_prerequisites WHERE exam_id = $1 ORDER BY created_at"
+        ))
+        .bind(exam_id)
+        .fetch_all(&self.db.pool)
+        .await?;
+
+        rows.into_iter().map(|row| self.row_to_prerequisite(row)).collect()
+    }
+
+    pub async fn delete_prerequisite(&self, prerequisite_id: Uuid) -> AppResult<()> {
+        let result = sqlx::query("DELETE FROM exam_prerequisites WHERE id = $1")
+            .bind(prerequisite_id)
+            .execute(&self.db.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Prerequisite not found".to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Human readable reasons why the exam is still locked for the user.
+    /// An empty list means every prerequisite is met.
+    pub async fn unmet_prerequisites(&self, exam_id: Uuid, user_id: Uuid) -> AppResult<Vec<String>> {
+        Ok(self
+            .unmet_prerequisites_for_exams(&[exam_id], user_id)
+            .await?
+            .remove(&exam_id)
+            .unwrap_or_default())
+    }
+
+    /// Reasons why each of `exam_ids` is still locked for the user, see
+    /// `unmet_prerequisites`. Exams without unmet prerequisites are left out.
+    pub async fn unmet_prerequisites_for_exams(
+        &self,
+        exam_ids: &[Uuid],
+        user_id: Uuid,
+    ) -> AppResult<HashMap<Uuid, Vec<String>>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT p.id, p.exam_id, p.required_exam_id, p.min_score_percent, p.required_achievement, p.created_at,
+                   required.title AS required_title,
+                   best.score_total AS best_score,
+                   COALESCE(best.max_score, 0) AS max_score,
+                   EXISTS (
+                       SELECT 1 FROM user_levels
+                       WHERE user_id = $2 AND achievements ? p.required_achievement
+                   ) AS achievement_unlocked
+            FROM exam_prerequisites p
+            LEFT JOIN exams required ON required.id = p.required_exam_id
+            LEFT JOIN LATERAL (
+                SELECT score_total, max_score FROM exam_attempts
+                WHERE exam_id = p.required_exam_id AND user_id = $2 AND submitted_at IS NOT NULL
+                ORDER BY score_total::FLOAT / NULLIF(max_score, 0) DESC NULLS LAST
+                LIMIT 1
+            ) best ON true
+            WHERE p.exam_id = ANY($1)
+            ORDER BY p.created_at
+            "#,
+        )
+        .bind(exam_ids)
+        .bind(user_id)
+        .fetch_all(&self.db.pool)
+        .await?;
+
+        let mut reasons: HashMap<Uuid, Vec<String>> = HashMap::new();
+        for row in rows {
+            let required_title: Option<String> = row.get("required_title");
+            let best_score: Option<i32> = row.get("best_score");
+            let max_score: i32 = row.get("max_score");
+            let achievement_unlocked: bool = row.get("achievement_unlocked");
+            let prerequisite = self.row_to_prerequisite(row)?;
+
+            let reason = if prerequisite.required_exam_id.is_some() {
+                let title = required_title.unwrap_or_else(|| "a removed exam".to_string());
+                (!prerequisite.is_score_met(best_score, max_score)).then(|| match prerequisite.min_score_percent {
+                    Some(percent) if percent > 0 => {
+                        format!("Requires a score of at least {}% on \"{}\"", percent, title)
+                    }
+                    _ => format!("Requires completing \"{}\"", title),
+                })
+            } else {
+                prerequisite
+                    .required_achievement
+                    .as_ref()
+                    .filter(|_| !achievement_unlocked)
+                    .map(|achievement| format!("Requires the \"{}\" achievement", achievement))
+            };
+
+            if let Some(reason) = reason {
+                reasons.entry(prerequisite.exam_id).or_default().push(reason);
+            }
+        }
+
+        Ok(reasons)
+    }
+
+    fn row_to_prerequisite(&self, row: PgRow) -> AppResult<ExamPrerequisite> {
+        Ok(ExamPrerequisite {
+            id: row.get("id"),
+            exam_id: row.get("exam_id"),
+            required_exam_id: row.get("required_exam_id"),
+            min_score_percent: row.get("min_score_percent"),
+            required_achievement: row.get("required_achievement"),
+            created_at: row.get("created_at"),
+        })
+    }
+}
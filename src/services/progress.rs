@@ -68,7 +68,7 @@ impl ProgressService {
         // Calculate completion status and experience
         let (completed_at, experience_bonus) = match request.status {
             ProgressStatus::Completed => {
-                let completion_time = request.completed_at.unwrap_or_else(|| Utc::now());
+                let completion_time = request.completed_at.unwrap_or_else(Utc::now);
                 let base_exp = request.experience_points.unwrap_or(0);
                 let bonus = self.calculate_completion_bonus(&request).await;
                 (Some(completion_time), base_exp + bonus)
@@ -1,13 +1,30 @@
 use crate::database::Database;
 use crate::errors::{AppError, AppResult};
 use crate::models::question::{
-    BulkCreateQuestionsRequest, Choice, ChoiceForStudentResponse, ChoiceResponse,
-    CreateQuestionRequest, Question, QuestionForStudentResponse, QuestionResponse, QuestionType, UpdateQuestionRequest,
+    check_accepted_answers, check_choices, check_cloze, check_numeric_answer, check_structured_key, BulkCreateQuestionsRequest, Choice, ChoiceForStudentResponse, ChoiceResponse,
+    CreateQuestionRequest, InconsistentQuestion, Question, QuestionForStudentResponse, QuestionResponse, UpdateQuestionRequest,
 };
+use crate::models::revision::{diff_revisions, ApplyTo, QuestionRevision, QuestionRevisionSummary, RevisionDiff};
+use crate::models::section::{SectionedQuestionsResponse, StudentSectionResponse};
+use crate::services::exam_attempt::ExamAttemptService;
+use crate::services::section::SectionService;
+use crate::utils::shuffle::{derive_seed, seeded_shuffle, seeded_shuffle_unpinned};
 use chrono::Utc;
-use sqlx::{postgres::PgRow, Row};
+use sqlx::{postgres::PgRow, PgConnection, Row};
 use uuid::Uuid;
 
+pub(crate) const QUESTION_COLUMNS: &str = "id, exam_id, section_id, question_text, question_type::text as question_type, \
+    content_format, correct_answer, score, scoring_policy, accepted_answers, numeric_answer, matching_pairs, ordering_items, cloze_gaps, \
+    explanation, attachment_ids, revision, pool, difficulty, owner_id, tags, topic, \
+    exam_id IS NULL AS from_bank";
+
+/// Columns of the `exam_questions` view, which includes bank questions
+/// linked into an exam with their per-exam section and score
+const EXAM_QUESTION_COLUMNS: &str = "id, exam_id, section_id, question_text, question_type::text as question_type, \
+    content_format, correct_answer, score, scoring_policy, accepted_answers, numeric_answer, matching_pairs, ordering_items, cloze_gaps, \
+    explanation, attachment_ids, revision, pool, difficulty, owner_id, tags, topic, \
+    from_bank";
+
 pub struct QuestionService {
     db: Database,
 }
@@ -23,23 +40,82 @@ impl QuestionService {
         exam_id: Uuid,
         request: CreateQuestionRequest,
     ) -> AppResult<QuestionResponse> {
+        self.ensure_section_in_exam(request.section_id, exam_id).await?;
+        self.insert_question(Some(exam_id), None, request).await
+    }
+
+    /// Create a question in a teacher's question bank. It belongs to no exam
+    /// and is linked into exams by reference.
+    pub async fn create_bank_question(
+        &self,
+        owner_id: Uuid,
+        request: CreateQuestionRequest,
+    ) -> AppResult<QuestionResponse> {
+        if request.section_id.is_some() {
+            return Err(AppError::BadRequest(
+                "Bank questions are placed in a section when they are linked into an exam".to_string(),
+            ));
+        }
+        self.insert_question(None, Some(owner_id), request).await
+    }
+
+    async fn insert_question(
+        &self,
+        exam_id: Option<Uuid>,
+        owner_id: Option<Uuid>,
+        request: CreateQuestionRequest,
+    ) -> AppResult<QuestionResponse> {
+        check_create_request(&request).map_err(AppError::BadRequest)?;
+        self.ensure_attachments_exist(request_attachment_ids(&request)).await?;
+
         // Start transaction
         let mut tx = self.db.pool.begin().await?;
+        let question = self.insert_checked_question(&mut tx, exam_id, owner_id, request).await?;
+        tx.commit().await?;
+
+        Ok(question)
+    }
 
+    /// Insert a question that passed `check_create_request` and references
+    /// existing attachments, with its choices
+    async fn insert_checked_question(
+        &self,
+        conn: &mut PgConnection,
+        exam_id: Option<Uuid>,
+        owner_id: Option<Uuid>,
+        request: CreateQuestionRequest,
+    ) -> AppResult<QuestionResponse> {
         // Insert question
-        let question_row = sqlx::query(
+        let question_row = sqlx::query(&format!(
             r#"
-            INSERT INTO questions (exam_id, question_text, question_type, correct_answer, score)
-            VALUES ($1, $2, $3::question_type, $4, $5)
-            RETURNING id, exam_id, question_text, question_type::text as question_type, correct_answer, score
+            INSERT INTO questions (exam_id, question_text, question_type, correct_answer, score, section_id, pool, difficulty,
+                                   owner_id, tags, topic, scoring_policy, accepted_answers, numeric_answer,
+                                   matching_pairs, ordering_items, cloze_gaps, explanation, attachment_ids, content_format)
+            VALUES ($1, $2, $3::question_type, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20)
+            RETURNING {QUESTION_COLUMNS}
             "#
-        )
-        .bind(&exam_id)
-        .bind(&request.question_text)
-        .bind(&request.question_type.to_string())
-        .bind(&request.correct_answer)
-        .bind(&request.score)
-        .fetch_one(&mut *tx)
+        ))
+        .bind(exam_id)
+        .bind(request.question_text)
+        .bind(request.question_type.to_string())
+        .bind(request.correct_answer)
+        .bind(request.score)
+        .bind(request.section_id)
+        .bind(request.pool)
+        .bind(request.difficulty)
+        .bind(owner_id)
+        .bind(request.tags)
+        .bind(request.topic)
+        .bind(request.scoring_policy.as_str())
+        .bind(serde_json::to_value(&request.accepted_answers).unwrap())
+        .bind(request.numeric_answer.map(|spec| serde_json::to_value(spec).unwrap()))
+        .bind(serde_json::to_value(&request.matching_pairs).unwrap())
+        .bind(request.ordering_items)
+        .bind(serde_json::to_value(&request.cloze_gaps).unwrap())
+        .bind(request.explanation)
+        .bind(request.attachment_ids)
+        .bind(request.content_format.as_str())
+        .fetch_one(&mut *conn)
         .await?;
 
         let question = self.row_to_question(question_row)?;
@@ -50,16 +126,19 @@ impl QuestionService {
             for choice_request in choice_requests {
                 let choice_row = sqlx::query(
                     r#"
-                    INSERT INTO choices (question_id, choice_text, is_correct, created_at)
-                    VALUES ($1, $2, $3, $4)
-                    RETURNING id, question_id, choice_text, is_correct, created_at
+                    INSERT INTO choices (question_id, choice_text, is_correct, pin_to_bottom, feedback, attachment_ids, created_at)
+                    VALUES ($1, $2, $3, $4, $5, $7, $6)
+                    RETURNING id, question_id, choice_text, is_correct, pin_to_bottom, feedback, attachment_ids, created_at
                     "#
                 )
                 .bind(question.id)
                 .bind(&choice_request.choice_text)
                 .bind(choice_request.is_correct)
+                .bind(choice_request.pin_to_bottom)
+                .bind(&choice_request.feedback)
                 .bind(Utc::now())
-                .fetch_one(&mut *tx)
+                .bind(&choice_request.attachment_ids)
+                .fetch_one(&mut *conn)
                 .await?;
 
                 let choice = self.row_to_choice(choice_row)?;
@@ -67,29 +146,20 @@ impl QuestionService {
             }
         }
 
-        tx.commit().await?;
-
-        Ok(QuestionResponse {
-            id: question.id,
-            exam_id: question.exam_id,
-            question_text: question.question_text,
-            question_type: question.question_type.parse().unwrap_or(QuestionType::Essay),
-            correct_answer: question.correct_answer,
-            score: question.score,
-            choices,
-        })
+        Ok(QuestionResponse::new(question, choices))
     }
 
-    /// Get all questions for an exam (for teachers/admins - includes correct answers)
+    /// Get all questions for an exam, including linked bank questions (for
+    /// teachers/admins - includes correct answers)
     pub async fn get_questions_by_exam(&self, exam_id: Uuid) -> AppResult<Vec<QuestionResponse>> {
-        let question_rows = sqlx::query(
+        let question_rows = sqlx::query(&format!(
             r#"
-            SELECT id, exam_id, question_text, question_type::text as question_type, correct_answer, score
-            FROM questions
-            WHERE exam_id = $1
+            SELECT {EXAM_QUESTION_COLUMNS}
+            FROM exam_questions
+            WHERE exam_id = $1 AND deleted_at IS NULL
             ORDER BY id
             "#
-        )
+        ))
         .bind(exam_id)
         .fetch_all(&self.db.pool)
         .await?;
@@ -101,10 +171,10 @@ impl QuestionService {
             // Get choices for this question
             let choice_rows = sqlx::query(
                 r#"
-                SELECT id, question_id, choice_text, is_correct, created_at
+                SELECT id, question_id, choice_text, is_correct, pin_to_bottom, feedback, attachment_ids, created_at
                 FROM choices
                 WHERE question_id = $1
-                ORDER BY id
+                ORDER BY created_at, id
                 "#
             )
             .bind(question.id)
@@ -119,30 +189,49 @@ impl QuestionService {
                 })
                 .collect::<AppResult<Vec<_>>>()?;
 
-            questions.push(QuestionResponse {
-                id: question.id,
-                exam_id: question.exam_id,
-                question_text: question.question_text,
-                question_type: question.question_type.parse().unwrap_or(QuestionType::Essay),
-                correct_answer: question.correct_answer,
-                score: question.score,
-                choices,
-            });
+            questions.push(QuestionResponse::new(question, choices));
         }
 
         Ok(questions)
     }
 
-    /// Get questions for students (without correct answers)
-    pub async fn get_questions_for_student(&self, exam_id: Uuid) -> AppResult<Vec<QuestionForStudentResponse>> {
+    /// Get questions for students (without correct answers), grouped by
+    /// section. Only the questions drawn for the student's latest attempt are
+    /// included, so the exam has to be started first.
+    pub async fn get_questions_for_student(
+        &self,
+        exam_id: Uuid,
+        user_id: Uuid,
+    ) -> AppResult<SectionedQuestionsResponse> {
+        let attempt_row = sqlx::query(
+            r#"
+            SELECT a.id, a.shuffle_seed, e.shuffle_choices
+            FROM exam_attempts a
+            JOIN exams e ON e.id = a.exam_id
+            WHERE a.exam_id = $1 AND a.user_id = $2
+            ORDER BY a.started_at DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(exam_id)
+        .bind(user_id)
+        .fetch_optional(&self.db.pool)
+        .await?
+        .ok_or_else(|| AppError::BadRequest("Start the exam to see its questions".to_string()))?;
+        let attempt_id: Uuid = attempt_row.get("id");
+        let shuffle_seed: i64 = attempt_row.get("shuffle_seed");
+        let choice_seed = attempt_row.get::<bool, _>("shuffle_choices").then_some(shuffle_seed);
+
         let question_rows = sqlx::query(
             r#"
-            SELECT id, exam_id, question_text, question_type::text as question_type, score
-            FROM questions
-            WHERE exam_id = $1
-            ORDER BY id
+            SELECT q.id, q.exam_id, q.section_id, q.score, aq.revision
+            FROM exam_questions q
+            JOIN attempt_questions aq ON aq.question_id = q.id
+            WHERE aq.attempt_id = $1 AND q.exam_id = $2
+            ORDER BY aq.position
             "#
         )
+        .bind(attempt_id)
         .bind(exam_id)
         .fetch_all(&self.db.pool)
         .await?;
@@ -150,72 +239,104 @@ impl QuestionService {
         let mut questions = Vec::new();
         for question_row in question_rows {
             let question_id: Uuid = question_row.get("id");
-            let exam_id: Uuid = question_row.get("exam_id");
-            let question_text: String = question_row.get("question_text");
-            let question_type: String = question_row.get("question_type");
-            let score: i32 = question_row.get("score");
-
-            // Get choices for this question (without is_correct field)
-            let choice_rows = sqlx::query(
-                r#"
-                SELECT id, choice_text
-                FROM choices
-                WHERE question_id = $1
-                ORDER BY id
-                "#
-            )
-            .bind(question_id)
-            .fetch_all(&self.db.pool)
-            .await?;
-
-            let choices: Vec<ChoiceForStudentResponse> = choice_rows
-                .into_iter()
-                .map(|row| ChoiceForStudentResponse {
-                    id: row.get("id"),
-                    choice_text: row.get("choice_text"),
-                })
-                .collect();
+            // The student sees the revision the attempt was started with
+            let question = self.get_revision(question_id, question_row.get("revision")).await?.question;
+            let choices = student_choices(&question, choice_seed);
+
+            // The key order of matching and ordering items would give the
+            // answer away, so they are always shuffled
+            let (matching_left, mut matching_right): (Vec<_>, Vec<_>) =
+                question.matching_pairs.into_iter().map(|pair| (pair.left, pair.right)).unzip();
+            seeded_shuffle(&mut matching_right, derive_seed(shuffle_seed, question_id));
+            let mut ordering_items = question.ordering_items;
+            seeded_shuffle(&mut ordering_items, derive_seed(shuffle_seed, question_id));
 
             questions.push(QuestionForStudentResponse {
                 id: question_id,
-                exam_id,
-                question_text,
-                question_type: question_type.parse().unwrap_or(QuestionType::Essay),
-                score,
+                exam_id: question_row.get("exam_id"),
+                section_id: question_row.get("section_id"),
+                question_text: question.question_text,
+                question_type: question.question_type,
+                content_format: question.content_format,
+                question_html: question.question_html,
+                score: question_row.get("score"),
+                scoring_policy: question.scoring_policy,
                 choices,
+                matching_left,
+                matching_right,
+                ordering_items,
+                cloze_gaps: question.cloze_gaps.into_iter().map(Into::into).collect(),
+                attachment_ids: question.attachment_ids,
             });
         }
 
-        Ok(questions)
+        let mut sections = Vec::new();
+        for section in SectionService::new(self.db.clone()).get_sections(exam_id).await? {
+            let (in_section, rest): (Vec<_>, Vec<_>) = questions
+                .into_iter()
+                .partition(|q| q.section_id == Some(section.id));
+            questions = rest;
+            sections.push(StudentSectionResponse::new(section, in_section));
+        }
+
+        Ok(SectionedQuestionsResponse { sections, questions })
+    }
+
+    /// Choices of a question revision without the `is_correct` field. With a
+    /// seed they are shuffled in the order of that attempt; pinned choices keep their place.
+    pub async fn get_student_choices(
+        &self,
+        question_id: Uuid,
+        revision: i32,
+        shuffle_seed: Option<i64>,
+    ) -> AppResult<Vec<ChoiceForStudentResponse>> {
+        let question = self.get_revision(question_id, revision).await?.question;
+        Ok(student_choices(&question, shuffle_seed))
     }
 
     /// Get a single question by ID
     pub async fn get_question_by_id(&self, question_id: Uuid) -> AppResult<QuestionResponse> {
-        let question_row = sqlx::query(
+        let mut conn = self.db.pool.acquire().await?;
+        self.load_question(&mut conn, question_id, false)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Question not found".to_string()))
+    }
+
+    /// A question with all its choices as it is now
+    async fn load_question(
+        &self,
+        conn: &mut PgConnection,
+        question_id: Uuid,
+        include_deleted: bool,
+    ) -> AppResult<Option<QuestionResponse>> {
+        let question_row = sqlx::query(&format!(
             r#"
-            SELECT id, exam_id, question_text, question_type::text as question_type, correct_answer, score
+            SELECT {QUESTION_COLUMNS}
             FROM questions
-            WHERE id = $1
+            WHERE id = $1 AND ($2 OR deleted_at IS NULL)
             "#
-        )
+        ))
         .bind(question_id)
-        .fetch_optional(&self.db.pool)
-        .await?
-        .ok_or_else(|| AppError::NotFound("Question not found".to_string()))?;
+        .bind(include_deleted)
+        .fetch_optional(&mut *conn)
+        .await?;
+        let Some(question_row) = question_row else {
+            return Ok(None);
+        };
 
         let question = self.row_to_question(question_row)?;
 
         // Get choices for this question
         let choice_rows = sqlx::query(
             r#"
-            SELECT id, question_id, choice_text, is_correct, created_at
+            SELECT id, question_id, choice_text, is_correct, pin_to_bottom, feedback, attachment_ids, created_at
             FROM choices
             WHERE question_id = $1
-            ORDER BY id
+            ORDER BY created_at, id
             "#
         )
         .bind(question.id)
-        .fetch_all(&self.db.pool)
+        .fetch_all(&mut *conn)
         .await?;
 
         let choices: Vec<ChoiceResponse> = choice_rows
@@ -226,143 +347,287 @@ impl QuestionService {
             })
             .collect::<AppResult<Vec<_>>>()?;
 
-        Ok(QuestionResponse {
-            id: question.id,
-            exam_id: question.exam_id,
-            question_text: question.question_text,
-            question_type: question.question_type.parse().unwrap_or(QuestionType::Essay),
-            correct_answer: question.correct_answer,
-            score: question.score,
-            choices,
+        Ok(Some(QuestionResponse::new(question, choices)))
+    }
+
+    /// A question as it was at `revision`. Attempts are shown and graded
+    /// against the revision they were started with.
+    pub async fn get_revision(&self, question_id: Uuid, revision: i32) -> AppResult<QuestionRevision> {
+        let row = sqlx::query(
+            "SELECT snapshot, created_by, created_at FROM question_revisions WHERE question_id = $1 AND revision = $2",
+        )
+        .bind(question_id)
+        .bind(revision)
+        .fetch_optional(&self.db.pool)
+        .await?;
+        if let Some(row) = row {
+            let mut question: QuestionResponse = serde_json::from_value(row.get("snapshot"))
+                .map_err(|e| AppError::BadRequest(format!("Failed to parse question revision: {}", e)))?;
+            // Older snapshots have no HTML, and rendering may have changed since
+            question.render_html();
+            return Ok(QuestionRevision {
+                question_id,
+                revision,
+                question,
+                created_by: row.get("created_by"),
+                created_at: row.get("created_at"),
+            });
+        }
+
+        // A revision is only stored once the question is edited, so an
+        // unstored one can still be the current question
+        let mut conn = self.db.pool.acquire().await?;
+        match self.load_question(&mut conn, question_id, true).await? {
+            Some(question) if question.revision == revision => Ok(QuestionRevision {
+                question_id,
+                revision,
+                question,
+                created_by: None,
+                created_at: None,
+            }),
+            _ => Err(AppError::NotFound("Question revision not found".to_string())),
+        }
+    }
+
+    /// All revisions of a question, oldest first, with the number of
+    /// attempts pinned to each
+    pub async fn get_revisions(&self, question_id: Uuid) -> AppResult<Vec<QuestionRevisionSummary>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT s.revision, r.created_by, r.created_at,
+                   (SELECT COUNT(*) FROM attempt_questions aq
+                    WHERE aq.question_id = q.id AND aq.revision = s.revision) AS attempt_count
+            FROM questions q
+            CROSS JOIN generate_series(1, q.revision) AS s(revision)
+            LEFT JOIN question_revisions r ON r.question_id = q.id AND r.revision = s.revision
+            WHERE q.id = $1 AND q.deleted_at IS NULL
+            ORDER BY s.revision
+            "#,
+        )
+        .bind(question_id)
+        .fetch_all(&self.db.pool)
+        .await?;
+        if rows.is_empty() {
+            return Err(AppError::NotFound("Question not found".to_string()));
+        }
+
+        Ok(rows
+            .into_iter()
+            .map(|row| QuestionRevisionSummary {
+                revision: row.get("revision"),
+                created_by: row.get("created_by"),
+                created_at: row.get("created_at"),
+                attempt_count: row.get("attempt_count"),
+            })
+            .collect())
+    }
+
+    /// What changed between two revisions of a question
+    pub async fn diff_revisions(&self, question_id: Uuid, from: i32, to: i32) -> AppResult<RevisionDiff> {
+        let from_revision = self.get_revision(question_id, from).await?;
+        let to_revision = self.get_revision(question_id, to).await?;
+        Ok(RevisionDiff {
+            question_id,
+            from,
+            to,
+            changes: diff_revisions(&from_revision.question, &to_revision.question),
         })
     }
 
-    /// Update a question and its choices
+    /// Update a question and its choices. The edit becomes a new revision;
+    /// the one it replaces is kept for the attempts pinned to it. With
+    /// `apply_to: regrade` the attempts are regraded in the same transaction.
+    /// Returns the question and the number of attempts regraded.
     pub async fn update_question(
         &self,
         question_id: Uuid,
         request: UpdateQuestionRequest,
-    ) -> AppResult<QuestionResponse> {
+        edited_by: Uuid,
+    ) -> AppResult<(QuestionResponse, usize)> {
+        let apply_to = request.apply_to;
+        check_accepted_answers(&request.question_type, &request.accepted_answers).map_err(AppError::BadRequest)?;
+        check_numeric_answer(&request.question_type, request.numeric_answer.as_ref()).map_err(AppError::BadRequest)?;
+        check_structured_key(&request.question_type, &request.matching_pairs, &request.ordering_items)
+            .map_err(AppError::BadRequest)?;
+        check_cloze(&request.question_type, &request.question_text, request.score, &request.cloze_gaps)
+            .map_err(AppError::BadRequest)?;
+        let attachment_ids = request
+            .attachment_ids
+            .iter()
+            .chain(request.choices.iter().flatten().flat_map(|choice| &choice.attachment_ids));
+        self.ensure_attachments_exist(attachment_ids).await?;
+
+        if request.section_id.is_some() {
+            let exam_id: Option<Uuid> = sqlx::query("SELECT exam_id FROM questions WHERE id = $1 AND deleted_at IS NULL")
+                .bind(question_id)
+                .fetch_optional(&self.db.pool)
+                .await?
+                .ok_or_else(|| AppError::NotFound("Question not found".to_string()))?
+                .get("exam_id");
+            let Some(exam_id) = exam_id else {
+                return Err(AppError::BadRequest(
+                    "Bank questions are placed in a section when they are linked into an exam".to_string(),
+                ));
+            };
+            self.ensure_section_in_exam(request.section_id, exam_id).await?;
+        }
+
         // Start transaction
         let mut tx = self.db.pool.begin().await?;
 
+        // Record the revision being replaced, unless an earlier edit already did
+        sqlx::query("SELECT 1 FROM questions WHERE id = $1 FOR UPDATE")
+            .bind(question_id)
+            .execute(&mut *tx)
+            .await?;
+        let previous = self
+            .load_question(&mut tx, question_id, false)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Question not found".to_string()))?;
+        sqlx::query(
+            r#"
+            INSERT INTO question_revisions (question_id, revision, snapshot)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (question_id, revision) DO NOTHING
+            "#,
+        )
+        .bind(question_id)
+        .bind(previous.revision)
+        .bind(serde_json::to_value(&previous).unwrap())
+        .execute(&mut *tx)
+        .await?;
+
         // Update question
-        let question_row = sqlx::query(
+        sqlx::query(
             r#"
             UPDATE questions
-            SET question_text = $1, question_type = $2::question_type, correct_answer = $3, score = $4
-            WHERE id = $5
-            RETURNING id, exam_id, question_text, question_type::text as question_type, correct_answer, score
+            SET question_text = $1, question_type = $2::question_type, correct_answer = $3, score = $4, section_id = $6,
+                revision = revision + 1, pool = $7, difficulty = $8, tags = $9, topic = $10, scoring_policy = $11,
+                accepted_answers = $12, numeric_answer = $13, matching_pairs = $14, ordering_items = $15,
+                cloze_gaps = $16, explanation = $17, attachment_ids = $18, content_format = $19
+            WHERE id = $5 AND deleted_at IS NULL
             "#
         )
-        .bind(&request.question_text)
+        .bind(request.question_text)
         .bind(request.question_type.to_string())
-        .bind(&request.correct_answer)
+        .bind(request.correct_answer)
         .bind(request.score)
         .bind(question_id)
-        .fetch_optional(&mut *tx)
-        .await?
-        .ok_or_else(|| AppError::NotFound("Question not found".to_string()))?;
-
-        let question = self.row_to_question(question_row)?;
+        .bind(request.section_id)
+        .bind(request.pool)
+        .bind(request.difficulty)
+        .bind(request.tags)
+        .bind(request.topic)
+        .bind(request.scoring_policy.as_str())
+        .bind(serde_json::to_value(&request.accepted_answers).unwrap())
+        .bind(request.numeric_answer.map(|spec| serde_json::to_value(spec).unwrap()))
+        .bind(serde_json::to_value(&request.matching_pairs).unwrap())
+        .bind(request.ordering_items)
+        .bind(serde_json::to_value(&request.cloze_gaps).unwrap())
+        .bind(request.explanation)
+        .bind(request.attachment_ids)
+        .bind(request.content_format.as_str())
+        .execute(&mut *tx)
+        .await?;
 
         // Update choices if provided
-        let mut choices = Vec::new();
         if let Some(choice_requests) = request.choices {
             for choice_request in choice_requests {
-                if let Some(delete) = choice_request.delete {
-                    if delete && choice_request.id.is_some() {
-                        // Delete choice
-                        sqlx::query("DELETE FROM choices WHERE id = $1 AND question_id = $2")
-                            .bind(choice_request.id.unwrap())
-                            .bind(question_id)
-                            .execute(&mut *tx)
-                            .await?;
-                        continue;
-                    }
+                if let (Some(true), Some(choice_id)) = (choice_request.delete, choice_request.id) {
+                    // Delete choice
+                    sqlx::query("DELETE FROM choices WHERE id = $1 AND question_id = $2")
+                        .bind(choice_id)
+                        .bind(question_id)
+                        .execute(&mut *tx)
+                        .await?;
+                    continue;
                 }
 
                 if let Some(choice_id) = choice_request.id {
                     // Update existing choice
-                    let choice_row = sqlx::query(
+                    sqlx::query(
                         r#"
                         UPDATE choices
-                        SET choice_text = $1, is_correct = $2
+                        SET choice_text = $1, is_correct = $2, pin_to_bottom = $5, feedback = $6, attachment_ids = $7
                         WHERE id = $3 AND question_id = $4
-                        RETURNING id, question_id, choice_text, is_correct, created_at
                         "#
                     )
                     .bind(&choice_request.choice_text)
                     .bind(choice_request.is_correct)
                     .bind(choice_id)
                     .bind(question_id)
-                    .fetch_optional(&mut *tx)
+                    .bind(choice_request.pin_to_bottom)
+                    .bind(&choice_request.feedback)
+                    .bind(&choice_request.attachment_ids)
+                    .execute(&mut *tx)
                     .await?;
-
-                    if let Some(row) = choice_row {
-                        let choice = self.row_to_choice(row)?;
-                        choices.push(choice.into());
-                    }
                 } else {
                     // Create new choice
-                    let choice_row = sqlx::query(
+                    sqlx::query(
                         r#"
-                        INSERT INTO choices (question_id, choice_text, is_correct, created_at)
-                        VALUES ($1, $2, $3, $4)
-                        RETURNING id, question_id, choice_text, is_correct, created_at
+                        INSERT INTO choices (question_id, choice_text, is_correct, pin_to_bottom, feedback, attachment_ids, created_at)
+                        VALUES ($1, $2, $3, $4, $5, $7, $6)
                         "#
                     )
                     .bind(question_id)
                     .bind(&choice_request.choice_text)
                     .bind(choice_request.is_correct)
+                    .bind(choice_request.pin_to_bottom)
+                    .bind(&choice_request.feedback)
                     .bind(Utc::now())
-                    .fetch_one(&mut *tx)
+                    .bind(&choice_request.attachment_ids)
+                    .execute(&mut *tx)
                     .await?;
-
-                    let choice = self.row_to_choice(choice_row)?;
-                    choices.push(choice.into());
                 }
             }
-        } else {
-            // If no choices provided, get existing ones
-            let choice_rows = sqlx::query(
-                r#"
-                SELECT id, question_id, choice_text, is_correct, created_at
-                FROM choices
-                WHERE question_id = $1
-                ORDER BY id
-                "#
-            )
-            .bind(question_id)
-            .fetch_all(&mut *tx)
-            .await?;
-
-            choices = choice_rows
-                .into_iter()
-                .map(|row| {
-                    let choice = self.row_to_choice(row)?;
-                    Ok(choice.into())
-                })
-                .collect::<AppResult<Vec<_>>>()?;
         }
 
+        let current = self
+            .load_question(&mut tx, question_id, false)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Question not found".to_string()))?;
+        let saved_choices: Vec<(&str, bool)> = current
+            .choices
+            .iter()
+            .map(|choice| (choice.choice_text.as_str(), choice.is_correct))
+            .collect();
+        check_choices(&request.question_type, current.correct_answer.as_deref(), &saved_choices)
+            .map_err(AppError::BadRequest)?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO question_revisions (question_id, revision, snapshot, created_by, created_at)
+            VALUES ($1, $2, $3, $4, NOW())
+            "#,
+        )
+        .bind(question_id)
+        .bind(current.revision)
+        .bind(serde_json::to_value(&current).unwrap())
+        .bind(edited_by)
+        .execute(&mut *tx)
+        .await?;
+
+        let regraded_attempts = match apply_to {
+            ApplyTo::FutureAttempts => 0,
+            ApplyTo::Regrade => {
+                ExamAttemptService::new(self.db.clone())
+                    .regrade_question(&mut tx, &current)
+                    .await?
+            }
+        };
+
         tx.commit().await?;
 
-        Ok(QuestionResponse {
-            id: question.id,
-            exam_id: question.exam_id,
-            question_text: question.question_text,
-            question_type: question.question_type.parse().unwrap_or(QuestionType::Essay),
-            correct_answer: question.correct_answer,
-            score: question.score,
-            choices,
-        })
+        Ok((current, regraded_attempts))
     }
 
-    /// Delete a question and its choices
-    pub async fn delete_question(&self, question_id: Uuid) -> AppResult<()> {
-        let result = sqlx::query("DELETE FROM questions WHERE id = $1")
+    /// Move a question to the trash. Its choices are kept so it can be restored.
+    pub async fn delete_question(&self, question_id: Uuid, deleted_by: Uuid) -> AppResult<()> {
+        let result = sqlx::query(
+            "UPDATE questions SET deleted_at = NOW(), deleted_by = $2 WHERE id = $1 AND deleted_at IS NULL",
+        )
             .bind(question_id)
+            .bind(deleted_by)
             .execute(&self.db.pool)
             .await?;
 
@@ -378,20 +643,33 @@ impl QuestionService {
         &self,
         request: BulkCreateQuestionsRequest,
     ) -> AppResult<Vec<QuestionResponse>> {
-        let mut created_questions = Vec::new();
+        // Reject the whole batch before anything is created
+        for (index, question_request) in request.questions.iter().enumerate() {
+            check_create_request(question_request)
+                .map_err(|e| AppError::BadRequest(format!("Question {}: {}", index + 1, e)))?;
+            self.ensure_section_in_exam(question_request.section_id, request.exam_id).await?;
+        }
+        self.ensure_attachments_exist(request.questions.iter().flat_map(request_attachment_ids)).await?;
 
+        // The batch is created as a whole or not at all
+        let mut tx = self.db.pool.begin().await?;
+        let mut created_questions = Vec::new();
         for question_request in request.questions {
-            let question = self.create_question(request.exam_id, question_request).await?;
+            let question = self
+                .insert_checked_question(&mut tx, Some(request.exam_id), None, question_request)
+                .await?;
             created_questions.push(question);
         }
+        tx.commit().await?;
 
         Ok(created_questions)
     }
 
-    /// Get total score for an exam
+    /// Get total score for an exam, counting linked bank questions at their
+    /// per-exam score
     pub async fn get_exam_total_score(&self, exam_id: Uuid) -> AppResult<i32> {
         let row = sqlx::query(
-            "SELECT COALESCE(SUM(score), 0) as total_score FROM questions WHERE exam_id = $1"
+            "SELECT COALESCE(SUM(score), 0)::INT as total_score FROM exam_questions WHERE exam_id = $1 AND deleted_at IS NULL"
         )
         .bind(exam_id)
         .fetch_one(&self.db.pool)
@@ -400,15 +678,136 @@ impl QuestionService {
         Ok(row.get("total_score"))
     }
 
+    /// Multiple choice and true/false questions whose choices and
+    /// `correct_answer` disagree, from the `inconsistent_choice_questions` view
+    pub async fn get_inconsistent_questions(&self) -> AppResult<Vec<InconsistentQuestion>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT question_id, exam_id, question_text, question_type::text AS question_type, correct_answer,
+                   correct_choices, issue
+            FROM inconsistent_choice_questions
+            ORDER BY exam_id, question_id
+            "#,
+        )
+        .fetch_all(&self.db.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| InconsistentQuestion {
+                question_id: row.get("question_id"),
+                exam_id: row.get("exam_id"),
+                question_text: row.get("question_text"),
+                question_type: row.get("question_type"),
+                correct_answer: row.get("correct_answer"),
+                correct_choices: row.get("correct_choices"),
+                issue: row.get("issue"),
+            })
+            .collect())
+    }
+
+    /// Reject references to attachments that were never uploaded
+    async fn ensure_attachments_exist(&self, attachment_ids: impl Iterator<Item = &Uuid>) -> AppResult<()> {
+        let mut attachment_ids: Vec<Uuid> = attachment_ids.copied().collect();
+        attachment_ids.sort();
+        attachment_ids.dedup();
+        if attachment_ids.is_empty() {
+            return Ok(());
+        }
+
+        let found: Vec<Uuid> = sqlx::query("SELECT id FROM attachments WHERE id = ANY($1)")
+            .bind(&attachment_ids)
+            .fetch_all(&self.db.pool)
+            .await?
+            .into_iter()
+            .map(|row| row.get("id"))
+            .collect();
+        match attachment_ids.iter().find(|id| !found.contains(id)) {
+            Some(missing) => Err(AppError::BadRequest(format!("Attachment {} not found", missing))),
+            None => Ok(()),
+        }
+    }
+
+    /// Reject a section that is not part of the exam
+    pub(crate) async fn ensure_section_in_exam(&self, section_id: Option<Uuid>, exam_id: Uuid) -> AppResult<()> {
+        let Some(section_id) = section_id else {
+            return Ok(());
+        };
+
+        let section = sqlx::query("SELECT 1 FROM exam_sections WHERE id = $1 AND exam_id = $2")
+            .bind(section_id)
+            .bind(exam_id)
+            .fetch_optional(&self.db.pool)
+            .await?;
+
+        if section.is_none() {
+            return Err(AppError::BadRequest("Section does not belong to this exam".to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Choices of a question including `is_correct`
+    pub(crate) async fn get_choices(&self, question_id: Uuid) -> AppResult<Vec<ChoiceResponse>> {
+        let choice_rows = sqlx::query(
+            r#"
+            SELECT id, question_id, choice_text, is_correct, pin_to_bottom, feedback, attachment_ids, created_at
+            FROM choices
+            WHERE question_id = $1
+            ORDER BY created_at, id
+            "#
+        )
+        .bind(question_id)
+        .fetch_all(&self.db.pool)
+        .await?;
+
+        choice_rows
+            .into_iter()
+            .map(|row| Ok(self.row_to_choice(row)?.into()))
+            .collect()
+    }
+
     // Helper methods
-    fn row_to_question(&self, row: PgRow) -> AppResult<Question> {
+    pub(crate) fn row_to_question(&self, row: PgRow) -> AppResult<Question> {
+        let accepted_answers_json: serde_json::Value = row.get("accepted_answers");
+        let accepted_answers = serde_json::from_value(accepted_answers_json)
+            .map_err(|e| AppError::BadRequest(format!("Failed to parse accepted answers: {}", e)))?;
+        let numeric_answer = row
+            .get::<Option<serde_json::Value>, _>("numeric_answer")
+            .map(serde_json::from_value)
+            .transpose()
+            .map_err(|e| AppError::BadRequest(format!("Failed to parse numeric answer: {}", e)))?;
+        let matching_pairs_json: serde_json::Value = row.get("matching_pairs");
+        let matching_pairs = serde_json::from_value(matching_pairs_json)
+            .map_err(|e| AppError::BadRequest(format!("Failed to parse matching pairs: {}", e)))?;
+        let cloze_gaps_json: serde_json::Value = row.get("cloze_gaps");
+        let cloze_gaps = serde_json::from_value(cloze_gaps_json)
+            .map_err(|e| AppError::BadRequest(format!("Failed to parse cloze gaps: {}", e)))?;
+
         Ok(Question {
             id: row.get("id"),
             exam_id: row.get("exam_id"),
+            section_id: row.get("section_id"),
             question_text: row.get("question_text"),
             question_type: row.get("question_type"),
+            content_format: row.get::<String, _>("content_format").parse().unwrap_or_default(),
             correct_answer: row.get("correct_answer"),
             score: row.get("score"),
+            scoring_policy: row.get::<String, _>("scoring_policy").parse().unwrap_or_default(),
+            accepted_answers,
+            numeric_answer,
+            matching_pairs,
+            ordering_items: row.get("ordering_items"),
+            cloze_gaps,
+            explanation: row.get("explanation"),
+            attachment_ids: row.get("attachment_ids"),
+            revision: row.get("revision"),
+            pool: row.get("pool"),
+            difficulty: row.get("difficulty"),
+            owner_id: row.get("owner_id"),
+            tags: row.get("tags"),
+            topic: row.get("topic"),
+            from_bank: row.get("from_bank"),
         })
     }
 
@@ -418,7 +817,56 @@ impl QuestionService {
             question_id: row.get("question_id"),
             choice_text: row.get("choice_text"),
             is_correct: row.get("is_correct"),
+            pin_to_bottom: row.get("pin_to_bottom"),
+            feedback: row.get("feedback"),
+            attachment_ids: row.get("attachment_ids"),
             created_at: row.get("created_at"),
         })
     }
 }
+
+/// Choices of a question as students see them, see `get_student_choices`
+fn student_choices(question: &QuestionResponse, shuffle_seed: Option<i64>) -> Vec<ChoiceForStudentResponse> {
+    let mut choices: Vec<(ChoiceForStudentResponse, bool)> = question
+        .choices
+        .iter()
+        .map(|choice| {
+            let student_choice = ChoiceForStudentResponse {
+                id: choice.id,
+                choice_text: choice.choice_text.clone(),
+                choice_html: choice.choice_html.clone(),
+                attachment_ids: choice.attachment_ids.clone(),
+            };
+            (student_choice, choice.pin_to_bottom)
+        })
+        .collect();
+
+    if let Some(seed) = shuffle_seed {
+        choices = seeded_shuffle_unpinned(choices, derive_seed(seed, question.id), |(_, pinned)| *pinned);
+    }
+
+    choices.into_iter().map(|(choice, _)| choice).collect()
+}
+
+/// Attachments a new question references, including those of its choices
+fn request_attachment_ids(request: &CreateQuestionRequest) -> impl Iterator<Item = &Uuid> {
+    request
+        .attachment_ids
+        .iter()
+        .chain(request.choices.iter().flatten().flat_map(|choice| &choice.attachment_ids))
+}
+
+/// Checks a new question has to pass before it is stored
+fn check_create_request(request: &CreateQuestionRequest) -> Result<(), String> {
+    let choices: Vec<(&str, bool)> = request
+        .choices
+        .iter()
+        .flatten()
+        .map(|choice| (choice.choice_text.as_str(), choice.is_correct))
+        .collect();
+    check_choices(&request.question_type, request.correct_answer.as_deref(), &choices)?;
+    check_accepted_answers(&request.question_type, &request.accepted_answers)?;
+    check_numeric_answer(&request.question_type, request.numeric_answer.as_ref())?;
+    check_structured_key(&request.question_type, &request.matching_pairs, &request.ordering_items)?;
+    check_cloze(&request.question_type, &request.question_text, request.score, &request.cloze_gaps)
+}
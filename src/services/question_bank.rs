@@ -0,0 +1,287 @@
+use crate::database::Database;
+use crate::errors::{AppError, AppResult};
+use crate::models::question::{CreateQuestionRequest, QuestionResponse};
+use crate::models::question_bank::{
+    BankLinkResponse, BankQuestionFilter, BankQuestionResponse, LinkBankQuestionRequest, QuestionUsage,
+    UpdateBankLinkRequest,
+};
+use crate::services::question::{QuestionService, QUESTION_COLUMNS};
+use crate::utils::pagination::Pagination;
+use sqlx::{postgres::PgRow, Postgres, QueryBuilder, Row};
+use uuid::Uuid;
+
+const LINK_COLUMNS: &str = "exam_id, question_id, section_id, score_override, created_at";
+
+/// Teacher-owned questions that belong to no exam and are linked into exams
+/// by reference. `owner_id` limits every operation to one teacher's bank;
+/// `None` (admins) gives access to all banks.
+pub struct QuestionBankService {
+    db: Database,
+}
+
+impl QuestionBankService {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    pub async fn create_question(
+        &self,
+        owner_id: Uuid,
+        request: CreateQuestionRequest,
+    ) -> AppResult<BankQuestionResponse> {
+        let question = QuestionService::new(self.db.clone())
+            .create_bank_question(owner_id, request)
+            .await?;
+
+        Ok(BankQuestionResponse {
+            question,
+            owner_id: Some(owner_id),
+            used_in_exams: 0,
+        })
+    }
+
+    /// Search bank questions and return one page of results with the total
+    /// match count
+    pub async fn search(
+        &self,
+        owner_id: Option<Uuid>,
+        filter: &BankQuestionFilter,
+        pagination: &Pagination,
+    ) -> AppResult<(Vec<BankQuestionResponse>, i64)> {
+        let mut count_query = QueryBuilder::<Postgres>::new("SELECT COUNT(*) AS total FROM questions");
+        Self::push_bank_filters(&mut count_query, owner_id, filter);
+        let total: i64 = count_query
+            .build()
+            .fetch_one(&self.db.pool)
+            .await?
+            .get("total");
+
+        let mut query = QueryBuilder::<Postgres>::new(format!(
+            r#"SELECT {QUESTION_COLUMNS},
+                (SELECT COUNT(*) FROM exam_question_links l
+                 JOIN exams e ON e.id = l.exam_id AND e.deleted_at IS NULL
+                 WHERE l.question_id = questions.id) AS used_in_exams
+            FROM questions"#
+        ));
+        Self::push_bank_filters(&mut query, owner_id, filter);
+        query.push(" ORDER BY question_text, id");
+        query.push(" LIMIT ").push_bind(pagination.limit_or_default(20));
+        query.push(" OFFSET ").push_bind(pagination.skip_or_default());
+
+        let question_service = QuestionService::new(self.db.clone());
+        let mut questions = Vec::new();
+        for row in query.build().fetch_all(&self.db.pool).await? {
+            let used_in_exams: i64 = row.get("used_in_exams");
+            let question = question_service.row_to_question(row)?;
+            let owner_id = question.owner_id;
+            let choices = question_service.get_choices(question.id).await?;
+            questions.push(BankQuestionResponse {
+                question: QuestionResponse::new(question, choices),
+                owner_id,
+                used_in_exams,
+            });
+        }
+
+        Ok((questions, total))
+    }
+
+    fn push_bank_filters(
+        query: &mut QueryBuilder<'_, Postgres>,
+        owner_id: Option<Uuid>,
+        filter: &BankQuestionFilter,
+    ) {
+        query.push(" WHERE exam_id IS NULL AND deleted_at IS NULL");
+
+        if let Some(owner_id) = owner_id {
+            query.push(" AND owner_id = ").push_bind(owner_id);
+        }
+        if let Some(tag) = &filter.tag {
+            query.push(" AND ").push_bind(tag.clone()).push(" = ANY(tags)");
+        }
+        if let Some(topic) = &filter.topic {
+            query.push(" AND topic = ").push_bind(topic.clone());
+        }
+        if let Some(difficulty) = &filter.difficulty {
+            query.push(" AND difficulty = ").push_bind(difficulty.clone());
+        }
+        if let Some(q) = filter.q.as_deref().map(str::trim).filter(|q| !q.is_empty()) {
+            query
+                .push(" AND question_text ILIKE ")
+                .push_bind(format!("%{}%", escape_like(q)));
+        }
+    }
+
+    /// Exams a bank question is linked into, with its score in each
+    pub async fn get_usage(&self, question_id: Uuid, owner_id: Option<Uuid>) -> AppResult<Vec<QuestionUsage>> {
+        self.ensure_bank_question(question_id, owner_id).await?;
+
+        let rows = sqlx::query(
+            r#"
+            SELECT l.exam_id, e.title AS exam_title, e.is_template, l.section_id, l.score_override,
+                   COALESCE(l.score_override, q.score) AS score, l.created_at AS linked_at,
+                   (SELECT COUNT(*) FROM answers ans
+                    JOIN exam_attempts a ON a.id = ans.attempt_id
+                    WHERE a.exam_id = l.exam_id AND ans.question_id = l.question_id) AS answers
+            FROM exam_question_links l
+            JOIN exams e ON e.id = l.exam_id AND e.deleted_at IS NULL
+            JOIN questions q ON q.id = l.question_id
+            WHERE l.question_id = $1
+            ORDER BY l.created_at
+            "#,
+        )
+        .bind(question_id)
+        .fetch_all(&self.db.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| QuestionUsage {
+                exam_id: row.get("exam_id"),
+                exam_title: row.get("exam_title"),
+                is_template: row.get("is_template"),
+                section_id: row.get("section_id"),
+                score_override: row.get("score_override"),
+                score: row.get("score"),
+                answers: row.get("answers"),
+                linked_at: row.get("linked_at"),
+            })
+            .collect())
+    }
+
+    /// Link a bank question into an exam
+    pub async fn link_question(
+        &self,
+        exam_id: Uuid,
+        request: LinkBankQuestionRequest,
+        owner_id: Option<Uuid>,
+    ) -> AppResult<BankLinkResponse> {
+        sqlx::query("SELECT 1 FROM exams WHERE id = $1 AND deleted_at IS NULL")
+            .bind(exam_id)
+            .fetch_optional(&self.db.pool)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Exam not found".to_string()))?;
+        self.ensure_bank_question(request.question_id, owner_id).await?;
+        QuestionService::new(self.db.clone())
+            .ensure_section_in_exam(request.section_id, exam_id)
+            .await?;
+
+        let row = sqlx::query(&format!(
+            r#"
+            INSERT INTO exam_question_links (exam_id, question_id, section_id, score_override)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (exam_id, question_id) DO NOTHING
+            RETURNING {LINK_COLUMNS}
+            "#
+        ))
+        .bind(exam_id)
+        .bind(request.question_id)
+        .bind(request.section_id)
+        .bind(request.score_override)
+        .fetch_optional(&self.db.pool)
+        .await?
+        .ok_or_else(|| AppError::Conflict("Question is already linked into this exam".to_string()))?;
+
+        self.row_to_link(row)
+    }
+
+    /// Change the section or score override of a linked question
+    pub async fn update_link(
+        &self,
+        exam_id: Uuid,
+        question_id: Uuid,
+        request: UpdateBankLinkRequest,
+    ) -> AppResult<BankLinkResponse> {
+        QuestionService::new(self.db.clone())
+            .ensure_section_in_exam(request.section_id, exam_id)
+            .await?;
+
+        let row = sqlx::query(&format!(
+            r#"
+            UPDATE exam_question_links
+            SET section_id = $3, score_override = $4
+            WHERE exam_id = $1 AND question_id = $2
+            RETURNING {LINK_COLUMNS}
+            "#
+        ))
+        .bind(exam_id)
+        .bind(question_id)
+        .bind(request.section_id)
+        .bind(request.score_override)
+        .fetch_optional(&self.db.pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Question is not linked into this exam".to_string()))?;
+
+        self.row_to_link(row)
+    }
+
+    /// Remove a bank question from an exam. Not allowed while an unsubmitted
+    /// attempt still has the question.
+    pub async fn unlink_question(&self, exam_id: Uuid, question_id: Uuid) -> AppResult<()> {
+        let in_progress = sqlx::query(
+            r#"
+            SELECT 1 FROM attempt_questions aq
+            JOIN exam_attempts a ON a.id = aq.attempt_id
+            WHERE a.exam_id = $1 AND aq.question_id = $2 AND a.submitted_at IS NULL
+            LIMIT 1
+            "#,
+        )
+        .bind(exam_id)
+        .bind(question_id)
+        .fetch_optional(&self.db.pool)
+        .await?;
+        if in_progress.is_some() {
+            return Err(AppError::Conflict(
+                "Question is part of an attempt in progress".to_string(),
+            ));
+        }
+
+        let result = sqlx::query("DELETE FROM exam_question_links WHERE exam_id = $1 AND question_id = $2")
+            .bind(exam_id)
+            .bind(question_id)
+            .execute(&self.db.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Question is not linked into this exam".to_string()));
+        }
+
+        Ok(())
+    }
+
+    async fn ensure_bank_question(&self, question_id: Uuid, owner_id: Option<Uuid>) -> AppResult<()> {
+        let row = sqlx::query("SELECT owner_id FROM questions WHERE id = $1 AND exam_id IS NULL AND deleted_at IS NULL")
+            .bind(question_id)
+            .fetch_optional(&self.db.pool)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Bank question not found".to_string()))?;
+
+        let question_owner: Option<Uuid> = row.get("owner_id");
+        if owner_id.is_some_and(|owner_id| question_owner != Some(owner_id)) {
+            return Err(AppError::Forbidden);
+        }
+
+        Ok(())
+    }
+
+    fn row_to_link(&self, row: PgRow) -> AppResult<BankLinkResponse> {
+        Ok(BankLinkResponse {
+            exam_id: row.get("exam_id"),
+            question_id: row.get("question_id"),
+            section_id: row.get("section_id"),
+            score_override: row.get("score_override"),
+            created_at: row.get("created_at"),
+        })
+    }
+}
+
+/// Escape `%`, `_` and `\` so user input matches literally in ILIKE
+pub fn escape_like(input: &str) -> String {
+    let mut escaped = String::with_capacity(input.len());
+    for c in input.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
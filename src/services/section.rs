@@ -0,0 +1,138 @@
+use crate::database::Database;
+use crate::errors::{AppError, AppResult};
+use crate::models::section::{
+    CreateSectionRequest, ExamSection, SectionResponse, UpdateSectionRequest,
+};
+use sqlx::{postgres::PgRow, Row};
+use uuid::Uuid;
+
+const SECTION_COLUMNS: &str =
+    "id, exam_id, title, instructions, position, time_limit_minutes, draw_count, created_at";
+
+pub struct SectionService {
+    db: Database,
+}
+
+impl SectionService {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    /// Add a section to an exam. Without a position it is appended at the end.
+    pub async fn create_section(
+        &self,
+        exam_id: Uuid,
+        request: CreateSectionRequest,
+    ) -> AppResult<SectionResponse> {
+        let exam = sqlx::query("SELECT 1 FROM exams WHERE id = $1 AND deleted_at IS NULL")
+            .bind(exam_id)
+            .fetch_optional(&self.db.pool)
+            .await?;
+
+        if exam.is_none() {
+            return Err(AppError::NotFound("Exam not found".to_string()));
+        }
+
+        let row = sqlx::query(&format!(
+            r#"
+            INSERT INTO exam_sections (exam_id, title, instructions, position, time_limit_minutes, draw_count)
+            VALUES (
+                $1, $2, $3,
+                COALESCE($4, (SELECT COALESCE(MAX(position), 0) + 1 FROM exam_sections WHERE exam_id = $1)),
+                $5, $6
+            )
+            RETURNING {SECTION_COLUMNS}
+            "#
+        ))
+        .bind(exam_id)
+        .bind(&request.title)
+        .bind(&request.instructions)
+        .bind(request.position)
+        .bind(request.time_limit_minutes)
+        .bind(request.draw_count)
+        .fetch_one(&self.db.pool)
+        .await?;
+
+        Ok(self.row_to_section(row)?.into())
+    }
+
+    /// Sections of an exam in display order
+    pub async fn get_sections(&self, exam_id: Uuid) -> AppResult<Vec<ExamSection>> {
+        let rows = sqlx::query(&format!(
+            r#"
+            SELECT {SECTION_COLUMNS}
+            FROM exam_sections
+            WHERE exam_id = $1
+            ORDER BY position, created_at
+            "#
+        ))
+        .bind(exam_id)
+        .fetch_all(&self.db.pool)
+        .await?;
+
+        rows.into_iter().map(|row| self.row_to_section(row)).collect()
+    }
+
+    pub async fn get_section_by_id(&self, section_id: Uuid) -> AppResult<ExamSection> {
+        let row = sqlx::query(&format!("SELECT {SECTION_COLUMNS} FROM exam_sections WHERE id = $1"))
+            .bind(section_id)
+            .fetch_optional(&self.db.pool)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Section not found".to_string()))?;
+
+        self.row_to_section(row)
+    }
+
+    pub async fn update_section(
+        &self,
+        section_id: Uuid,
+        request: UpdateSectionRequest,
+    ) -> AppResult<SectionResponse> {
+        let row = sqlx::query(&format!(
+            r#"
+            UPDATE exam_sections
+            SET title = $1, instructions = $2, position = $3, time_limit_minutes = $4, draw_count = $5
+            WHERE id = $6
+            RETURNING {SECTION_COLUMNS}
+            "#
+        ))
+        .bind(&request.title)
+        .bind(&request.instructions)
+        .bind(request.position)
+        .bind(request.time_limit_minutes)
+        .bind(request.draw_count)
+        .bind(section_id)
+        .fetch_optional(&self.db.pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Section not found".to_string()))?;
+
+        Ok(self.row_to_section(row)?.into())
+    }
+
+    /// Delete a section. Its questions stay in the exam without a section.
+    pub async fn delete_section(&self, section_id: Uuid) -> AppResult<()> {
+        let result = sqlx::query("DELETE FROM exam_sections WHERE id = $1")
+            .bind(section_id)
+            .execute(&self.db.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Section not found".to_string()));
+        }
+
+        Ok(())
+    }
+
+    fn row_to_section(&self, row: PgRow) -> AppResult<ExamSection> {
+        Ok(ExamSection {
+            id: row.get("id"),
+            exam_id: row.get("exam_id"),
+            title: row.get("title"),
+            instructions: row.get("instructions"),
+            position: row.get("position"),
+            time_limit_minutes: row.get("time_limit_minutes"),
+            draw_count: row.get("draw_count"),
+            created_at: row.get("created_at"),
+        })
+    }
+}
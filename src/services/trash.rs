@@ -0,0 +1,200 @@
+use crate::database::Database;
+use crate::errors::{AppError, AppResult};
+use crate::models::trash::{PurgeSummary, TrashItemType, TrashedItem};
+use chrono::{Duration, Utc};
+use sqlx::{postgres::PgRow, Row};
+use uuid::Uuid;
+
+pub struct TrashService {
+    db: Database,
+}
+
+impl TrashService {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    /// List soft-deleted exams, questions and classes, most recent first.
+    /// With `owner_id` only the items of that teacher are listed.
+    pub async fn list_trash(
+        &self,
+        item_type: Option<TrashItemType>,
+        owner_id: Option<Uuid>,
+    ) -> AppResult<Vec<TrashedItem>> {
+        let rows = sqlx::query(&format!(
+            r#"
+            SELECT * FROM (
+                SELECT 'exams' AS item_type, id, title, deleted_at, deleted_by, {} AS owner_id
+                FROM exams WHERE deleted_at IS NOT NULL
+                UNION ALL
+                SELECT 'questions' AS item_type, id, question_text AS title, deleted_at, deleted_by, {} AS owner_id
+                FROM questions WHERE deleted_at IS NOT NULL
+                UNION ALL
+                SELECT 'classes' AS item_type, id, name AS title, deleted_at, deleted_by, {} AS owner_id
+                FROM classes WHERE deleted_at IS NOT NULL
+            ) trash
+            WHERE ($1::TEXT IS NULL OR item_type = $1) AND ($2::UUID IS NULL OR owner_id = $2)
+            ORDER BY deleted_at DESC
+            "#,
+            owner_expr(TrashItemType::Exams),
+            owner_expr(TrashItemType::Questions),
+            owner_expr(TrashItemType::Classes),
+        ))
+        .bind(item_type.map(|t| t.table()))
+        .bind(owner_id)
+        .fetch_all(&self.db.pool)
+        .await?;
+
+        rows.into_iter().map(|row| self.row_to_trashed_item(row)).collect()
+    }
+
+    /// Restore a soft-deleted record. With `owner_id` only an item of that
+    /// teacher is restored.
+    pub async fn restore(&self, item_type: TrashItemType, id: Uuid, owner_id: Option<Uuid>) -> AppResult<()> {
+        let result = sqlx::query(&format!(
+            r#"
+            UPDATE {} SET deleted_at = NULL, deleted_by = NULL
+            WHERE id = $1 AND deleted_at IS NOT NULL AND ($2::UUID IS NULL OR {} = $2)
+            "#,
+            item_type.table(),
+            owner_expr(item_type)
+        ))
+        .bind(id)
+        .bind(owner_id)
+        .execute(&self.db.pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Item not found in trash".to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Permanently delete records that have been in the trash for longer than
+    /// `retention_days`, together with the rows that depend on them
+    pub async fn purge_expired(&self, retention_days: i64) -> AppResult<PurgeSummary> {
+        let cutoff = Utc::now() - Duration::days(retention_days);
+        let mut tx = self.db.pool.begin().await?;
+
+        // Deleting an exam drops the prerequisites that name it, which would
+        // unlock the exams requiring it. Exams still required by an exam that
+        // is not purged stay in the trash.
+        let kept_exams: Vec<Uuid> = sqlx::query(
+            r#"
+            WITH RECURSIVE required AS (
+                SELECT p.required_exam_id AS id
+                FROM exam_prerequisites p
+                JOIN exams e ON e.id = p.exam_id
+                WHERE p.required_exam_id IS NOT NULL AND (e.deleted_at IS NULL OR e.deleted_at >= $1)
+                UNION
+                SELECT p.required_exam_id
+                FROM exam_prerequisites p
+                JOIN required r ON r.id = p.exam_id
+                WHERE p.required_exam_id IS NOT NULL
+            )
+            SELECT id FROM exams WHERE deleted_at < $1 AND id IN (SELECT id FROM required)
+            "#,
+        )
+        .bind(cutoff)
+        .fetch_all(&mut *tx)
+        .await?
+        .into_iter()
+        .map(|row| row.get("id"))
+        .collect();
+
+        // Answers and pinned questions of these attempts go with them
+        // through ON DELETE CASCADE
+        sqlx::query(
+            "DELETE FROM exam_attempts WHERE exam_id IN (SELECT id FROM exams WHERE deleted_at < $1 AND id <> ALL($2))",
+        )
+        .bind(cutoff)
+        .bind(&kept_exams)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+            DELETE FROM exam_assignments
+            WHERE exam_id IN (SELECT id FROM exams WHERE deleted_at < $1 AND id <> ALL($2))
+               OR class_id IN (SELECT id FROM classes WHERE deleted_at < $1)
+            "#,
+        )
+        .bind(cutoff)
+        .bind(&kept_exams)
+        .execute(&mut *tx)
+        .await?;
+
+        // A question that attempts were given stays in the trash, so their
+        // answers and scores are kept
+        let questions = sqlx::query(
+            r#"
+            DELETE FROM questions q
+            WHERE q.deleted_at < $1
+              AND NOT EXISTS (SELECT 1 FROM answers a WHERE a.question_id = q.id)
+              AND NOT EXISTS (SELECT 1 FROM attempt_questions aq WHERE aq.question_id = q.id)
+            "#,
+        )
+        .bind(cutoff)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        // Questions of a purged exam go with it through ON DELETE CASCADE
+        let exams = sqlx::query("DELETE FROM exams WHERE deleted_at < $1 AND id <> ALL($2)")
+            .bind(cutoff)
+            .bind(&kept_exams)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+
+        sqlx::query(
+            "DELETE FROM class_members WHERE class_id IN (SELECT id FROM classes WHERE deleted_at < $1)",
+        )
+        .bind(cutoff)
+        .execute(&mut *tx)
+        .await?;
+
+        let classes = sqlx::query("DELETE FROM classes WHERE deleted_at < $1")
+            .bind(cutoff)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+
+        tx.commit().await?;
+
+        Ok(PurgeSummary {
+            exams,
+            questions,
+            classes,
+            kept_exams,
+        })
+    }
+
+    fn row_to_trashed_item(&self, row: PgRow) -> AppResult<TrashedItem> {
+        let item_type = match row.get::<String, _>("item_type").as_str() {
+            "exams" => TrashItemType::Exams,
+            "questions" => TrashItemType::Questions,
+            _ => TrashItemType::Classes,
+        };
+
+        Ok(TrashedItem {
+            item_type,
+            id: row.get("id"),
+            title: row.get("title"),
+            deleted_at: row.get("deleted_at"),
+            deleted_by: row.get("deleted_by"),
+        })
+    }
+}
+
+/// Teacher a trashed item belongs to: the creator of an exam or class, and
+/// the owner of a bank question or the creator of the exam of a question
+fn owner_expr(item_type: TrashItemType) -> &'static str {
+    match item_type {
+        TrashItemType::Exams | TrashItemType::Classes => "created_by",
+        TrashItemType::Questions => {
+            "COALESCE(owner_id, (SELECT e.created_by FROM exams e WHERE e.id = questions.exam_id))"
+        }
+    }
+}
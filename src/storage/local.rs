@@ -0,0 +1,89 @@
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::errors::{AppError, AppResult};
+use crate::storage::{hmac_sha256, BlobStore};
+
+/// Blobs as files in one directory. Downloads go through the API, which
+/// checks the signed link before serving the file.
+pub struct LocalBlobStore {
+    root: PathBuf,
+    signing_key: Vec<u8>,
+}
+
+impl LocalBlobStore {
+    pub fn new(root: impl AsRef<Path>, signing_key: &[u8]) -> anyhow::Result<Self> {
+        std::fs::create_dir_all(root.as_ref())?;
+        Ok(Self {
+            root: root.as_ref().to_path_buf(),
+            signing_key: signing_key.to_vec(),
+        })
+    }
+
+    fn path(&self, key: &str) -> AppResult<PathBuf> {
+        let valid = !key.is_empty()
+            && !key.starts_with('.')
+            && key.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+        if !valid {
+            return Err(AppError::BadRequest(format!("Invalid storage key: {}", key)));
+        }
+        Ok(self.root.join(key))
+    }
+
+    fn signature(&self, key: &str, expires: i64) -> Vec<u8> {
+        hmac_sha256(&self.signing_key, format!("{}:{}", key, expires).as_bytes())
+    }
+}
+
+#[async_trait]
+impl BlobStore for LocalBlobStore {
+    async fn put(&self, key: &str, _content_type: &str, bytes: Vec<u8>) -> AppResult<()> {
+        tokio::fs::write(self.path(key)?, bytes)
+            .await
+            .map_err(|e| AppError::Storage(format!("Failed to write {}: {}", key, e)))
+    }
+
+    async fn get(&self, key: &str) -> AppResult<Vec<u8>> {
+        match tokio::fs::read(self.path(key)?).await {
+            Ok(bytes) => Ok(bytes),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                Err(AppError::NotFound("Attachment content not found".to_string()))
+            }
+            Err(e) => Err(AppError::Storage(format!("Failed to read {}: {}", key, e))),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> AppResult<()> {
+        match tokio::fs::remove_file(self.path(key)?).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(AppError::Storage(format!("Failed to delete {}: {}", key, e))),
+        }
+    }
+
+    fn download_url(&self, key: &str, expires_at: DateTime<Utc>) -> String {
+        let expires = expires_at.timestamp();
+        format!(
+            "/api/v1/attachments/content/{}?expires={}&signature={}",
+            key,
+            expires,
+            hex::encode(self.signature(key, expires))
+        )
+    }
+
+    fn verify_download(&self, key: &str, expires: i64, signature: &str) -> bool {
+        if expires < Utc::now().timestamp() {
+            return false;
+        }
+        let Ok(signature) = hex::decode(signature) else {
+            return false;
+        };
+        let mut mac = Hmac::<Sha256>::new_from_slice(&self.signing_key).expect("HMAC accepts keys of any length");
+        mac.update(format!("{}:{}", key, expires).as_bytes());
+        mac.verify_slice(&signature).is_ok()
+    }
+}
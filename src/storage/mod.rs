@@ -0,0 +1,66 @@
+pub mod local;
+pub mod s3;
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::config::Config;
+use crate::errors::AppResult;
+
+pub use local::LocalBlobStore;
+pub use s3::S3BlobStore;
+
+/// Where attachment contents are kept. Blobs are addressed by a key the
+/// caller chooses and never changed once written.
+#[async_trait]
+pub trait BlobStore: Send + Sync {
+    async fn put(&self, key: &str, content_type: &str, bytes: Vec<u8>) -> AppResult<()>;
+
+    async fn get(&self, key: &str) -> AppResult<Vec<u8>>;
+
+    async fn delete(&self, key: &str) -> AppResult<()>;
+
+    /// A URL the blob can be downloaded from without credentials until `expires_at`
+    fn download_url(&self, key: &str, expires_at: DateTime<Utc>) -> String;
+
+    /// Whether a download link handed out by `download_url` is genuine and
+    /// still valid. Only stores that serve their own downloads accept any.
+    fn verify_download(&self, _key: &str, _expires: i64, _signature: &str) -> bool {
+        false
+    }
+}
+
+/// The blob store selected by `STORAGE_BACKEND`
+pub fn from_config(config: &Config) -> anyhow::Result<Arc<dyn BlobStore>> {
+    match config.storage_backend.as_str() {
+        "local" => Ok(Arc::new(LocalBlobStore::new(
+            &config.storage_local_path,
+            config.jwt_secret.as_bytes(),
+        )?)),
+        "s3" => {
+            let required = |value: &Option<String>, name: &str| {
+                value
+                    .clone()
+                    .ok_or_else(|| anyhow::anyhow!("{} is required when STORAGE_BACKEND is s3", name))
+            };
+            Ok(Arc::new(S3BlobStore::new(
+                &required(&config.s3_endpoint, "S3_ENDPOINT")?,
+                &config.s3_region,
+                &required(&config.s3_bucket, "S3_BUCKET")?,
+                &required(&config.s3_access_key, "S3_ACCESS_KEY")?,
+                &required(&config.s3_secret_key, "S3_SECRET_KEY")?,
+            )?))
+        }
+        other => Err(anyhow::anyhow!("Invalid STORAGE_BACKEND: {}", other)),
+    }
+}
+
+pub(crate) fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
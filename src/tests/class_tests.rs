@@ -172,4 +172,17 @@ mod class_handler_tests {
         assert_eq!(negative_pagination.limit_or_default(20), -1);
         assert_eq!(negative_pagination.skip_or_default(), -10);
     }
+
+    #[tokio::test]
+    async fn test_trash_item_type_path_values() {
+        use crate::models::trash::TrashItemType;
+
+        let item_type: TrashItemType = serde_json::from_str("\"classes\"").unwrap();
+        assert_eq!(item_type, TrashItemType::Classes);
+        assert_eq!(item_type.table(), "classes");
+        assert_eq!(TrashItemType::Exams.table(), "exams");
+        assert_eq!(TrashItemType::Questions.table(), "questions");
+
+        assert!(serde_json::from_str::<TrashItemType>("\"users\"").is_err());
+    }
 }
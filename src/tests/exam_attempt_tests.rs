@@ -36,6 +36,8 @@ mod exam_attempt_handler_tests {
         let valid_request = AnswerSubmission {
             question_id: Uuid::new_v4(),
             answer_text: "This is a valid answer".to_string(),
+            choice_ids: vec![],
+            response: None,
         };
         assert!(valid_request.validate().is_ok());
 
@@ -44,6 +46,8 @@ mod exam_attempt_handler_tests {
         let empty_answer_request = AnswerSubmission {
             question_id: Uuid::new_v4(),
             answer_text: "".to_string(), // Currently allowed
+            choice_ids: vec![],
+            response: None,
         };
         assert!(empty_answer_request.validate().is_ok()); // This passes because validation isn't implemented
     }
@@ -57,10 +61,14 @@ mod exam_attempt_handler_tests {
             AnswerSubmission {
                 question_id,
                 answer_text: "Answer 1".to_string(),
+                choice_ids: vec![],
+                response: None,
             },
             AnswerSubmission {
                 question_id: Uuid::new_v4(),
                 answer_text: "Answer 2".to_string(),
+                choice_ids: vec![],
+                response: None,
             },
         ];
 
@@ -139,6 +147,8 @@ mod exam_attempt_handler_tests {
         let long_request = AnswerSubmission {
             question_id: Uuid::new_v4(),
             answer_text: long_answer,
+            choice_ids: vec![],
+            response: None,
         };
         assert!(long_request.validate().is_ok());
 
@@ -146,6 +156,8 @@ mod exam_attempt_handler_tests {
         let special_request = AnswerSubmission {
             question_id: Uuid::new_v4(),
             answer_text: "Answer with special chars: @#$%^&*()".to_string(),
+            choice_ids: vec![],
+            response: None,
         };
         assert!(special_request.validate().is_ok());
 
@@ -153,6 +165,8 @@ mod exam_attempt_handler_tests {
         let whitespace_request = AnswerSubmission {
             question_id: Uuid::new_v4(),
             answer_text: "   ".to_string(),
+            choice_ids: vec![],
+            response: None,
         };
         // This should pass validation as whitespace might be valid answer
         assert!(whitespace_request.validate().is_ok());
@@ -197,14 +211,18 @@ mod exam_attempt_handler_tests {
         assert_eq!(empty_answers.len(), 0);
 
         // Test mixed valid answers
-        let mixed_answers = vec![
+        let mixed_answers = [
             AnswerSubmission {
                 question_id: Uuid::new_v4(),
                 answer_text: "Valid answer".to_string(),
+                choice_ids: vec![],
+                response: None,
             },
             AnswerSubmission {
                 question_id: Uuid::new_v4(),
                 answer_text: "".to_string(), // Currently allowed since no validation
+                choice_ids: vec![],
+                response: None,
             },
         ];
 
@@ -231,9 +249,13 @@ mod exam_attempt_handler_tests {
 
 #[cfg(test)]
 mod exam_attempt_service_tests {
+    use crate::models::accommodation::{Accommodation, CreateAccommodationRequest};
     use crate::models::attempt::{
-        StartExamAttemptRequest, SubmitExamAttemptRequest, AnswerSubmission, ExamAttempt
+        StartExamAttemptRequest, SubmitExamAttemptRequest, AnswerSubmission, ExamAttempt, AnswerDraft,
+        SaveAnswerRequest, merge_answers
     };
+    use crate::models::question::StructuredResponse;
+    use crate::services::exam_attempt::ExamAttemptService;
     use uuid::Uuid;
     use validator::Validate;
     use chrono::Utc;
@@ -245,18 +267,24 @@ mod exam_attempt_service_tests {
         let question_id = Uuid::new_v4();
 
         // Test valid start request
-        let valid_start = StartExamAttemptRequest { exam_id };
+        let valid_start = StartExamAttemptRequest {
+            exam_id,
+            access_code: None,
+        };
         assert!(valid_start.validate().is_ok());
 
         // Test valid submit request
         let answers = vec![AnswerSubmission {
             question_id,
             answer_text: "Valid answer".to_string(),
+            choice_ids: vec![],
+            response: None,
         }];
 
         let valid_submit = SubmitExamAttemptRequest {
             attempt_id,
             answers,
+            access_code: None,
         };
         assert!(valid_submit.validate().is_ok());
     }
@@ -276,6 +304,13 @@ mod exam_attempt_service_tests {
             started_at: None,
             submitted_at: None,
             score_total: None,
+            client_ip: None,
+            submitted_ip: None,
+            passed: None,
+            grade: None,
+            max_score: None,
+            shuffle_seed: 0,
+            auto_submitted: false,
         };
 
         let response = crate::models::attempt::ExamAttemptResponse::from(not_started.clone());
@@ -289,6 +324,13 @@ mod exam_attempt_service_tests {
             started_at: Some(now),
             submitted_at: None,
             score_total: None,
+            client_ip: None,
+            submitted_ip: None,
+            passed: None,
+            grade: None,
+            max_score: None,
+            shuffle_seed: 0,
+            auto_submitted: false,
         };
 
         let response = crate::models::attempt::ExamAttemptResponse::from(in_progress);
@@ -302,6 +344,13 @@ mod exam_attempt_service_tests {
             started_at: Some(now),
             submitted_at: Some(now + chrono::Duration::hours(1)),
             score_total: Some(85),
+            client_ip: None,
+            submitted_ip: None,
+            passed: None,
+            grade: None,
+            max_score: None,
+            shuffle_seed: 0,
+            auto_submitted: false,
         };
 
         let response = crate::models::attempt::ExamAttemptResponse::from(completed);
@@ -356,24 +405,92 @@ mod exam_attempt_service_tests {
         assert!(past_time < now);
     }
 
+    #[tokio::test]
+    async fn test_attempt_deadline_with_accommodation() {
+        let now = Utc::now();
+        let end_time = now + chrono::Duration::hours(3);
+
+        // Without an accommodation the deadline is simply started_at + duration
+        let deadline = ExamAttemptService::attempt_deadline(now, 60, end_time, None);
+        assert_eq!(deadline, now + chrono::Duration::minutes(60));
+
+        let accommodation = Accommodation {
+            id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            exam_id: None,
+            time_multiplier: 1.5,
+            extra_minutes: 10,
+            extended_end_time: None,
+            notes: None,
+            created_by: None,
+            created_at: now,
+        };
+
+        // 60 * 1.5 + 10 = 100 minutes
+        let deadline = ExamAttemptService::attempt_deadline(now, 60, end_time, Some(&accommodation));
+        assert_eq!(deadline, now + chrono::Duration::minutes(100));
+
+        // The exam window still caps the deadline
+        let closing_soon = now + chrono::Duration::minutes(75);
+        let deadline = ExamAttemptService::attempt_deadline(now, 60, closing_soon, Some(&accommodation));
+        assert_eq!(deadline, closing_soon);
+
+        // ...unless the window is extended for this student
+        let extended = Accommodation {
+            extended_end_time: Some(now + chrono::Duration::hours(2)),
+            ..accommodation
+        };
+        let deadline = ExamAttemptService::attempt_deadline(now, 60, closing_soon, Some(&extended));
+        assert_eq!(deadline, now + chrono::Duration::minutes(100));
+    }
+
+    #[tokio::test]
+    async fn test_accommodation_request_validation() {
+        let valid_request = CreateAccommodationRequest {
+            user_id: Uuid::new_v4(),
+            exam_id: Some(Uuid::new_v4()),
+            time_multiplier: Some(1.5),
+            extra_minutes: Some(15),
+            extended_end_time: None,
+            notes: Some("Extended time".to_string()),
+        };
+        assert!(valid_request.validate().is_ok());
+
+        let invalid_request = CreateAccommodationRequest {
+            user_id: Uuid::new_v4(),
+            exam_id: None,
+            time_multiplier: Some(0.5), // Less time than the exam duration is not an accommodation
+            extra_minutes: Some(-5),
+            extended_end_time: None,
+            notes: None,
+        };
+        assert!(invalid_request.validate().is_err());
+    }
+
     #[tokio::test]
     async fn test_answer_validation_business_logic() {
         // Test different answer types
         let multiple_choice_answer = AnswerSubmission {
             question_id: Uuid::new_v4(),
             answer_text: "A".to_string(),
+            choice_ids: vec![],
+            response: None,
         };
         assert!(multiple_choice_answer.validate().is_ok());
 
         let essay_answer = AnswerSubmission {
             question_id: Uuid::new_v4(),
             answer_text: "This is a detailed essay answer explaining the concept...".to_string(),
+            choice_ids: vec![],
+            response: None,
         };
         assert!(essay_answer.validate().is_ok());
 
         let true_false_answer = AnswerSubmission {
             question_id: Uuid::new_v4(),
             answer_text: "true".to_string(),
+            choice_ids: vec![],
+            response: None,
         };
         assert!(true_false_answer.validate().is_ok());
 
@@ -382,7 +499,138 @@ mod exam_attempt_service_tests {
         let long_answer_submission = AnswerSubmission {
             question_id: Uuid::new_v4(),
             answer_text: very_long_answer,
+            choice_ids: vec![],
+            response: None,
         };
         assert!(long_answer_submission.validate().is_ok());
     }
+
+    #[tokio::test]
+    async fn test_blank_answer_detection() {
+        let answer = |answer_text: &str, choice_ids: Vec<Uuid>, response: Option<StructuredResponse>| AnswerSubmission {
+            question_id: Uuid::new_v4(),
+            answer_text: answer_text.to_string(),
+            choice_ids,
+            response,
+        };
+
+        assert!(answer("", vec![], None).is_blank());
+        assert!(answer("   ", vec![], Some(StructuredResponse::Ordering(vec![]))).is_blank());
+        assert!(!answer("B", vec![], None).is_blank());
+        assert!(!answer("", vec![Uuid::new_v4()], None).is_blank());
+        assert!(!answer("", vec![], Some(StructuredResponse::Ordering(vec!["a".to_string()]))).is_blank());
+
+        let empty_gaps = [("gap".to_string(), " ".to_string())].into_iter().collect();
+        assert!(answer("", vec![], Some(StructuredResponse::Cloze(empty_gaps))).is_blank());
+    }
+
+    #[tokio::test]
+    async fn test_saved_answers_merged_with_submission() {
+        let (q1, q2, q3) = (Uuid::from_u128(1), Uuid::from_u128(2), Uuid::from_u128(3));
+        let answer = |question_id: Uuid, answer_text: &str| AnswerSubmission {
+            question_id,
+            answer_text: answer_text.to_string(),
+            choice_ids: vec![],
+            response: None,
+        };
+        let draft = AnswerDraft {
+            question_id: q2,
+            answer_text: "saved".to_string(),
+            choice_ids: vec![],
+            response: Some(StructuredResponse::Ordering(vec!["a".to_string(), "b".to_string()])),
+            version: 3,
+            saved_at: Utc::now(),
+        };
+        let saved = vec![answer(q1, "saved"), draft.into()];
+        let submitted = vec![answer(q3, "new"), answer(q1, "changed"), answer(q3, "changed again")];
+
+        let merged: Vec<(Uuid, String)> = merge_answers(saved, submitted)
+            .into_iter()
+            .map(|answer| (answer.question_id, answer.answer_text))
+            .collect();
+        assert_eq!(
+            merged,
+            vec![
+                (q1, "changed".to_string()),
+                (q2, "saved".to_string()),
+                (q3, "changed again".to_string()),
+            ]
+        );
+
+        // Nothing saved: the submission is graded as before
+        assert_eq!(merge_answers(vec![], vec![answer(q1, "A")]).len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_save_answer_request_version() {
+        let first: SaveAnswerRequest = serde_json::from_value(serde_json::json!({
+            "answer_text": "Paris"
+        }))
+        .unwrap();
+        assert_eq!(first.version, None);
+        assert!(first.choice_ids.is_empty());
+
+        let next: SaveAnswerRequest = serde_json::from_value(serde_json::json!({
+            "choice_ids": [Uuid::nil()],
+            "version": 2
+        }))
+        .unwrap();
+        assert_eq!(next.version, Some(2));
+        assert_eq!(next.answer_text, "");
+    }
+
+    #[tokio::test]
+    async fn test_accommodation_never_shortens_deadline() {
+        // The auto-submit job selects candidates by the deadline without
+        // accommodations, which must never be later than the real one
+        let now = Utc::now();
+        let end_time = now + chrono::Duration::minutes(45);
+        let base = ExamAttemptService::attempt_deadline(now, 60, end_time, None);
+
+        for (time_multiplier, extra_minutes, extended_end_time) in [
+            (1.0, 0, None),
+            (1.25, 0, None),
+            (1.0, 15, Some(now - chrono::Duration::hours(1))),
+            (2.0, 30, Some(now + chrono::Duration::hours(4))),
+        ] {
+            let accommodation = Accommodation {
+                id: Uuid::new_v4(),
+                user_id: Uuid::new_v4(),
+                exam_id: None,
+                time_multiplier,
+                extra_minutes,
+                extended_end_time,
+                notes: None,
+                created_by: None,
+                created_at: now,
+            };
+            let deadline = ExamAttemptService::attempt_deadline(now, 60, end_time, Some(&accommodation));
+            assert!(deadline >= base);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_auto_submitted_attempt_response() {
+        let now = Utc::now();
+        let attempt = ExamAttempt {
+            id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            exam_id: Uuid::new_v4(),
+            started_at: Some(now - chrono::Duration::hours(2)),
+            submitted_at: Some(now),
+            score_total: Some(40),
+            client_ip: Some("10.0.0.5".to_string()),
+            submitted_ip: None,
+            passed: Some(false),
+            grade: None,
+            max_score: Some(100),
+            shuffle_seed: 0,
+            auto_submitted: true,
+        };
+
+        let response = crate::models::attempt::ExamAttemptResponse::from(attempt);
+        assert_eq!(response.status, "completed");
+        assert!(response.auto_submitted);
+        assert_eq!(serde_json::to_value(&response).unwrap()["auto_submitted"], true);
+    }
 }
@@ -1,10 +1,16 @@
-use crate::models::exams::{CreateExamRequest, UpdateExamRequest, CreateExamAssignmentRequest, DeleteExamAssignmentRequest};
+use crate::models::access::UpdateIpAllowlistRequest;
+use crate::models::exams::{CloneExamRequest, CreateExamRequest, ExamAvailability, ExamFilter, ExamSort, ExamWindow, PassMarkType, UpdateExamRequest, NegativeMarking, ReviewPolicy, ScoreFloor, CreateExamAssignmentRequest, DeleteExamAssignmentRequest};
+use crate::models::grade_scale::{grade_for, GradeBand, GradeBandRequest, GradeScaleRequest};
+use crate::models::prerequisite::{CreatePrerequisiteRequest, ExamPrerequisite};
+use crate::models::section::{AttemptSection, CreateSectionRequest, ExamSection};
 use crate::models::user::UserRole;
+use crate::services::access::ExamAccessService;
 use crate::middleware::auth::AuthUser;
 use crate::utils::pagination::Pagination;
 use uuid::Uuid;
 use validator::Validate;
 use chrono::{Utc, Duration};
+use axum::extract::Query;
 
 #[cfg(test)]
 mod exam_handler_tests {
@@ -37,6 +43,15 @@ mod exam_handler_tests {
             end_time: now + Duration::hours(3),
             category: "Computer Science".to_string(),
             difficulty: "Intermediate".to_string(),
+            is_template: None,
+            pass_mark: None,
+            pass_mark_type: None,
+            grade_scale_id: None,
+            shuffle_questions: None,
+            shuffle_choices: None,
+            wrong_answer_penalty: None,
+            score_floor: None,
+            review_policy: None,
         };
         assert!(valid_request.validate().is_ok());
 
@@ -48,6 +63,15 @@ mod exam_handler_tests {
             end_time: now + Duration::hours(3),
             category: "Computer Science".to_string(),
             difficulty: "Intermediate".to_string(),
+            is_template: None,
+            pass_mark: None,
+            pass_mark_type: None,
+            grade_scale_id: None,
+            shuffle_questions: None,
+            shuffle_choices: None,
+            wrong_answer_penalty: None,
+            score_floor: None,
+            review_policy: None,
         };
         assert!(invalid_request.validate().is_err());
     }
@@ -64,6 +88,17 @@ mod exam_handler_tests {
             end_time: now + Duration::hours(3),
             category: "Computer Science".to_string(),
             difficulty: "Advanced".to_string(),
+            is_template: None,
+            pass_mark: None,
+            pass_mark_type: None,
+            grade_scale_id: None,
+            clear_pass_mark: false,
+            clear_grade_scale: false,
+            shuffle_questions: None,
+            shuffle_choices: None,
+            wrong_answer_penalty: None,
+            score_floor: None,
+            review_policy: None,
         };
         assert!(valid_request.validate().is_ok());
 
@@ -76,6 +111,17 @@ mod exam_handler_tests {
             end_time: now + Duration::hours(2),
             category: "Computer Science".to_string(),
             difficulty: "Advanced".to_string(),
+            is_template: None,
+            pass_mark: None,
+            pass_mark_type: None,
+            grade_scale_id: None,
+            clear_pass_mark: false,
+            clear_grade_scale: false,
+            shuffle_questions: None,
+            shuffle_choices: None,
+            wrong_answer_penalty: None,
+            score_floor: None,
+            review_policy: None,
         };
         assert!(invalid_request.validate().is_err());
     }
@@ -156,6 +202,15 @@ mod exam_handler_tests {
             end_time: now + Duration::hours(3),
             category: "Mathematics".to_string(),
             difficulty: "Easy".to_string(),
+            is_template: None,
+            pass_mark: None,
+            pass_mark_type: None,
+            grade_scale_id: None,
+            shuffle_questions: None,
+            shuffle_choices: None,
+            wrong_answer_penalty: None,
+            score_floor: None,
+            review_policy: None,
         };
         assert!(future_exam.validate().is_ok());
         assert!(future_exam.start_time < future_exam.end_time);
@@ -169,6 +224,15 @@ mod exam_handler_tests {
             end_time: now + Duration::hours(1),
             category: "Mathematics".to_string(),
             difficulty: "Easy".to_string(),
+            is_template: None,
+            pass_mark: None,
+            pass_mark_type: None,
+            grade_scale_id: None,
+            shuffle_questions: None,
+            shuffle_choices: None,
+            wrong_answer_penalty: None,
+            score_floor: None,
+            review_policy: None,
         };
         assert!(past_exam.validate().is_ok());
     }
@@ -186,6 +250,15 @@ mod exam_handler_tests {
             end_time: now + Duration::hours(2),
             category: "General".to_string(),
             difficulty: "Easy".to_string(),
+            is_template: None,
+            pass_mark: None,
+            pass_mark_type: None,
+            grade_scale_id: None,
+            shuffle_questions: None,
+            shuffle_choices: None,
+            wrong_answer_penalty: None,
+            score_floor: None,
+            review_policy: None,
         };
         assert!(short_exam.validate().is_ok());
         assert_eq!(short_exam.duration_minutes, 5);
@@ -199,6 +272,15 @@ mod exam_handler_tests {
             end_time: now + Duration::hours(5),
             category: "Computer Science".to_string(),
             difficulty: "Hard".to_string(),
+            is_template: None,
+            pass_mark: None,
+            pass_mark_type: None,
+            grade_scale_id: None,
+            shuffle_questions: None,
+            shuffle_choices: None,
+            wrong_answer_penalty: None,
+            score_floor: None,
+            review_policy: None,
         };
         assert!(long_exam.validate().is_ok());
         assert_eq!(long_exam.duration_minutes, 240);
@@ -217,6 +299,15 @@ mod exam_handler_tests {
             end_time: now + Duration::hours(2),
             category: "Literature".to_string(),
             difficulty: "Medium".to_string(),
+            is_template: None,
+            pass_mark: None,
+            pass_mark_type: None,
+            grade_scale_id: None,
+            shuffle_questions: None,
+            shuffle_choices: None,
+            wrong_answer_penalty: None,
+            score_floor: None,
+            review_policy: None,
         };
         assert!(with_long_content.validate().is_ok());
         assert!(!with_long_content.title.is_empty());
@@ -231,6 +322,15 @@ mod exam_handler_tests {
             end_time: now + Duration::hours(2),
             category: "General".to_string(),
             difficulty: "Easy".to_string(),
+            is_template: None,
+            pass_mark: None,
+            pass_mark_type: None,
+            grade_scale_id: None,
+            shuffle_questions: None,
+            shuffle_choices: None,
+            wrong_answer_penalty: None,
+            score_floor: None,
+            review_policy: None,
         };
         assert!(minimal_exam.validate().is_ok());
     }
@@ -270,8 +370,19 @@ mod exam_handler_tests {
             end_time: now + Duration::hours(2),
             category: "Science".to_string(),
             difficulty: "Medium".to_string(),
+            is_template: None,
+            pass_mark: None,
+            pass_mark_type: None,
+            grade_scale_id: None,
+            clear_pass_mark: false,
+            clear_grade_scale: false,
+            shuffle_questions: None,
+            shuffle_choices: None,
+            wrong_answer_penalty: None,
+            score_floor: None,
+            review_policy: None,
         };
-        assert_eq!(active_exam.is_active, true);
+        assert!(active_exam.is_active);
 
         let inactive_exam = UpdateExamRequest {
             title: "Inactive Exam".to_string(),
@@ -282,7 +393,297 @@ mod exam_handler_tests {
             end_time: now + Duration::hours(2),
             category: "Science".to_string(),
             difficulty: "Medium".to_string(),
+            is_template: None,
+            pass_mark: None,
+            pass_mark_type: None,
+            grade_scale_id: None,
+            clear_pass_mark: false,
+            clear_grade_scale: false,
+            shuffle_questions: None,
+            shuffle_choices: None,
+            wrong_answer_penalty: None,
+            score_floor: None,
+            review_policy: None,
         };
-        assert_eq!(inactive_exam.is_active, false);
+        assert!(!inactive_exam.is_active);
+    }
+
+    #[tokio::test]
+    async fn test_clone_exam_request_validation() {
+        let now = Utc::now();
+        let request: CloneExamRequest = serde_json::from_value(serde_json::json!({
+            "start_time": now + Duration::days(120),
+            "end_time": now + Duration::days(120) + Duration::hours(2),
+        }))
+        .unwrap();
+        assert!(request.validate().is_ok());
+        assert!(request.title.is_none());
+        assert!(!request.include_assignments);
+        assert!(!request.as_template);
+
+        let invalid_request = CloneExamRequest {
+            title: Some("".to_string()), // Empty title should fail
+            duration_minutes: Some(0),   // Zero duration should fail
+            start_time: now,
+            end_time: now + Duration::hours(1),
+            include_assignments: true,
+            as_template: false,
+        };
+        assert!(invalid_request.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_exam_filter_query_parsing() {
+        let uri: axum::http::Uri =
+            "/exams?category=Math&difficulty=Hard&status=upcoming&q=final%20exam&sort=title_asc&limit=5"
+                .parse()
+                .unwrap();
+        let Query(filter) = Query::<ExamFilter>::try_from_uri(&uri).unwrap();
+        let Query(pagination) = Query::<Pagination>::try_from_uri(&uri).unwrap();
+
+        assert_eq!(filter.category.as_deref(), Some("Math"));
+        assert_eq!(filter.difficulty.as_deref(), Some("Hard"));
+        assert_eq!(filter.status, Some(ExamWindow::Upcoming));
+        assert_eq!(filter.q.as_deref(), Some("final exam"));
+        assert_eq!(filter.sort, ExamSort::TitleAsc);
+        assert_eq!(filter.sort.order_by(), "title ASC");
+        assert_eq!(pagination.limit_or_default(20), 5);
+
+        // Defaults when nothing is given
+        let uri: axum::http::Uri = "/exams".parse().unwrap();
+        let Query(filter) = Query::<ExamFilter>::try_from_uri(&uri).unwrap();
+        assert!(filter.status.is_none());
+        assert_eq!(filter.sort, ExamSort::StartTimeDesc);
+
+        // Unknown window is rejected
+        let uri: axum::http::Uri = "/exams?status=soon".parse().unwrap();
+        assert!(Query::<ExamFilter>::try_from_uri(&uri).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_exam_availability() {
+        let now = Utc::now();
+        let start_time = now + Duration::hours(1);
+        let window_end = now + Duration::hours(3);
+
+        assert_eq!(ExamAvailability::at(now, start_time, window_end), ExamAvailability::Upcoming);
+        assert_eq!(
+            ExamAvailability::at(now + Duration::hours(2), start_time, window_end),
+            ExamAvailability::Open
+        );
+        assert_eq!(ExamAvailability::at(window_end, start_time, window_end), ExamAvailability::Open);
+        assert_eq!(
+            ExamAvailability::at(now + Duration::hours(4), start_time, window_end),
+            ExamAvailability::Closed
+        );
+    }
+
+    #[tokio::test]
+    async fn test_section_request_validation() {
+        let valid_request = CreateSectionRequest {
+            title: "Part A: Multiple choice".to_string(),
+            instructions: Some("Choose one answer per question".to_string()),
+            position: None,
+            time_limit_minutes: Some(20),
+            draw_count: Some(10),
+        };
+        assert!(valid_request.validate().is_ok());
+
+        let invalid_request = CreateSectionRequest {
+            title: "".to_string(),
+            instructions: None,
+            position: Some(1),
+            time_limit_minutes: Some(0),
+            draw_count: Some(0),
+        };
+        assert!(invalid_request.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_section_deadline() {
+        let now = Utc::now();
+        let mut section = ExamSection {
+            id: Uuid::new_v4(),
+            exam_id: Uuid::new_v4(),
+            title: "Part B: Essay".to_string(),
+            instructions: None,
+            position: 2,
+            time_limit_minutes: Some(40),
+            draw_count: None,
+            created_at: now,
+        };
+        assert_eq!(section.deadline(now), Some(now + Duration::minutes(40)));
+
+        section.time_limit_minutes = None;
+        assert_eq!(section.deadline(now), None);
+    }
+
+    #[tokio::test]
+    async fn test_section_accepts_answers() {
+        let now = Utc::now();
+        let mut section = ExamSection {
+            id: Uuid::new_v4(),
+            exam_id: Uuid::new_v4(),
+            title: "Part A: Listening".to_string(),
+            instructions: None,
+            position: 1,
+            time_limit_minutes: Some(20),
+            draw_count: None,
+            created_at: now,
+        };
+        let mut attempt_section = AttemptSection {
+            attempt_id: Uuid::new_v4(),
+            section_id: section.id,
+            started_at: now - Duration::minutes(10),
+            finished_at: None,
+        };
+        assert!(attempt_section.accepts_answers(&section, now));
+
+        // Past the time limit, even though it was never finished
+        assert!(!attempt_section.accepts_answers(&section, now + Duration::minutes(11)));
+
+        // Finished early
+        attempt_section.finished_at = Some(now);
+        assert!(!attempt_section.accepts_answers(&section, now));
+
+        // Without a time limit only finishing closes the section
+        section.time_limit_minutes = None;
+        attempt_section.finished_at = None;
+        assert!(attempt_section.accepts_answers(&section, now + Duration::days(1)));
+    }
+
+    #[tokio::test]
+    async fn test_prerequisite_score_check() {
+        let prerequisite = ExamPrerequisite {
+            id: Uuid::new_v4(),
+            exam_id: Uuid::new_v4(),
+            required_exam_id: Some(Uuid::new_v4()),
+            min_score_percent: Some(60),
+            required_achievement: None,
+            created_at: Utc::now(),
+        };
+
+        // No submitted attempt on the required exam
+        assert!(!prerequisite.is_score_met(None, 50));
+        // 30 / 50 = 60%
+        assert!(prerequisite.is_score_met(Some(30), 50));
+        assert!(!prerequisite.is_score_met(Some(29), 50));
+
+        // Without a minimum, completing the exam is enough
+        let completion_only = ExamPrerequisite {
+            min_score_percent: None,
+            ..prerequisite
+        };
+        assert!(completion_only.is_score_met(Some(0), 50));
+    }
+
+    #[tokio::test]
+    async fn test_prerequisite_request_validation() {
+        let valid_request = CreatePrerequisiteRequest {
+            required_exam_id: Some(Uuid::new_v4()),
+            min_score_percent: Some(60),
+            required_achievement: None,
+        };
+        assert!(valid_request.validate().is_ok());
+
+        let invalid_request = CreatePrerequisiteRequest {
+            required_exam_id: Some(Uuid::new_v4()),
+            min_score_percent: Some(120),
+            required_achievement: None,
+        };
+        assert!(invalid_request.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_access_code_generation() {
+        let code = ExamAccessService::generate_access_code();
+        assert_eq!(code.len(), 6);
+        assert!(code.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit()));
+        assert!(!code.contains('O') && !code.contains('0'));
+    }
+
+    #[tokio::test]
+    async fn test_ip_allowlist_request_validation() {
+        let valid_request = UpdateIpAllowlistRequest {
+            ip_allowlist: vec!["10.0.0.0/8".to_string(), "192.168.1.20".to_string()],
+        };
+        assert!(valid_request.validate().is_ok());
+
+        let invalid_request = UpdateIpAllowlistRequest {
+            ip_allowlist: vec!["10.0.0.0/40".to_string()],
+        };
+        assert!(invalid_request.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_pass_mark_evaluation() {
+        assert!(PassMarkType::Points.is_passed(30, 30, 80));
+        assert!(!PassMarkType::Points.is_passed(30, 29, 80));
+        assert!(PassMarkType::Percent.is_passed(60, 48, 80));
+        assert!(!PassMarkType::Percent.is_passed(60, 47, 80));
+        // An exam without points cannot be passed on percentage
+        assert!(!PassMarkType::Percent.is_passed(50, 0, 0));
+    }
+
+    #[tokio::test]
+    async fn test_grade_scale_bands() {
+        let bands = vec![
+            GradeBand { label: "B".to_string(), min_percent: 80.0 },
+            GradeBand { label: "A".to_string(), min_percent: 90.0 },
+            GradeBand { label: "C".to_string(), min_percent: 70.0 },
+        ];
+        assert_eq!(grade_for(&bands, 95.0).as_deref(), Some("A"));
+        assert_eq!(grade_for(&bands, 90.0).as_deref(), Some("A"));
+        assert_eq!(grade_for(&bands, 89.9).as_deref(), Some("B"));
+        assert_eq!(grade_for(&bands, 65.0), None);
+
+        let invalid_request = GradeScaleRequest {
+            name: "Letter grades".to_string(),
+            bands: vec![GradeBandRequest { label: "A".to_string(), min_percent: 120.0 }],
+        };
+        assert!(invalid_request.validate().is_err());
+
+        let empty_request = GradeScaleRequest {
+            name: "Letter grades".to_string(),
+            bands: vec![],
+        };
+        assert!(empty_request.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_negative_marking() {
+        let per_exam = NegativeMarking { wrong_answer_penalty: 0.25, score_floor: ScoreFloor::Exam };
+        assert_eq!(per_exam.penalty(Some(false), false, 0, 4), 1.0);
+        assert_eq!(per_exam.penalty(Some(false), true, 0, 4), 0.0);
+        assert_eq!(per_exam.penalty(Some(true), false, 4, 4), 0.0);
+        assert_eq!(per_exam.penalty(None, false, 0, 4), 0.0);
+        // Three right answers and two wrong ones of one point each
+        assert_eq!(per_exam.total(3, 0.5), 3);
+        assert_eq!(per_exam.total(0, 1.5), 0);
+
+        let per_question = NegativeMarking { wrong_answer_penalty: 0.5, score_floor: ScoreFloor::Question };
+        assert_eq!(per_question.penalty(Some(false), false, 0, 4), 0.0);
+        assert_eq!(per_question.penalty(Some(false), false, 1, 4), 1.0);
+        assert_eq!(per_question.penalty(Some(false), false, 3, 4), 2.0);
+
+        assert_eq!(NegativeMarking::default().penalty(Some(false), false, 0, 4), 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_review_policy_release() {
+        let now = Utc::now();
+        let ended = now - Duration::hours(1);
+        let running = now + Duration::hours(1);
+
+        assert!(ReviewPolicy::AfterSubmission.is_released(true, running, now));
+        assert!(!ReviewPolicy::AfterSubmission.is_released(false, ended, now));
+        assert!(!ReviewPolicy::AfterExamEnd.is_released(true, running, now));
+        assert!(ReviewPolicy::AfterExamEnd.is_released(true, ended, now));
+        assert!(!ReviewPolicy::AfterExamEnd.is_released(false, ended, now));
+        assert!(!ReviewPolicy::Never.is_released(true, ended, now));
+
+        assert_eq!("after_exam_end".parse::<ReviewPolicy>(), Ok(ReviewPolicy::AfterExamEnd));
+        assert!("later".parse::<ReviewPolicy>().is_err());
+        assert_eq!(ReviewPolicy::default(), ReviewPolicy::AfterSubmission);
     }
 }
@@ -6,3 +6,5 @@ pub mod service_tests;
 pub mod user_service_tests;
 
 
+
+pub mod attachment_tests;
\ No newline at end of file
@@ -1,7 +1,19 @@
 use crate::models::question::{
     CreateQuestionRequest, CreateChoiceRequest, UpdateQuestionRequest, UpdateChoiceRequest,
-    QuestionType, BulkCreateQuestionsRequest
+    QuestionType, BulkCreateQuestionsRequest, ScoringPolicy, AcceptedAnswer, check_choices, check_accepted_answers,
+    NumericAnswer, ToleranceType, check_numeric_answer, MatchingPair, StructuredResponse, check_structured_key,
+    ClozeGap, GapType, check_cloze, cloze_gap_keys, QuestionResponse, ChoiceResponse
 };
+use crate::models::content::{render_html, ContentFormat};
+use crate::models::revision::{diff_revisions, ApplyTo};
+use crate::models::attempt::{AnswerFeedback, AnswerSubmission, ChoiceFeedback};
+use crate::models::grading::{
+    check_numeric, grade_answer, grade_cloze, grade_matching, grade_multiple_select, grade_single_choice, grade_numeric, grade_ordering, grade_short_answer,
+    significant_figures, NumericMismatch
+};
+use crate::models::draw_rule::{draw_questions, DrawRule, PoolQuestion};
+use crate::models::question_bank::{LinkBankQuestionRequest, UpdateBankLinkRequest};
+use crate::services::question_bank::escape_like;
 use crate::models::user::UserRole;
 use crate::middleware::auth::AuthUser;
 use uuid::Uuid;
@@ -12,6 +24,8 @@ mod question_service_tests {
     use super::*;
     use crate::database::Database;
     use crate::services::question::QuestionService;
+    use crate::services::trash::TrashService;
+    use chrono::{DateTime, Duration, Utc};
     use sqlx::PgPool;
 
     // Helper function to create test database
@@ -34,24 +48,43 @@ mod question_service_tests {
             CreateChoiceRequest {
                 choice_text: "Option A".to_string(),
                 is_correct: true,
+                pin_to_bottom: false,
+                feedback: None,
+                attachment_ids: vec![],
             },
             CreateChoiceRequest {
                 choice_text: "Option B".to_string(),
                 is_correct: false,
+                pin_to_bottom: false,
+                feedback: None,
+                attachment_ids: vec![],
             },
         ];
 
         let request = CreateQuestionRequest {
             question_text: "What is 2 + 2?".to_string(),
             question_type: QuestionType::MultipleChoice,
-            correct_answer: Some("A".to_string()),
+            content_format: ContentFormat::Plain,
+            correct_answer: Some("Option A".to_string()),
             score: 10,
             choices: Some(choices),
+            section_id: None,
+            pool: None,
+            difficulty: None,
+            tags: vec![],
+            topic: None,
+            scoring_policy: ScoringPolicy::AllOrNothing,
+            accepted_answers: vec![],
+            numeric_answer: None,
+            matching_pairs: vec![],
+            ordering_items: vec![],
+            cloze_gaps: vec![],
+            explanation: None,
+            attachment_ids: vec![],
         };
 
         let result = question_service.create_question(exam_id, request).await;
-        if result.is_ok() {
-            let question_response = result.unwrap();
+        if let Ok(question_response) = result {
             assert_eq!(question_response.question_text, "What is 2 + 2?");
             assert_eq!(question_response.score, 10);
             assert_eq!(question_response.choices.len(), 2);
@@ -66,8 +99,7 @@ mod question_service_tests {
         let exam_id = Uuid::new_v4();
 
         let result = question_service.get_questions_by_exam(exam_id).await;
-        if result.is_ok() {
-            let questions = result.unwrap();
+        if let Ok(questions) = result {
             // Should return empty list for new exam
             assert!(questions.is_empty());
         }
@@ -80,11 +112,11 @@ mod question_service_tests {
         let question_service = QuestionService::new(db);
         let exam_id = Uuid::new_v4();
 
-        let result = question_service.get_questions_for_student(exam_id).await;
-        if result.is_ok() {
-            let questions = result.unwrap();
-            // Should return empty list for new exam
-            assert!(questions.is_empty());
+        let result = question_service.get_questions_for_student(exam_id, Uuid::new_v4()).await;
+        if let Ok(questions) = result {
+            // Should return no sections and no questions for new exam
+            assert!(questions.sections.is_empty());
+            assert!(questions.questions.is_empty());
         }
     }
 
@@ -99,16 +131,44 @@ mod question_service_tests {
             CreateQuestionRequest {
                 question_text: "Question 1".to_string(),
                 question_type: QuestionType::MultipleChoice,
+                content_format: ContentFormat::Plain,
                 correct_answer: Some("A".to_string()),
                 score: 5,
                 choices: None,
+                section_id: None,
+                pool: None,
+                difficulty: None,
+                tags: vec![],
+                topic: None,
+                scoring_policy: ScoringPolicy::AllOrNothing,
+                accepted_answers: vec![],
+                numeric_answer: None,
+                matching_pairs: vec![],
+                ordering_items: vec![],
+                cloze_gaps: vec![],
+                explanation: None,
+                attachment_ids: vec![],
             },
             CreateQuestionRequest {
                 question_text: "Question 2".to_string(),
                 question_type: QuestionType::Essay,
+                content_format: ContentFormat::Plain,
                 correct_answer: None,
                 score: 15,
                 choices: None,
+                section_id: None,
+                pool: None,
+                difficulty: None,
+                tags: vec![],
+                topic: None,
+                scoring_policy: ScoringPolicy::AllOrNothing,
+                accepted_answers: vec![],
+                numeric_answer: None,
+                matching_pairs: vec![],
+                ordering_items: vec![],
+                cloze_gaps: vec![],
+                explanation: None,
+                attachment_ids: vec![],
             },
         ];
 
@@ -118,8 +178,7 @@ mod question_service_tests {
         };
 
         let result = question_service.bulk_create_questions(request).await;
-        if result.is_ok() {
-            let created_questions = result.unwrap();
+        if let Ok(created_questions) = result {
             assert_eq!(created_questions.len(), 2);
         }
     }
@@ -132,22 +191,145 @@ mod question_service_tests {
         let exam_id = Uuid::new_v4();
 
         let result = question_service.get_exam_total_score(exam_id).await;
-        if result.is_ok() {
-            let total_score = result.unwrap();
+        if let Ok(total_score) = result {
             // Should be 0 for new exam with no questions
             assert_eq!(total_score, 0);
         }
     }
 
+    #[tokio::test]
+    #[ignore] // Ignored because it requires a test database
+    async fn test_purge_keeps_answered_question_of_live_exam() {
+        let db = create_test_db().await;
+        let pool = db.pool.clone();
+
+        let exam_id: Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO exams (title, duration_minutes, start_time, end_time, category, difficulty)
+            VALUES ('Purge test', 60, NOW() - INTERVAL '90 days', NOW() + INTERVAL '1 day', 'test', 'easy')
+            RETURNING id
+            "#,
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+        // Trashed long enough ago to be purged
+        let question_id: Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO questions (exam_id, question_text, question_type, score, deleted_at)
+            VALUES ($1, 'Trashed question', 'essay', 5, NOW() - INTERVAL '60 days')
+            RETURNING id
+            "#,
+        )
+        .bind(exam_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+        let attempt_id: Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO exam_attempts (exam_id, started_at, submitted_at, score_total, max_score)
+            VALUES ($1, NOW() - INTERVAL '70 days', NOW() - INTERVAL '70 days', 5, 5)
+            RETURNING id
+            "#,
+        )
+        .bind(exam_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        sqlx::query("INSERT INTO attempt_questions (attempt_id, question_id, position) VALUES ($1, $2, 0)")
+            .bind(attempt_id)
+            .bind(question_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO answers (attempt_id, question_id, answer_text, score_awarded) VALUES ($1, $2, 'Essay', 5)")
+            .bind(attempt_id)
+            .bind(question_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        TrashService::new(db).purge_expired(30).await.unwrap();
+
+        let answers: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM answers WHERE attempt_id = $1")
+            .bind(attempt_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(answers, 1);
+
+        let question_kept: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM questions WHERE id = $1)")
+            .bind(question_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert!(question_kept);
+    }
+
+    #[tokio::test]
+    #[ignore] // Ignored because it requires a test database
+    async fn test_purge_keeps_exam_required_by_live_exam() {
+        let db = create_test_db().await;
+        let pool = db.pool.clone();
+
+        let insert_exam = r#"
+            INSERT INTO exams (title, duration_minutes, start_time, end_time, category, difficulty, deleted_at)
+            VALUES ('Purge test', 60, NOW(), NOW() + INTERVAL '1 day', 'test', 'easy', $1)
+            RETURNING id
+        "#;
+        let required_id: Uuid = sqlx::query_scalar(insert_exam)
+            .bind(Utc::now() - Duration::days(60))
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        let dependent_id: Uuid = sqlx::query_scalar(insert_exam)
+            .bind(None::<DateTime<Utc>>)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO exam_prerequisites (exam_id, required_exam_id) VALUES ($1, $2)")
+            .bind(dependent_id)
+            .bind(required_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let summary = TrashService::new(db).purge_expired(30).await.unwrap();
+        assert!(summary.kept_exams.contains(&required_id));
+
+        let prerequisites: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM exam_prerequisites WHERE exam_id = $1")
+            .bind(dependent_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(prerequisites, 1);
+    }
+
     #[tokio::test]
     async fn test_question_request_validation() {
         // Test valid create request
         let valid_create = CreateQuestionRequest {
             question_text: "Valid question text".to_string(),
             question_type: QuestionType::MultipleChoice,
+            content_format: ContentFormat::Plain,
             correct_answer: Some("A".to_string()),
             score: 10,
             choices: None,
+            section_id: None,
+            pool: None,
+            difficulty: None,
+            tags: vec![],
+            topic: None,
+            scoring_policy: ScoringPolicy::AllOrNothing,
+            accepted_answers: vec![],
+            numeric_answer: None,
+            matching_pairs: vec![],
+            ordering_items: vec![],
+            cloze_gaps: vec![],
+            explanation: None,
+            attachment_ids: vec![],
         };
         assert!(valid_create.validate().is_ok());
 
@@ -155,9 +337,23 @@ mod question_service_tests {
         let invalid_create = CreateQuestionRequest {
             question_text: "".to_string(), // Empty text should fail
             question_type: QuestionType::MultipleChoice,
+            content_format: ContentFormat::Plain,
             correct_answer: Some("A".to_string()),
             score: 0, // Zero score should fail
             choices: None,
+            section_id: None,
+            pool: None,
+            difficulty: None,
+            tags: vec![],
+            topic: None,
+            scoring_policy: ScoringPolicy::AllOrNothing,
+            accepted_answers: vec![],
+            numeric_answer: None,
+            matching_pairs: vec![],
+            ordering_items: vec![],
+            cloze_gaps: vec![],
+            explanation: None,
+            attachment_ids: vec![],
         };
         assert!(invalid_create.validate().is_err());
 
@@ -165,9 +361,24 @@ mod question_service_tests {
         let valid_update = UpdateQuestionRequest {
             question_text: "Updated question text".to_string(),
             question_type: QuestionType::Essay,
+            content_format: ContentFormat::Plain,
             correct_answer: None,
             score: 15,
             choices: None,
+            section_id: None,
+            pool: None,
+            difficulty: None,
+            tags: vec![],
+            topic: None,
+            scoring_policy: ScoringPolicy::AllOrNothing,
+            accepted_answers: vec![],
+            numeric_answer: None,
+            matching_pairs: vec![],
+            ordering_items: vec![],
+            cloze_gaps: vec![],
+            explanation: None,
+            attachment_ids: vec![],
+            apply_to: ApplyTo::FutureAttempts,
         };
         assert!(valid_update.validate().is_ok());
 
@@ -175,9 +386,24 @@ mod question_service_tests {
         let invalid_update = UpdateQuestionRequest {
             question_text: "".to_string(), // Empty text should fail
             question_type: QuestionType::Essay,
+            content_format: ContentFormat::Plain,
             correct_answer: None,
             score: 0, // Zero score should fail
             choices: None,
+            section_id: None,
+            pool: None,
+            difficulty: None,
+            tags: vec![],
+            topic: None,
+            scoring_policy: ScoringPolicy::AllOrNothing,
+            accepted_answers: vec![],
+            numeric_answer: None,
+            matching_pairs: vec![],
+            ordering_items: vec![],
+            cloze_gaps: vec![],
+            explanation: None,
+            attachment_ids: vec![],
+            apply_to: ApplyTo::FutureAttempts,
         };
         assert!(invalid_update.validate().is_err());
     }
@@ -188,6 +414,9 @@ mod question_service_tests {
         let valid_choice = CreateChoiceRequest {
             choice_text: "Valid choice text".to_string(),
             is_correct: true,
+            pin_to_bottom: false,
+            feedback: None,
+            attachment_ids: vec![],
         };
         assert!(valid_choice.validate().is_ok());
 
@@ -195,6 +424,9 @@ mod question_service_tests {
         let invalid_choice = CreateChoiceRequest {
             choice_text: "".to_string(), // Empty text should fail
             is_correct: false,
+            pin_to_bottom: false,
+            feedback: None,
+            attachment_ids: vec![],
         };
         assert!(invalid_choice.validate().is_err());
 
@@ -204,6 +436,9 @@ mod question_service_tests {
             choice_text: "Updated choice text".to_string(),
             is_correct: false,
             delete: Some(false),
+            pin_to_bottom: false,
+            feedback: None,
+            attachment_ids: vec![],
         };
         assert!(valid_update_choice.validate().is_ok());
 
@@ -213,6 +448,9 @@ mod question_service_tests {
             choice_text: "Choice to delete".to_string(),
             is_correct: false,
             delete: Some(true),
+            pin_to_bottom: false,
+            feedback: None,
+            attachment_ids: vec![],
         };
         assert!(delete_choice.validate().is_ok());
     }
@@ -249,16 +487,44 @@ mod question_service_tests {
             CreateQuestionRequest {
                 question_text: "Question 1".to_string(),
                 question_type: QuestionType::MultipleChoice,
+                content_format: ContentFormat::Plain,
                 correct_answer: Some("A".to_string()),
                 score: 5,
                 choices: None,
+                section_id: None,
+                pool: None,
+                difficulty: None,
+                tags: vec![],
+                topic: None,
+                scoring_policy: ScoringPolicy::AllOrNothing,
+                accepted_answers: vec![],
+                numeric_answer: None,
+                matching_pairs: vec![],
+                ordering_items: vec![],
+                cloze_gaps: vec![],
+                explanation: None,
+                attachment_ids: vec![],
             },
             CreateQuestionRequest {
                 question_text: "Question 2".to_string(),
                 question_type: QuestionType::Essay,
+                content_format: ContentFormat::Plain,
                 correct_answer: None,
                 score: 10,
                 choices: None,
+                section_id: None,
+                pool: None,
+                difficulty: None,
+                tags: vec![],
+                topic: None,
+                scoring_policy: ScoringPolicy::AllOrNothing,
+                accepted_answers: vec![],
+                numeric_answer: None,
+                matching_pairs: vec![],
+                ordering_items: vec![],
+                cloze_gaps: vec![],
+                explanation: None,
+                attachment_ids: vec![],
             },
         ];
 
@@ -276,9 +542,23 @@ mod question_service_tests {
             CreateQuestionRequest {
                 question_text: "".to_string(), // Invalid
                 question_type: QuestionType::MultipleChoice,
+                content_format: ContentFormat::Plain,
                 correct_answer: Some("A".to_string()),
                 score: 0, // Invalid
                 choices: None,
+                section_id: None,
+                pool: None,
+                difficulty: None,
+                tags: vec![],
+                topic: None,
+                scoring_policy: ScoringPolicy::AllOrNothing,
+                accepted_answers: vec![],
+                numeric_answer: None,
+                matching_pairs: vec![],
+                ordering_items: vec![],
+                cloze_gaps: vec![],
+                explanation: None,
+                attachment_ids: vec![],
             },
         ];
 
@@ -343,27 +623,61 @@ mod question_handler_tests {
         let valid_request = CreateQuestionRequest {
             question_text: "What is 2 + 2?".to_string(),
             question_type: QuestionType::MultipleChoice,
+            content_format: ContentFormat::Plain,
             correct_answer: Some("4".to_string()),
             score: 10,
             choices: Some(vec![
                 CreateChoiceRequest {
                     choice_text: "2".to_string(),
                     is_correct: false,
+                    pin_to_bottom: false,
+                    feedback: None,
+                    attachment_ids: vec![],
                 },
                 CreateChoiceRequest {
                     choice_text: "4".to_string(),
                     is_correct: true,
+                    pin_to_bottom: false,
+                    feedback: None,
+                    attachment_ids: vec![],
                 },
             ]),
+            section_id: None,
+            pool: None,
+            difficulty: None,
+            tags: vec![],
+            topic: None,
+            scoring_policy: ScoringPolicy::AllOrNothing,
+            accepted_answers: vec![],
+            numeric_answer: None,
+            matching_pairs: vec![],
+            ordering_items: vec![],
+            cloze_gaps: vec![],
+            explanation: None,
+            attachment_ids: vec![],
         };
         assert!(valid_request.validate().is_ok());
 
         let invalid_request = CreateQuestionRequest {
             question_text: "".to_string(), // Empty text should fail
             question_type: QuestionType::MultipleChoice,
+            content_format: ContentFormat::Plain,
             correct_answer: Some("4".to_string()),
             score: 0, // Zero score should fail validation
             choices: None,
+            section_id: None,
+            pool: None,
+            difficulty: None,
+            tags: vec![],
+            topic: None,
+            scoring_policy: ScoringPolicy::AllOrNothing,
+            accepted_answers: vec![],
+            numeric_answer: None,
+            matching_pairs: vec![],
+            ordering_items: vec![],
+            cloze_gaps: vec![],
+            explanation: None,
+            attachment_ids: vec![],
         };
         assert!(invalid_request.validate().is_err());
     }
@@ -373,18 +687,48 @@ mod question_handler_tests {
         let valid_request = UpdateQuestionRequest {
             question_text: "Updated question text".to_string(),
             question_type: QuestionType::Essay,
+            content_format: ContentFormat::Plain,
             correct_answer: None,
             score: 15,
             choices: None,
+            section_id: None,
+            pool: None,
+            difficulty: None,
+            tags: vec![],
+            topic: None,
+            scoring_policy: ScoringPolicy::AllOrNothing,
+            accepted_answers: vec![],
+            numeric_answer: None,
+            matching_pairs: vec![],
+            ordering_items: vec![],
+            cloze_gaps: vec![],
+            explanation: None,
+            attachment_ids: vec![],
+            apply_to: ApplyTo::FutureAttempts,
         };
         assert!(valid_request.validate().is_ok());
 
         let invalid_request = UpdateQuestionRequest {
             question_text: "".to_string(), // Empty text should fail
             question_type: QuestionType::MultipleChoice,
+            content_format: ContentFormat::Plain,
             correct_answer: Some("Answer".to_string()),
             score: 0, // Zero score should fail
             choices: None,
+            section_id: None,
+            pool: None,
+            difficulty: None,
+            tags: vec![],
+            topic: None,
+            scoring_policy: ScoringPolicy::AllOrNothing,
+            accepted_answers: vec![],
+            numeric_answer: None,
+            matching_pairs: vec![],
+            ordering_items: vec![],
+            cloze_gaps: vec![],
+            explanation: None,
+            attachment_ids: vec![],
+            apply_to: ApplyTo::FutureAttempts,
         };
         assert!(invalid_request.validate().is_err());
     }
@@ -394,12 +738,18 @@ mod question_handler_tests {
         let valid_request = CreateChoiceRequest {
             choice_text: "Option A".to_string(),
             is_correct: true,
+            pin_to_bottom: false,
+            feedback: None,
+            attachment_ids: vec![],
         };
         assert!(valid_request.validate().is_ok());
 
         let invalid_request = CreateChoiceRequest {
             choice_text: "".to_string(), // Empty choice text should fail
             is_correct: false,
+            pin_to_bottom: false,
+            feedback: None,
+            attachment_ids: vec![],
         };
         assert!(invalid_request.validate().is_err());
     }
@@ -411,6 +761,9 @@ mod question_handler_tests {
             choice_text: "Updated choice text".to_string(),
             is_correct: false,
             delete: Some(false),
+            pin_to_bottom: false,
+            feedback: None,
+            attachment_ids: vec![],
         };
         assert!(valid_request.validate().is_ok());
 
@@ -419,6 +772,9 @@ mod question_handler_tests {
             choice_text: "".to_string(), // Empty text should fail
             is_correct: true,
             delete: None,
+            pin_to_bottom: false,
+            feedback: None,
+            attachment_ids: vec![],
         };
         assert!(invalid_request.validate().is_err());
     }
@@ -440,45 +796,99 @@ mod question_handler_tests {
         let mc_question = CreateQuestionRequest {
             question_text: "Multiple choice question?".to_string(),
             question_type: QuestionType::MultipleChoice,
+            content_format: ContentFormat::Plain,
             correct_answer: Some("A".to_string()),
             score: 5,
             choices: Some(vec![
                 CreateChoiceRequest {
                     choice_text: "Choice A".to_string(),
                     is_correct: true,
+                    pin_to_bottom: false,
+                    feedback: None,
+                    attachment_ids: vec![],
                 },
                 CreateChoiceRequest {
                     choice_text: "Choice B".to_string(),
                     is_correct: false,
+                    pin_to_bottom: false,
+                    feedback: None,
+                    attachment_ids: vec![],
                 },
             ]),
+            section_id: None,
+            pool: None,
+            difficulty: None,
+            tags: vec![],
+            topic: None,
+            scoring_policy: ScoringPolicy::AllOrNothing,
+            accepted_answers: vec![],
+            numeric_answer: None,
+            matching_pairs: vec![],
+            ordering_items: vec![],
+            cloze_gaps: vec![],
+            explanation: None,
+            attachment_ids: vec![],
         };
         assert!(matches!(mc_question.question_type, QuestionType::MultipleChoice));
 
         let essay_question = CreateQuestionRequest {
             question_text: "Essay question?".to_string(),
             question_type: QuestionType::Essay,
+            content_format: ContentFormat::Plain,
             correct_answer: None, // Essay questions don't have predefined correct answers
             score: 20,
             choices: None,
+            section_id: None,
+            pool: None,
+            difficulty: None,
+            tags: vec![],
+            topic: None,
+            scoring_policy: ScoringPolicy::AllOrNothing,
+            accepted_answers: vec![],
+            numeric_answer: None,
+            matching_pairs: vec![],
+            ordering_items: vec![],
+            cloze_gaps: vec![],
+            explanation: None,
+            attachment_ids: vec![],
         };
         assert!(matches!(essay_question.question_type, QuestionType::Essay));
 
         let tf_question = CreateQuestionRequest {
             question_text: "True or False question?".to_string(),
             question_type: QuestionType::TrueFalse,
+            content_format: ContentFormat::Plain,
             correct_answer: Some("true".to_string()),
             score: 2,
             choices: Some(vec![
                 CreateChoiceRequest {
                     choice_text: "True".to_string(),
                     is_correct: true,
+                    pin_to_bottom: false,
+                    feedback: None,
+                    attachment_ids: vec![],
                 },
                 CreateChoiceRequest {
                     choice_text: "False".to_string(),
                     is_correct: false,
+                    pin_to_bottom: false,
+                    feedback: None,
+                    attachment_ids: vec![],
                 },
             ]),
+            section_id: None,
+            pool: None,
+            difficulty: None,
+            tags: vec![],
+            topic: None,
+            scoring_policy: ScoringPolicy::AllOrNothing,
+            accepted_answers: vec![],
+            numeric_answer: None,
+            matching_pairs: vec![],
+            ordering_items: vec![],
+            cloze_gaps: vec![],
+            explanation: None,
+            attachment_ids: vec![],
         };
         assert!(matches!(tf_question.question_type, QuestionType::TrueFalse));
     }
@@ -500,9 +910,23 @@ mod question_handler_tests {
         let long_request = CreateQuestionRequest {
             question_text: long_text,
             question_type: QuestionType::Essay,
+            content_format: ContentFormat::Plain,
             correct_answer: None,
             score: 10,
             choices: None,
+            section_id: None,
+            pool: None,
+            difficulty: None,
+            tags: vec![],
+            topic: None,
+            scoring_policy: ScoringPolicy::AllOrNothing,
+            accepted_answers: vec![],
+            numeric_answer: None,
+            matching_pairs: vec![],
+            ordering_items: vec![],
+            cloze_gaps: vec![],
+            explanation: None,
+            attachment_ids: vec![],
         };
         assert!(long_request.validate().is_ok());
 
@@ -510,18 +934,38 @@ mod question_handler_tests {
         let special_request = CreateQuestionRequest {
             question_text: "What is the value of π (pi) ≈ 3.14159?".to_string(),
             question_type: QuestionType::MultipleChoice,
+            content_format: ContentFormat::Plain,
             correct_answer: Some("3.14159".to_string()),
             score: 5,
             choices: Some(vec![
                 CreateChoiceRequest {
                     choice_text: "3.14159".to_string(),
                     is_correct: true,
+                    pin_to_bottom: false,
+                    feedback: None,
+                    attachment_ids: vec![],
                 },
                 CreateChoiceRequest {
                     choice_text: "3.14".to_string(),
                     is_correct: false,
+                    pin_to_bottom: false,
+                    feedback: None,
+                    attachment_ids: vec![],
                 },
             ]),
+            section_id: None,
+            pool: None,
+            difficulty: None,
+            tags: vec![],
+            topic: None,
+            scoring_policy: ScoringPolicy::AllOrNothing,
+            accepted_answers: vec![],
+            numeric_answer: None,
+            matching_pairs: vec![],
+            ordering_items: vec![],
+            cloze_gaps: vec![],
+            explanation: None,
+            attachment_ids: vec![],
         };
         assert!(special_request.validate().is_ok());
 
@@ -529,9 +973,23 @@ mod question_handler_tests {
         let _whitespace_request = CreateQuestionRequest {
             question_text: "   ".to_string(),
             question_type: QuestionType::MultipleChoice,
+            content_format: ContentFormat::Plain,
             correct_answer: Some("Answer".to_string()),
             score: 5,
             choices: None,
+            section_id: None,
+            pool: None,
+            difficulty: None,
+            tags: vec![],
+            topic: None,
+            scoring_policy: ScoringPolicy::AllOrNothing,
+            accepted_answers: vec![],
+            numeric_answer: None,
+            matching_pairs: vec![],
+            ordering_items: vec![],
+            cloze_gaps: vec![],
+            explanation: None,
+            attachment_ids: vec![],
         };
         // This should likely fail validation if trimmed
         // assert!(whitespace_request.validate().is_err());
@@ -542,14 +1000,20 @@ mod question_handler_tests {
         let correct_choice = CreateChoiceRequest {
             choice_text: "Correct answer".to_string(),
             is_correct: true,
+            pin_to_bottom: false,
+            feedback: None,
+            attachment_ids: vec![],
         };
-        assert_eq!(correct_choice.is_correct, true);
+        assert!(correct_choice.is_correct);
 
         let incorrect_choice = CreateChoiceRequest {
             choice_text: "Wrong answer".to_string(),
             is_correct: false,
+            pin_to_bottom: false,
+            feedback: None,
+            attachment_ids: vec![],
         };
-        assert_eq!(incorrect_choice.is_correct, false);
+        assert!(!incorrect_choice.is_correct);
     }
 
     #[tokio::test]
@@ -558,18 +1022,38 @@ mod question_handler_tests {
         let positive_score_request = CreateQuestionRequest {
             question_text: "Valid question?".to_string(),
             question_type: QuestionType::MultipleChoice,
+            content_format: ContentFormat::Plain,
             correct_answer: Some("Yes".to_string()),
             score: 10,
             choices: Some(vec![
                 CreateChoiceRequest {
                     choice_text: "Yes".to_string(),
                     is_correct: true,
+                    pin_to_bottom: false,
+                    feedback: None,
+                    attachment_ids: vec![],
                 },
                 CreateChoiceRequest {
                     choice_text: "No".to_string(),
                     is_correct: false,
+                    pin_to_bottom: false,
+                    feedback: None,
+                    attachment_ids: vec![],
                 },
             ]),
+            section_id: None,
+            pool: None,
+            difficulty: None,
+            tags: vec![],
+            topic: None,
+            scoring_policy: ScoringPolicy::AllOrNothing,
+            accepted_answers: vec![],
+            numeric_answer: None,
+            matching_pairs: vec![],
+            ordering_items: vec![],
+            cloze_gaps: vec![],
+            explanation: None,
+            attachment_ids: vec![],
         };
         assert!(positive_score_request.validate().is_ok());
         assert!(positive_score_request.score > 0);
@@ -578,9 +1062,23 @@ mod question_handler_tests {
         let high_score_request = CreateQuestionRequest {
             question_text: "High value question?".to_string(),
             question_type: QuestionType::Essay,
+            content_format: ContentFormat::Plain,
             correct_answer: None,
             score: 100,
             choices: None,
+            section_id: None,
+            pool: None,
+            difficulty: None,
+            tags: vec![],
+            topic: None,
+            scoring_policy: ScoringPolicy::AllOrNothing,
+            accepted_answers: vec![],
+            numeric_answer: None,
+            matching_pairs: vec![],
+            ordering_items: vec![],
+            cloze_gaps: vec![],
+            explanation: None,
+            attachment_ids: vec![],
         };
         assert!(high_score_request.validate().is_ok());
         assert_eq!(high_score_request.score, 100);
@@ -592,18 +1090,38 @@ mod question_handler_tests {
         let mc_with_answer = CreateQuestionRequest {
             question_text: "MC question?".to_string(),
             question_type: QuestionType::MultipleChoice,
+            content_format: ContentFormat::Plain,
             correct_answer: Some("A".to_string()),
             score: 5,
             choices: Some(vec![
                 CreateChoiceRequest {
                     choice_text: "Choice A".to_string(),
                     is_correct: true,
+                    pin_to_bottom: false,
+                    feedback: None,
+                    attachment_ids: vec![],
                 },
                 CreateChoiceRequest {
                     choice_text: "Choice B".to_string(),
                     is_correct: false,
+                    pin_to_bottom: false,
+                    feedback: None,
+                    attachment_ids: vec![],
                 },
             ]),
+            section_id: None,
+            pool: None,
+            difficulty: None,
+            tags: vec![],
+            topic: None,
+            scoring_policy: ScoringPolicy::AllOrNothing,
+            accepted_answers: vec![],
+            numeric_answer: None,
+            matching_pairs: vec![],
+            ordering_items: vec![],
+            cloze_gaps: vec![],
+            explanation: None,
+            attachment_ids: vec![],
         };
         assert!(mc_with_answer.correct_answer.is_some());
 
@@ -611,9 +1129,23 @@ mod question_handler_tests {
         let essay_no_answer = CreateQuestionRequest {
             question_text: "Essay question?".to_string(),
             question_type: QuestionType::Essay,
+            content_format: ContentFormat::Plain,
             correct_answer: None,
             score: 20,
             choices: None,
+            section_id: None,
+            pool: None,
+            difficulty: None,
+            tags: vec![],
+            topic: None,
+            scoring_policy: ScoringPolicy::AllOrNothing,
+            accepted_answers: vec![],
+            numeric_answer: None,
+            matching_pairs: vec![],
+            ordering_items: vec![],
+            cloze_gaps: vec![],
+            explanation: None,
+            attachment_ids: vec![],
         };
         assert!(essay_no_answer.correct_answer.is_none());
 
@@ -621,18 +1153,38 @@ mod question_handler_tests {
         let tf_with_answer = CreateQuestionRequest {
             question_text: "TF question?".to_string(),
             question_type: QuestionType::TrueFalse,
+            content_format: ContentFormat::Plain,
             correct_answer: Some("false".to_string()),
             score: 2,
             choices: Some(vec![
                 CreateChoiceRequest {
                     choice_text: "True".to_string(),
                     is_correct: false,
+                    pin_to_bottom: false,
+                    feedback: None,
+                    attachment_ids: vec![],
                 },
                 CreateChoiceRequest {
                     choice_text: "False".to_string(),
                     is_correct: true,
+                    pin_to_bottom: false,
+                    feedback: None,
+                    attachment_ids: vec![],
                 },
             ]),
+            section_id: None,
+            pool: None,
+            difficulty: None,
+            tags: vec![],
+            topic: None,
+            scoring_policy: ScoringPolicy::AllOrNothing,
+            accepted_answers: vec![],
+            numeric_answer: None,
+            matching_pairs: vec![],
+            ordering_items: vec![],
+            cloze_gaps: vec![],
+            explanation: None,
+            attachment_ids: vec![],
         };
         assert!(tf_with_answer.correct_answer.is_some());
     }
@@ -649,6 +1201,7 @@ mod question_handler_tests {
         let update_request = UpdateQuestionRequest {
             question_text: "Updated question text".to_string(),
             question_type: QuestionType::MultipleChoice,
+            content_format: ContentFormat::Plain,
             correct_answer: Some("Updated answer".to_string()),
             score: 15,
             choices: Some(vec![
@@ -657,8 +1210,25 @@ mod question_handler_tests {
                     choice_text: "Updated choice".to_string(),
                     is_correct: true,
                     delete: Some(false),
+                    pin_to_bottom: false,
+                    feedback: None,
+                    attachment_ids: vec![],
                 },
             ]),
+            section_id: None,
+            pool: None,
+            difficulty: None,
+            tags: vec![],
+            topic: None,
+            scoring_policy: ScoringPolicy::AllOrNothing,
+            accepted_answers: vec![],
+            numeric_answer: None,
+            matching_pairs: vec![],
+            ordering_items: vec![],
+            cloze_gaps: vec![],
+            explanation: None,
+            attachment_ids: vec![],
+            apply_to: ApplyTo::FutureAttempts,
         };
         assert!(update_request.validate().is_ok());
     }
@@ -670,6 +1240,9 @@ mod question_handler_tests {
             choice_text: "Updated choice".to_string(),
             is_correct: true,
             delete: Some(false),
+            pin_to_bottom: false,
+            feedback: None,
+            attachment_ids: vec![],
         };
         assert!(update_choice.validate().is_ok());
 
@@ -679,9 +1252,12 @@ mod question_handler_tests {
             choice_text: "Same text".to_string(),
             is_correct: false, // Changed from true to false
             delete: Some(false),
+            pin_to_bottom: false,
+            feedback: None,
+            attachment_ids: vec![],
         };
         assert!(toggle_correctness.validate().is_ok());
-        assert_eq!(toggle_correctness.is_correct, false);
+        assert!(!toggle_correctness.is_correct);
     }
 
     #[tokio::test]
@@ -693,25 +1269,59 @@ mod question_handler_tests {
                 CreateQuestionRequest {
                     question_text: "Question 1".to_string(),
                     question_type: QuestionType::MultipleChoice,
+                    content_format: ContentFormat::Plain,
                     correct_answer: Some("A".to_string()),
                     score: 5,
                     choices: Some(vec![
                         CreateChoiceRequest {
                             choice_text: "Choice A".to_string(),
                             is_correct: true,
+                            pin_to_bottom: false,
+                            feedback: None,
+                            attachment_ids: vec![],
                         },
                         CreateChoiceRequest {
                             choice_text: "Choice B".to_string(),
                             is_correct: false,
+                            pin_to_bottom: false,
+                            feedback: None,
+                            attachment_ids: vec![],
                         },
                     ]),
+                    section_id: None,
+                    pool: None,
+                    difficulty: None,
+                    tags: vec![],
+                    topic: None,
+                    scoring_policy: ScoringPolicy::AllOrNothing,
+                    accepted_answers: vec![],
+                    numeric_answer: None,
+                    matching_pairs: vec![],
+                    ordering_items: vec![],
+                    cloze_gaps: vec![],
+                    explanation: None,
+                    attachment_ids: vec![],
                 },
                 CreateQuestionRequest {
                     question_text: "Question 2".to_string(),
                     question_type: QuestionType::Essay,
+                    content_format: ContentFormat::Plain,
                     correct_answer: None,
                     score: 10,
                     choices: None,
+                    section_id: None,
+                    pool: None,
+                    difficulty: None,
+                    tags: vec![],
+                    topic: None,
+                    scoring_policy: ScoringPolicy::AllOrNothing,
+                    accepted_answers: vec![],
+                    numeric_answer: None,
+                    matching_pairs: vec![],
+                    ordering_items: vec![],
+                    cloze_gaps: vec![],
+                    explanation: None,
+                    attachment_ids: vec![],
                 },
             ],
         };
@@ -749,4 +1359,540 @@ mod question_handler_tests {
         let invalid_type: Result<QuestionType, _> = "invalid_type".parse();
         assert!(invalid_type.is_err());
     }
+
+    fn pool_question(section_id: Option<Uuid>, pool: &str, difficulty: &str) -> PoolQuestion {
+        PoolQuestion {
+            id: Uuid::new_v4(),
+            section_id,
+            pool: Some(pool.to_string()),
+            difficulty: Some(difficulty.to_string()),
+        }
+    }
+
+    fn draw_rule(pool: &str, difficulty: Option<&str>, draw_count: i32) -> DrawRule {
+        DrawRule {
+            id: Uuid::new_v4(),
+            exam_id: Uuid::new_v4(),
+            section_id: None,
+            pool: Some(pool.to_string()),
+            difficulty: difficulty.map(str::to_string),
+            draw_count,
+            created_at: chrono::Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_draw_questions_by_rules() {
+        let mut questions: Vec<PoolQuestion> = (0..6).map(|_| pool_question(None, "algebra", "easy")).collect();
+        questions.extend((0..4).map(|_| pool_question(None, "algebra", "hard")));
+        questions.extend((0..2).map(|_| pool_question(None, "geometry", "easy")));
+
+        // Stratified: 3 easy and 2 hard algebra questions; geometry matches no rule
+        let rules = vec![draw_rule("algebra", Some("easy"), 3), draw_rule("algebra", Some("hard"), 2)];
+        let drawn = draw_questions(&questions, &rules, &std::collections::HashMap::new(), &mut rand::thread_rng());
+
+        assert_eq!(drawn.len(), 7);
+        let count = |pool: &str, difficulty: &str| {
+            questions
+                .iter()
+                .filter(|q| drawn.contains(&q.id))
+                .filter(|q| q.pool.as_deref() == Some(pool) && q.difficulty.as_deref() == Some(difficulty))
+                .count()
+        };
+        assert_eq!(count("algebra", "easy"), 3);
+        assert_eq!(count("algebra", "hard"), 2);
+        assert_eq!(count("geometry", "easy"), 2);
+
+        // The drawn set keeps the question order
+        let positions: Vec<usize> = drawn
+            .iter()
+            .map(|id| questions.iter().position(|q| q.id == *id).unwrap())
+            .collect();
+        assert!(positions.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[tokio::test]
+    async fn test_draw_questions_section_draw_count() {
+        let section_id = Uuid::new_v4();
+        let questions: Vec<PoolQuestion> = (0..5).map(|_| pool_question(Some(section_id), "general", "easy")).collect();
+        let section_draw_counts = std::collections::HashMap::from([(section_id, 2)]);
+
+        let drawn = draw_questions(&questions, &[], &section_draw_counts, &mut rand::thread_rng());
+        assert_eq!(drawn.len(), 2);
+
+        // A rule asking for more than the pool has draws all of it
+        let drawn = draw_questions(&questions, &[draw_rule("general", None, 10)], &section_draw_counts, &mut rand::thread_rng());
+        assert_eq!(drawn.len(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_bank_link_request_validation() {
+        let link = LinkBankQuestionRequest {
+            question_id: Uuid::new_v4(),
+            section_id: None,
+            score_override: Some(5),
+        };
+        assert!(link.validate().is_ok());
+
+        let no_override = LinkBankQuestionRequest {
+            question_id: Uuid::new_v4(),
+            section_id: None,
+            score_override: None,
+        };
+        assert!(no_override.validate().is_ok());
+
+        let zero_score = UpdateBankLinkRequest {
+            section_id: None,
+            score_override: Some(0),
+        };
+        assert!(zero_score.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_bank_search_text_is_escaped() {
+        assert_eq!(escape_like("photosynthesis"), "photosynthesis");
+        assert_eq!(escape_like("50% of x_1"), "50\\% of x\\_1");
+        assert_eq!(escape_like("a\\b"), "a\\\\b");
+    }
+
+    #[tokio::test]
+    async fn test_multiple_select_scoring_policies() {
+        let (a, b, c, d) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let correct = [a, b, c];
+
+        // Exactly right gets the full score under every policy
+        for policy in [ScoringPolicy::AllOrNothing, ScoringPolicy::PerCorrect, ScoringPolicy::RightMinusWrong] {
+            assert_eq!(grade_multiple_select(&[c, a, b], &correct, policy, 6), (Some(true), 6));
+        }
+
+        // Two of three correct choices
+        assert_eq!(grade_multiple_select(&[a, b], &correct, ScoringPolicy::AllOrNothing, 6), (Some(false), 0));
+        assert_eq!(grade_multiple_select(&[a, b], &correct, ScoringPolicy::PerCorrect, 6), (Some(false), 4));
+        assert_eq!(grade_multiple_select(&[a, b], &correct, ScoringPolicy::RightMinusWrong, 6), (Some(false), 4));
+
+        // Two correct and one wrong choice
+        assert_eq!(grade_multiple_select(&[a, b, d], &correct, ScoringPolicy::PerCorrect, 6), (Some(false), 0));
+        assert_eq!(grade_multiple_select(&[a, b, d], &correct, ScoringPolicy::RightMinusWrong, 6), (Some(false), 2));
+
+        // Right-minus-wrong never goes below zero
+        assert_eq!(grade_multiple_select(&[d], &correct, ScoringPolicy::RightMinusWrong, 6), (Some(false), 0));
+        assert_eq!(grade_multiple_select(&[], &correct, ScoringPolicy::RightMinusWrong, 6), (Some(false), 0));
+    }
+
+    #[tokio::test]
+    async fn test_multiple_select_needs_a_correct_choice() {
+        assert!(check_choices(&QuestionType::MultipleSelect, None, &[("a", false), ("b", false)]).is_err());
+        assert!(check_choices(&QuestionType::MultipleSelect, None, &[("a", true), ("b", true)]).is_ok());
+        assert!(check_choices(&QuestionType::Essay, None, &[]).is_ok());
+        assert_eq!("multiple_select".parse::<QuestionType>().unwrap().to_string(), "multiple_select");
+    }
+
+    fn accepted(text: &str, score_percent: i32) -> AcceptedAnswer {
+        AcceptedAnswer {
+            text: text.to_string(),
+            score_percent,
+            case_sensitive: false,
+            ignore_whitespace: false,
+            ignore_diacritics: false,
+            regex: false,
+            max_distance: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_short_answer_matching() {
+        let answers = vec![
+            AcceptedAnswer { ignore_diacritics: true, max_distance: 1, ..accepted("Photosynthèse", 100) },
+            AcceptedAnswer { regex: true, ..accepted(r"light\s+reactions?", 50) },
+        ];
+
+        assert_eq!(grade_short_answer("  photosynthese ", &answers, 4), (Some(true), 4));
+        assert_eq!(grade_short_answer("Photosinthese", &answers, 4), (Some(true), 4));
+        assert_eq!(grade_short_answer("Light  Reaction", &answers, 4), (Some(false), 2));
+        assert_eq!(grade_short_answer("the light reaction", &answers, 4), (Some(false), 0));
+        assert_eq!(grade_short_answer("respiration", &answers, 4), (Some(false), 0));
+
+        let case_sensitive = vec![AcceptedAnswer { case_sensitive: true, ..accepted("NaCl", 100) }];
+        assert_eq!(grade_short_answer("nacl", &case_sensitive, 2), (Some(false), 0));
+
+        // No accepted answers leaves the question for manual grading
+        assert_eq!(grade_short_answer("anything", &[], 2), (None, 0));
+    }
+
+    #[tokio::test]
+    async fn test_accepted_answers_validation() {
+        assert!(check_accepted_answers(&QuestionType::ShortAnswer, &[]).is_err());
+        assert!(check_accepted_answers(&QuestionType::ShortAnswer, &[accepted("Paris", 100)]).is_ok());
+        assert!(check_accepted_answers(&QuestionType::ShortAnswer, &[AcceptedAnswer { regex: true, ..accepted("(unclosed", 100) }]).is_err());
+        assert!(check_accepted_answers(&QuestionType::ShortAnswer, &[AcceptedAnswer { regex: true, max_distance: 2, ..accepted("a+", 100) }]).is_err());
+        assert!(accepted("Paris", 101).validate().is_err());
+    }
+
+    fn gravity() -> NumericAnswer {
+        NumericAnswer {
+            value: 9.81,
+            tolerance: 0.02,
+            tolerance_type: ToleranceType::Absolute,
+            significant_figures: None,
+            units: vec!["m/s^2".to_string(), "N/kg".to_string()],
+            unit_required: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_numeric_tolerance_and_units() {
+        let spec = gravity();
+        assert_eq!(grade_numeric("9.8 m/s^2", &spec, 3), (Some(true), 3, None));
+        assert_eq!(grade_numeric("9.80", &spec, 3), (Some(true), 3, None));
+        assert_eq!(grade_numeric(" 9.82 m / s^2 ", &spec, 3), (Some(true), 3, None));
+        assert_eq!(check_numeric("9.7 m/s^2", &spec), Err(NumericMismatch::OutOfTolerance));
+        assert_eq!(check_numeric("9.8 km/h", &spec), Err(NumericMismatch::WrongUnit));
+        assert_eq!(check_numeric("about ten", &spec), Err(NumericMismatch::NotANumber));
+        assert_eq!(
+            check_numeric("9.8", &NumericAnswer { unit_required: true, ..gravity() }),
+            Err(NumericMismatch::MissingUnit)
+        );
+
+        let relative = NumericAnswer { value: 3.0e8, tolerance: 0.01, tolerance_type: ToleranceType::Relative, ..gravity() };
+        assert_eq!(check_numeric("2.98e8", &relative), Ok(()));
+        assert_eq!(check_numeric("2.9e8", &relative), Err(NumericMismatch::OutOfTolerance));
+
+        let (_, score, reason) = grade_numeric("12", &spec, 3);
+        assert_eq!((score, reason.map(|r| r.as_str())), (0, Some("out_of_tolerance")));
+    }
+
+    #[tokio::test]
+    async fn test_numeric_significant_figures() {
+        assert_eq!(significant_figures("9.80"), 3);
+        assert_eq!(significant_figures("0.0045"), 2);
+        assert_eq!(significant_figures("1500"), 2);
+        assert_eq!(significant_figures("1.500e3"), 4);
+        assert_eq!(significant_figures("-2.0"), 2);
+
+        let spec = NumericAnswer { significant_figures: Some(3), ..gravity() };
+        assert_eq!(check_numeric("9.81", &spec), Ok(()));
+        assert_eq!(check_numeric("9.8", &spec), Err(NumericMismatch::SignificantFigures));
+
+        assert!(check_numeric_answer(&QuestionType::Numeric, None).is_err());
+        assert!(check_numeric_answer(&QuestionType::Numeric, Some(&gravity())).is_ok());
+    }
+
+    fn pair(left: &str, right: &str) -> MatchingPair {
+        MatchingPair { left: left.to_string(), right: right.to_string() }
+    }
+
+    #[tokio::test]
+    async fn test_matching_partial_scoring() {
+        let key = vec![pair("France", "Paris"), pair("Spain", "Madrid"), pair("Italy", "Rome"), pair("Peru", "Lima")];
+        let all = vec![pair("Peru", "Lima"), pair("Italy", "Rome"), pair("Spain", "Madrid"), pair("France", "Paris")];
+        assert_eq!(grade_matching(&all, &key, 8), (Some(true), 8));
+
+        let swapped = vec![pair("France", "Madrid"), pair("Spain", "Paris"), pair("Italy", "Rome"), pair("Peru", "Lima")];
+        assert_eq!(grade_matching(&swapped, &key, 8), (Some(false), 4));
+
+        // The first pair for a left item counts
+        let repeated = vec![pair("France", "Paris"), pair("France", "Madrid"), pair(" Italy ", "Rome ")];
+        assert_eq!(grade_matching(&repeated, &key, 8), (Some(false), 4));
+        assert_eq!(grade_matching(&[], &key, 8), (Some(false), 0));
+    }
+
+    #[tokio::test]
+    async fn test_ordering_partial_scoring() {
+        let key: Vec<String> = ["Mercury", "Venus", "Earth", "Mars"].map(String::from).to_vec();
+        assert_eq!(grade_ordering(&key, &key, 4), (Some(true), 4));
+
+        let answer: Vec<String> = ["Mercury", "Earth", "Venus", "Mars"].map(String::from).to_vec();
+        assert_eq!(grade_ordering(&answer, &key, 4), (Some(false), 2));
+        assert_eq!(grade_ordering(&answer[..1], &key, 4), (Some(false), 1));
+        assert_eq!(grade_ordering(&[], &[], 4), (None, 0));
+    }
+
+    #[tokio::test]
+    async fn test_structured_key_validation() {
+        let pairs = vec![pair("a", "1"), pair("b", "2")];
+        let items: Vec<String> = vec!["first".to_string(), "second".to_string()];
+        assert!(check_structured_key(&QuestionType::Matching, &pairs, &[]).is_ok());
+        assert!(check_structured_key(&QuestionType::Matching, &pairs[..1], &[]).is_err());
+        assert!(check_structured_key(&QuestionType::Matching, &[pair("a", "1"), pair("b", "1")], &[]).is_err());
+        assert!(check_structured_key(&QuestionType::Ordering, &[], &items).is_ok());
+        assert!(check_structured_key(&QuestionType::Ordering, &[], &items[..1]).is_err());
+        assert!(check_structured_key(&QuestionType::Ordering, &[], &["x".to_string(), " x".to_string()]).is_err());
+        assert!(check_structured_key(&QuestionType::Essay, &[], &[]).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_structured_response_payload() {
+        let response: StructuredResponse =
+            serde_json::from_value(serde_json::json!({ "matching": [{ "left": "a", "right": "1" }] })).unwrap();
+        assert_eq!(response, StructuredResponse::Matching(vec![pair("a", "1")]));
+
+        let response: StructuredResponse =
+            serde_json::from_value(serde_json::json!({ "ordering": ["b", "a"] })).unwrap();
+        assert_eq!(response, StructuredResponse::Ordering(vec!["b".to_string(), "a".to_string()]));
+    }
+
+    fn cloze_gaps() -> Vec<ClozeGap> {
+        vec![
+            ClozeGap {
+                key: "verb".to_string(),
+                gap_type: GapType::Dropdown,
+                score: 1,
+                options: vec!["go".to_string(), "went".to_string(), "gone".to_string()],
+                correct_option: Some("went".to_string()),
+                accepted_answers: vec![],
+                numeric_answer: None,
+            },
+            ClozeGap {
+                key: "place".to_string(),
+                gap_type: GapType::Text,
+                score: 2,
+                options: vec![],
+                correct_option: None,
+                accepted_answers: vec![accepted("the market", 100)],
+                numeric_answer: None,
+            },
+            ClozeGap {
+                key: "count".to_string(),
+                gap_type: GapType::Numeric,
+                score: 1,
+                options: vec![],
+                correct_option: None,
+                accepted_answers: vec![],
+                numeric_answer: Some(NumericAnswer { value: 3.0, units: vec![], ..gravity() }),
+            },
+        ]
+    }
+
+    const CLOZE_TEXT: &str = "Yesterday I {{verb}} to {{ place }} and bought {{count}} apples.";
+
+    #[tokio::test]
+    async fn test_cloze_validation() {
+        assert_eq!(cloze_gap_keys(CLOZE_TEXT), vec!["verb", "place", "count"]);
+        assert!(check_cloze(&QuestionType::Cloze, CLOZE_TEXT, 4, &cloze_gaps()).is_ok());
+        assert!(check_cloze(&QuestionType::Cloze, CLOZE_TEXT, 5, &cloze_gaps()).is_err());
+        assert!(check_cloze(&QuestionType::Cloze, CLOZE_TEXT, 0, &[]).is_err());
+        assert!(check_cloze(&QuestionType::Cloze, "I {{verb}} there.", 4, &cloze_gaps()).is_err());
+        assert!(check_cloze(&QuestionType::Cloze, &format!("{CLOZE_TEXT} {{{{extra}}}}"), 4, &cloze_gaps()).is_err());
+
+        let mut gaps = cloze_gaps();
+        gaps[0].correct_option = Some("goes".to_string());
+        assert!(check_cloze(&QuestionType::Cloze, CLOZE_TEXT, 4, &gaps).is_err());
+
+        let mut gaps = cloze_gaps();
+        gaps[1].accepted_answers.clear();
+        assert!(check_cloze(&QuestionType::Cloze, CLOZE_TEXT, 4, &gaps).is_err());
+        assert!(check_cloze(&QuestionType::Essay, CLOZE_TEXT, 4, &gaps).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_cloze_grading_per_gap() {
+        let answers = |verb: &str, place: &str, count: &str| {
+            [("verb", verb), ("place", place), ("count", count)]
+                .into_iter()
+                .map(|(key, answer)| (key.to_string(), answer.to_string()))
+                .collect()
+        };
+
+        let (is_correct, score, results) = grade_cloze(&answers("went", "The Market", "3"), &cloze_gaps(), 4);
+        assert_eq!((is_correct, score), (Some(true), 4));
+        assert!(results.iter().all(|result| result.is_correct));
+
+        let (is_correct, score, results) = grade_cloze(&answers("gone", "the market", "three"), &cloze_gaps(), 4);
+        assert_eq!((is_correct, score), (Some(false), 2));
+        let marks: Vec<_> = results.iter().map(|r| (r.key.as_str(), r.is_correct, r.score_awarded)).collect();
+        assert_eq!(marks, vec![("verb", false, 0), ("place", true, 2), ("count", false, 0)]);
+        assert_eq!(results[2].incorrect_reason.as_deref(), Some("not_a_number"));
+
+        // A score override on a bank link scales the gap scores
+        let (_, score, _) = grade_cloze(&answers("went", "", ""), &cloze_gaps(), 8);
+        assert_eq!(score, 2);
+    }
+
+    #[tokio::test]
+    async fn test_choice_consistency_validation() {
+        let choices = [("O(n log n)", false), ("O(n²)", true)];
+        assert!(check_choices(&QuestionType::MultipleChoice, None, &choices).is_ok());
+        assert!(check_choices(&QuestionType::MultipleChoice, Some(" o(N²) "), &choices).is_ok());
+        assert!(check_choices(&QuestionType::MultipleChoice, Some("O(n log n)"), &choices).is_err());
+        assert!(check_choices(&QuestionType::MultipleChoice, None, &[("a", true), ("b", true)]).is_err());
+        assert!(check_choices(&QuestionType::TrueFalse, Some("true"), &[("True", false), ("False", false)]).is_err());
+        // Without choices a true/false question is graded by correct_answer alone
+        assert!(check_choices(&QuestionType::TrueFalse, Some("true"), &[]).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_single_choice_grading() {
+        let (right, wrong) = (Uuid::new_v4(), Uuid::new_v4());
+        let choices = [(right, true), (wrong, false)];
+        assert_eq!(grade_single_choice(Some(right), &choices, 5), (Some(true), 5));
+        assert_eq!(grade_single_choice(Some(wrong), &choices, 5), (Some(false), 0));
+        assert_eq!(grade_single_choice(None, &choices, 5), (Some(false), 0));
+    }
+
+    fn revision_of(correct: &str) -> QuestionResponse {
+        let choice = |id: u128, text: &str| ChoiceResponse {
+            id: Uuid::from_u128(id),
+            question_id: Uuid::nil(),
+            choice_text: text.to_string(),
+            choice_html: String::new(),
+            is_correct: text == correct,
+            pin_to_bottom: false,
+            feedback: None,
+            attachment_ids: vec![],
+            created_at: chrono::DateTime::UNIX_EPOCH,
+        };
+        QuestionResponse {
+            id: Uuid::nil(),
+            exam_id: None,
+            section_id: None,
+            question_text: "Capital of Australia?".to_string(),
+            question_type: QuestionType::MultipleChoice,
+            content_format: ContentFormat::Plain,
+            question_html: String::new(),
+            correct_answer: Some(correct.to_string()),
+            score: 2,
+            scoring_policy: ScoringPolicy::AllOrNothing,
+            accepted_answers: vec![],
+            numeric_answer: None,
+            matching_pairs: vec![],
+            ordering_items: vec![],
+            cloze_gaps: vec![],
+            explanation: None,
+            attachment_ids: vec![],
+            pool: None,
+            difficulty: None,
+            tags: vec![],
+            topic: None,
+            from_bank: false,
+            revision: 1,
+            choices: vec![choice(1, "Sydney"), choice(2, "Canberra")],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_revision_diff() {
+        let before = revision_of("Sydney");
+        let mut after = revision_of("Canberra");
+        after.revision = 2;
+        after.explanation = Some("Canberra was built as a compromise.".to_string());
+        after.choices.pop();
+
+        let changes = diff_revisions(&before, &after);
+        let fields: Vec<&str> = changes.iter().map(|change| change.field.as_str()).collect();
+        assert_eq!(
+            fields,
+            vec![
+                format!("choices[{}]", Uuid::from_u128(1)).as_str(),
+                format!("choices[{}]", Uuid::from_u128(2)).as_str(),
+                "correct_answer",
+                "explanation",
+            ]
+        );
+        // The removed choice has no value in the new revision
+        assert!(changes[1].to.is_null());
+        assert_eq!(changes[2].to, "Canberra");
+
+        assert!(diff_revisions(&before, &before).is_empty());
+        assert_eq!(ApplyTo::default(), ApplyTo::FutureAttempts);
+    }
+
+    #[tokio::test]
+    async fn test_grade_answer_against_revision() {
+        let answer = AnswerSubmission {
+            question_id: Uuid::nil(),
+            answer_text: String::new(),
+            choice_ids: vec![Uuid::from_u128(2)],
+            response: None,
+        };
+
+        // The same answer is graded by the key of each revision
+        let old_key = grade_answer(&revision_of("Sydney"), 2, &answer).unwrap();
+        assert_eq!((old_key.is_correct, old_key.score_awarded), (Some(false), 0));
+        let fixed_key = grade_answer(&revision_of("Canberra"), 2, &answer).unwrap();
+        assert_eq!((fixed_key.is_correct, fixed_key.score_awarded), (Some(true), 2));
+        assert_eq!(fixed_key.choice_ids, Some(vec![Uuid::from_u128(2)]));
+
+        let foreign_choice = AnswerSubmission { choice_ids: vec![Uuid::from_u128(9)], ..answer };
+        assert!(grade_answer(&revision_of("Canberra"), 2, &foreign_choice).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_feedback_from_answered_revision() {
+        let mut answered = revision_of("Canberra");
+        answered.explanation = Some("Canberra was built as a compromise.".to_string());
+        answered.choices[0].feedback = Some("Sydney is the largest city, not the capital.".to_string());
+
+        // A later edit rewrites the feedback the student never saw
+        let mut current = answered.clone();
+        current.explanation = Some("See chapter 4.".to_string());
+        current.choices[0].feedback = Some("Rewritten feedback".to_string());
+
+        let feedback = AnswerFeedback::for_answer(&answered, &[Uuid::from_u128(1)]);
+        assert_eq!(feedback.explanation.as_deref(), Some("Canberra was built as a compromise."));
+        assert_eq!(
+            feedback.choices,
+            vec![ChoiceFeedback {
+                choice_id: Uuid::from_u128(1),
+                feedback: "Sydney is the largest city, not the capital.".to_string(),
+            }]
+        );
+        assert_ne!(feedback, AnswerFeedback::for_answer(&current, &[Uuid::from_u128(1)]));
+
+        // Choices without feedback, or not in the revision, are left out
+        let feedback = AnswerFeedback::for_answer(&answered, &[Uuid::from_u128(2), Uuid::from_u128(9)]);
+        assert!(feedback.choices.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_render_html_sanitizes_content() {
+        // Plain text is escaped, never interpreted
+        assert_eq!(
+            render_html(ContentFormat::Plain, "<b>2 < 3</b>\n*not bold*"),
+            "&lt;b&gt;2 &lt; 3&lt;/b&gt;<br>\n*not bold*"
+        );
+
+        let html = render_html(ContentFormat::Markdown, "**Bold** and `code`");
+        assert_eq!(html.trim(), "<p><strong>Bold</strong> and <code>code</code></p>");
+
+        // Scripts, event handlers and javascript: links are removed
+        let html = render_html(
+            ContentFormat::Markdown,
+            "<script>alert(1)</script>\n\n<img src=\"x.png\" onerror=\"alert(1)\">\n\n[click](javascript:alert(1))",
+        );
+        assert!(!html.contains("<script"));
+        assert!(!html.contains("onerror"));
+        assert!(!html.contains("javascript:"));
+        assert!(html.contains("<img src=\"x.png\">"));
+
+        // Math is only recognized in markdown_math, and left for the client to typeset
+        let source = "Solve $x^2 = 4$:\n\n$$\\frac{a}{b}$$";
+        assert!(!render_html(ContentFormat::Markdown, source).contains("class=\"math"));
+        let html = render_html(ContentFormat::MarkdownMath, source);
+        assert!(html.contains("<span class=\"math math-inline\">x^2 = 4</span>"));
+        assert!(html.contains("<span class=\"math math-display\">\\frac{a}{b}</span>"));
+
+        // Math cannot smuggle markup either
+        let html = render_html(ContentFormat::MarkdownMath, "$<script>alert(1)</script>$");
+        assert!(!html.contains("<script"));
+    }
+
+    #[tokio::test]
+    async fn test_question_response_renders_choices_in_question_format() {
+        let mut question = revision_of("Canberra");
+        question.content_format = ContentFormat::Markdown;
+        question.question_text = "Capital of *Australia*?".to_string();
+        question.choices[1].choice_text = "**Canberra**".to_string();
+        question.render_html();
+
+        assert_eq!(question.question_html.trim(), "<p>Capital of <em>Australia</em>?</p>");
+        assert_eq!(question.choices[1].choice_html.trim(), "<p><strong>Canberra</strong></p>");
+
+        // Revisions recorded before content formats existed are plain text
+        let mut snapshot = serde_json::to_value(revision_of("Canberra")).unwrap();
+        let fields = snapshot.as_object_mut().unwrap();
+        fields.remove("content_format");
+        fields.remove("question_html");
+        let question: QuestionResponse = serde_json::from_value(snapshot).unwrap();
+        assert_eq!(question.content_format, ContentFormat::Plain);
+    }
 }
@@ -1,4 +1,5 @@
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 mod service_tests {
     use crate::models::class::{CreateClassRequest, UpdateClassRequest};
     use crate::models::exams::{CreateExamRequest, UpdateExamRequest};
@@ -61,6 +62,15 @@ mod service_tests {
             end_time,
             category: "Computer Science".to_string(),
             difficulty: "Hard".to_string(),
+            is_template: None,
+            pass_mark: None,
+            pass_mark_type: None,
+            grade_scale_id: None,
+            shuffle_questions: None,
+            shuffle_choices: None,
+            wrong_answer_penalty: None,
+            score_floor: None,
+            review_policy: None,
         };
         assert!(valid_create_request.validate().is_ok());
 
@@ -73,6 +83,17 @@ mod service_tests {
             is_active: true,
             category: "Computer Science".to_string(),
             difficulty: "Hard".to_string(),
+            is_template: None,
+            pass_mark: None,
+            pass_mark_type: None,
+            grade_scale_id: None,
+            clear_pass_mark: false,
+            clear_grade_scale: false,
+            shuffle_questions: None,
+            shuffle_choices: None,
+            wrong_answer_penalty: None,
+            score_floor: None,
+            review_policy: None,
         };
         assert!(valid_update_request.validate().is_ok());
     }
@@ -131,7 +152,7 @@ mod service_tests {
         let assignment_id = Uuid::new_v4();
 
         // All should be unique
-        let ids = vec![class_id, exam_id, user_id, assignment_id];
+        let ids = [class_id, exam_id, user_id, assignment_id];
         for (i, id1) in ids.iter().enumerate() {
             for (j, id2) in ids.iter().enumerate() {
                 if i != j {
@@ -163,6 +184,15 @@ mod service_tests {
                 end_time,
                 category: "General".to_string(),
                 difficulty: "Medium".to_string(),
+                is_template: None,
+                pass_mark: None,
+                pass_mark_type: None,
+                grade_scale_id: None,
+                shuffle_questions: None,
+                shuffle_choices: None,
+                wrong_answer_penalty: None,
+                score_floor: None,
+                review_policy: None,
             };
             assert!(exam_request.validate().is_ok());
         }
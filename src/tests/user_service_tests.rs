@@ -1,4 +1,5 @@
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 mod user_service_tests {
     use crate::database::Database;
     use crate::services::user::UserService;
@@ -0,0 +1,111 @@
+use axum::http::HeaderMap;
+use std::net::{IpAddr, SocketAddr};
+
+/// Parse a CIDR range such as `10.0.0.0/24` or `2001:db8::/32`. A bare
+/// address is treated as a single-host range.
+pub fn parse_cidr(cidr: &str) -> Result<(IpAddr, u8), String> {
+    let (addr, prefix) = match cidr.trim().split_once('/') {
+        Some((addr, prefix)) => (addr, Some(prefix)),
+        None => (cidr.trim(), None),
+    };
+
+    let addr: IpAddr = addr
+        .parse()
+        .map_err(|_| format!("Invalid IP range: {}", cidr))?;
+    let max_prefix = if addr.is_ipv4() { 32 } else { 128 };
+    let prefix = match prefix {
+        Some(prefix) => prefix
+            .parse::<u8>()
+            .ok()
+            .filter(|p| *p <= max_prefix)
+            .ok_or_else(|| format!("Invalid IP range: {}", cidr))?,
+        None => max_prefix,
+    };
+
+    Ok((addr, prefix))
+}
+
+/// Whether `ip` falls inside the CIDR range. Invalid ranges match nothing.
+pub fn ip_in_cidr(ip: IpAddr, cidr: &str) -> bool {
+    let Ok((network, prefix)) = parse_cidr(cidr) else {
+        return false;
+    };
+
+    // IPv4 clients may show up as IPv4-mapped IPv6 addresses
+    let ip = match ip {
+        IpAddr::V6(v6) => v6.to_ipv4_mapped().map_or(ip, IpAddr::V4),
+        v4 => v4,
+    };
+
+    match (ip, network) {
+        (IpAddr::V4(ip), IpAddr::V4(network)) => {
+            let mask = u32::MAX.checked_shl(32 - prefix as u32).unwrap_or(0);
+            u32::from(ip) & mask == u32::from(network) & mask
+        }
+        (IpAddr::V6(ip), IpAddr::V6(network)) => {
+            let mask = u128::MAX.checked_shl(128 - prefix as u32).unwrap_or(0);
+            u128::from(ip) & mask == u128::from(network) & mask
+        }
+        _ => false,
+    }
+}
+
+/// Address of the client. Behind a reverse proxy the last `X-Forwarded-For`
+/// entry is used when `trust_proxy_headers` is enabled: that is the one the
+/// proxy appended, while earlier entries come from the client.
+pub fn client_ip(headers: &HeaderMap, peer: SocketAddr, trust_proxy_headers: bool) -> IpAddr {
+    if trust_proxy_headers {
+        let forwarded = headers
+            .get_all("x-forwarded-for")
+            .iter()
+            .next_back()
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.rsplit(',').next())
+            .and_then(|value| value.trim().parse().ok());
+
+        if let Some(ip) = forwarded {
+            return ip;
+        }
+    }
+
+    peer.ip()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ip_in_cidr() {
+        let ip: IpAddr = "192.168.1.42".parse().unwrap();
+        assert!(ip_in_cidr(ip, "192.168.1.0/24"));
+        assert!(!ip_in_cidr(ip, "192.168.2.0/24"));
+        assert!(ip_in_cidr(ip, "192.168.1.42"));
+        assert!(ip_in_cidr(ip, "0.0.0.0/0"));
+
+        let mapped: IpAddr = "::ffff:192.168.1.42".parse().unwrap();
+        assert!(ip_in_cidr(mapped, "192.168.1.0/24"));
+
+        let v6: IpAddr = "2001:db8::1".parse().unwrap();
+        assert!(ip_in_cidr(v6, "2001:db8::/32"));
+        assert!(!ip_in_cidr(v6, "192.168.1.0/24"));
+    }
+
+    #[test]
+    fn test_parse_cidr_rejects_invalid_ranges() {
+        assert!(parse_cidr("10.0.0.0/8").is_ok());
+        assert!(parse_cidr("10.0.0.0/33").is_err());
+        assert!(parse_cidr("not-an-ip/24").is_err());
+    }
+
+    #[test]
+    fn test_client_ip_from_forwarded_header() {
+        let peer: SocketAddr = "10.0.0.1:5000".parse().unwrap();
+        let mut headers = HeaderMap::new();
+        // The left-most entry is whatever the client sent and must be ignored
+        headers.insert("x-forwarded-for", "192.168.1.10, 203.0.113.7".parse().unwrap());
+
+        assert_eq!(client_ip(&headers, peer, true), "203.0.113.7".parse::<IpAddr>().unwrap());
+        assert_eq!(client_ip(&headers, peer, false), peer.ip());
+    }
+}
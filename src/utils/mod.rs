@@ -1,3 +1,6 @@
 pub mod jwt;
 pub mod password;
-pub mod pagination;
\ No newline at end of file
+pub mod pagination;
+pub mod ip;
+pub mod shuffle;
+pub mod text;
\ No newline at end of file
@@ -0,0 +1,71 @@
+use uuid::Uuid;
+
+/// SplitMix64 step. Used instead of `rand` generators because the order has
+/// to stay the same for a stored seed across library upgrades.
+fn next_u64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Seed for shuffling something that belongs to one attempt, e.g. the
+/// choices of one of its questions
+pub fn derive_seed(seed: i64, id: Uuid) -> u64 {
+    let (high, low) = id.as_u64_pair();
+    let mut state = seed as u64 ^ high;
+    next_u64(&mut state) ^ low
+}
+
+/// Fisher-Yates shuffle driven by `seed`; the same seed gives the same order
+pub fn seeded_shuffle<T>(items: &mut [T], seed: u64) {
+    let mut state = seed;
+    for i in (1..items.len()).rev() {
+        let j = (next_u64(&mut state) % (i as u64 + 1)) as usize;
+        items.swap(i, j);
+    }
+}
+
+/// Shuffle the items for which `is_pinned` is false and put the pinned
+/// items after them, in their original order
+pub fn seeded_shuffle_unpinned<T>(items: Vec<T>, seed: u64, is_pinned: impl Fn(&T) -> bool) -> Vec<T> {
+    let (pinned, mut free): (Vec<_>, Vec<_>) = items.into_iter().partition(|item| is_pinned(item));
+    seeded_shuffle(&mut free, seed);
+    free.extend(pinned);
+    free
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seeded_shuffle_is_deterministic() {
+        let mut first: Vec<u32> = (0..20).collect();
+        let mut second = first.clone();
+        seeded_shuffle(&mut first, 42);
+        seeded_shuffle(&mut second, 42);
+        assert_eq!(first, second);
+        assert_ne!(first, (0..20).collect::<Vec<u32>>());
+
+        let mut other: Vec<u32> = (0..20).collect();
+        seeded_shuffle(&mut other, 43);
+        assert_ne!(first, other);
+    }
+
+    #[test]
+    fn test_pinned_items_go_last() {
+        // Choices are stored in creation order, so pinned ones can be anywhere
+        let items = vec!["All of the above", "a", "b", "None of the above", "c", "d", "e"];
+        for seed in 0..10 {
+            let shuffled = seeded_shuffle_unpinned(items.clone(), seed, |item| item.ends_with("the above"));
+            assert_eq!(shuffled[5..], ["All of the above", "None of the above"]);
+            let mut sorted = shuffled.clone();
+            sorted.sort();
+            let mut expected = items.clone();
+            expected.sort();
+            assert_eq!(sorted, expected);
+        }
+    }
+}
@@ -0,0 +1,77 @@
+use unicode_normalization::{UnicodeNormalization, char::is_combining_mark};
+
+/// Options for comparing free-text answers
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TextNormalization {
+    pub case_sensitive: bool,
+    /// Drop all whitespace instead of collapsing runs of it to one space
+    pub ignore_whitespace: bool,
+    pub ignore_diacritics: bool,
+}
+
+/// Bring `text` into the form two answers are compared in
+pub fn normalize_text(text: &str, options: TextNormalization) -> String {
+    let mut text = if options.ignore_whitespace {
+        text.split_whitespace().collect::<String>()
+    } else {
+        text.split_whitespace().collect::<Vec<_>>().join(" ")
+    };
+    if !options.case_sensitive {
+        text = text.to_lowercase();
+    }
+    if options.ignore_diacritics {
+        text = text.nfd().filter(|c| !is_combining_mark(*c)).nfc().collect();
+    }
+    text
+}
+
+/// Number of single-character insertions, deletions and substitutions that
+/// turn `a` into `b`
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+
+    for (i, ca) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_text() {
+        let default = TextNormalization::default();
+        assert_eq!(normalize_text("  New   York ", default), "new york");
+        assert_eq!(
+            normalize_text("New York", TextNormalization { ignore_whitespace: true, ..default }),
+            "newyork"
+        );
+        assert_eq!(
+            normalize_text("Café Crème", TextNormalization { ignore_diacritics: true, ..default }),
+            "cafe creme"
+        );
+        assert_eq!(
+            normalize_text("Paris", TextNormalization { case_sensitive: true, ..default }),
+            "Paris"
+        );
+    }
+
+    #[test]
+    fn test_levenshtein() {
+        assert_eq!(levenshtein("", ""), 0);
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+        assert_eq!(levenshtein("photosynthesis", "photosinthesis"), 1);
+        assert_eq!(levenshtein("abc", ""), 3);
+        assert_eq!(levenshtein("naïve", "naive"), 1);
+    }
+}
@@ -1,5 +1,5 @@
 CREATE TYPE user_role AS ENUM ('admin', 'teacher', 'student');
-CREATE TYPE question_type AS ENUM ('multiple_choice', 'essay', 'true_false');
+CREATE TYPE question_type AS ENUM ('multiple_choice', 'essay', 'true_false', 'multiple_select', 'short_answer', 'numeric', 'matching', 'ordering', 'cloze');
 CREATE TABLE IF NOT EXISTS users (
     id          UUID PRIMARY KEY DEFAULT gen_random_uuid(),
     name        TEXT        NOT NULL,
@@ -17,7 +17,9 @@ CREATE TABLE IF NOT EXISTS classes (
     id         UUID PRIMARY KEY DEFAULT gen_random_uuid(),
     name       TEXT NOT NULL,
     created_by UUID REFERENCES users (id),
-    created_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP
+    created_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP,
+    deleted_at TIMESTAMPTZ,
+    deleted_by UUID REFERENCES users (id)
     );
 
 CREATE TABLE IF NOT EXISTS class_members (
@@ -27,6 +29,21 @@ CREATE TABLE IF NOT EXISTS class_members (
     PRIMARY KEY (user_id, class_id)
     );
 
+CREATE TABLE IF NOT EXISTS grade_scales (
+    id         UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name       TEXT        NOT NULL,
+    created_by UUID REFERENCES users (id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+    );
+
+CREATE TABLE IF NOT EXISTS grade_scale_bands (
+    id          UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    scale_id    UUID             NOT NULL REFERENCES grade_scales (id) ON DELETE CASCADE,
+    label       TEXT             NOT NULL,
+    min_percent DOUBLE PRECISION NOT NULL CHECK (min_percent >= 0 AND min_percent <= 100),
+    UNIQUE (scale_id, min_percent)
+    );
+
 CREATE TABLE IF NOT EXISTS exams (
     id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
     title            TEXT      NOT NULL,
@@ -37,23 +54,89 @@ CREATE TABLE IF NOT EXISTS exams (
     end_time         TIMESTAMPTZ NOT NULL,
     is_active        BOOLEAN     DEFAULT TRUE,
     category         TEXT        NOT NULL,
-    difficulty     TEXT        NOT NULL
+    difficulty     TEXT        NOT NULL,
+    is_template      BOOLEAN     NOT NULL DEFAULT FALSE,
+    access_code      TEXT,
+    ip_allowlist     TEXT[]      NOT NULL DEFAULT '{}',
+    pass_mark        INTEGER,
+    pass_mark_type   TEXT        NOT NULL DEFAULT 'percent',
+    grade_scale_id   UUID REFERENCES grade_scales (id) ON DELETE SET NULL,
+    shuffle_questions BOOLEAN    NOT NULL DEFAULT FALSE,
+    shuffle_choices  BOOLEAN     NOT NULL DEFAULT FALSE,
+    -- Negative marking: share of a question's score deducted for a wrong answer
+    wrong_answer_penalty DOUBLE PRECISION NOT NULL DEFAULT 0
+        CHECK (wrong_answer_penalty >= 0 AND wrong_answer_penalty <= 1),
+    score_floor      TEXT        NOT NULL DEFAULT 'exam',
+    -- When students see explanations and choice feedback
+    review_policy    TEXT        NOT NULL DEFAULT 'after_submission',
+    deleted_at       TIMESTAMPTZ,
+    deleted_by       UUID REFERENCES users (id)
     );
 
 CREATE TABLE IF NOT EXISTS exam_assignments (
     exam_id  UUID REFERENCES exams (id),
     class_id UUID REFERENCES classes (id),
+    access_code TEXT,
     created_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP,
     PRIMARY KEY (exam_id, class_id)
     );
 
+CREATE TABLE IF NOT EXISTS exam_sections (
+    id                 UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    exam_id            UUID        NOT NULL REFERENCES exams (id) ON DELETE CASCADE,
+    title              TEXT        NOT NULL,
+    instructions       TEXT,
+    position           INTEGER     NOT NULL,
+    time_limit_minutes INTEGER CHECK (time_limit_minutes > 0),
+    draw_count         INTEGER CHECK (draw_count > 0),
+    created_at         TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+    );
+
+-- Uploaded media; the content is in the blob store under storage_key
+CREATE TABLE IF NOT EXISTS attachments (
+    id           UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    storage_key  TEXT        NOT NULL UNIQUE,
+    file_name    TEXT        NOT NULL,
+    content_type TEXT        NOT NULL,
+    size_bytes   BIGINT      NOT NULL,
+    uploaded_by  UUID        NOT NULL REFERENCES users (id),
+    created_at   TIMESTAMPTZ NOT NULL DEFAULT NOW()
+    );
+
 CREATE TABLE IF NOT EXISTS questions (
-    id             UUID PRIMARY KEY DEFAULT gen_random_uuid(),
-    exam_id        UUID REFERENCES exams (id) ON DELETE CASCADE,
-    question_text  TEXT          NOT NULL,
-    question_type  question_type NOT NULL,
-    correct_answer TEXT,
-    score          INTEGER       DEFAULT 1
+    id               UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    exam_id          UUID REFERENCES exams (id) ON DELETE CASCADE,
+    section_id       UUID REFERENCES exam_sections (id) ON DELETE SET NULL,
+    question_text    TEXT          NOT NULL,
+    question_type    question_type NOT NULL,
+    -- How question and choice texts are written: plain, markdown or markdown_math
+    content_format   TEXT          NOT NULL DEFAULT 'plain',
+    correct_answer   TEXT,
+    score            INTEGER       DEFAULT 1,
+    scoring_policy   TEXT          NOT NULL DEFAULT 'all_or_nothing',
+    -- Accepted answers of short_answer questions with their matching options
+    accepted_answers JSONB         NOT NULL DEFAULT '[]',
+    -- Expected value, tolerance and units of numeric questions
+    numeric_answer   JSONB,
+    -- Left/right pairs of matching questions
+    matching_pairs   JSONB         NOT NULL DEFAULT '[]',
+    -- Items of ordering questions in the correct order
+    ordering_items   TEXT[]        NOT NULL DEFAULT '{}',
+    -- Gaps of cloze questions with their answer specs and scores
+    cloze_gaps       JSONB         NOT NULL DEFAULT '[]',
+    -- Released to students according to the exam's review policy
+    explanation      TEXT,
+    -- Media shown with the question, see attachments
+    attachment_ids   UUID[]        NOT NULL DEFAULT '{}',
+    -- Bumped by every edit, see question_revisions
+    revision         INTEGER       NOT NULL DEFAULT 1,
+    pool             TEXT,
+    difficulty       TEXT,
+    owner_id         UUID REFERENCES users (id),
+    tags             TEXT[]        NOT NULL DEFAULT '{}',
+    topic            TEXT,
+    deleted_at       TIMESTAMPTZ,
+    deleted_by       UUID REFERENCES users (id)
     );
 
 CREATE TABLE IF NOT EXISTS choices (
@@ -61,6 +144,9 @@ CREATE TABLE IF NOT EXISTS choices (
     question_id UUID REFERENCES questions (id) ON DELETE CASCADE,
     choice_text TEXT NOT NULL,
     is_correct  BOOLEAN DEFAULT FALSE,
+    pin_to_bottom BOOLEAN NOT NULL DEFAULT FALSE,
+    feedback    TEXT,
+    attachment_ids UUID[] NOT NULL DEFAULT '{}',
     created_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP
     );
 
@@ -70,16 +156,42 @@ CREATE TABLE IF NOT EXISTS exam_attempts (
     exam_id      UUID REFERENCES exams (id),
     started_at   TIMESTAMPTZ,
     submitted_at TIMESTAMPTZ,
-    score_total  INTEGER
+    score_total  INTEGER,
+    client_ip    TEXT,
+    submitted_ip TEXT,
+    access_code  TEXT,
+    max_score    INTEGER,
+    shuffle_seed BIGINT       NOT NULL DEFAULT 0,
+    passed       BOOLEAN,
+    grade        TEXT,
+    -- Submitted by the background job after the deadline passed
+    auto_submitted BOOLEAN    NOT NULL DEFAULT false
+    );
+
+CREATE TABLE IF NOT EXISTS attempt_sections (
+    attempt_id  UUID        NOT NULL REFERENCES exam_attempts (id) ON DELETE CASCADE,
+    section_id  UUID        NOT NULL REFERENCES exam_sections (id) ON DELETE CASCADE,
+    started_at  TIMESTAMPTZ NOT NULL,
+    finished_at TIMESTAMPTZ,
+    PRIMARY KEY (attempt_id, section_id)
     );
 
 CREATE TABLE IF NOT EXISTS answers (
-    id            UUID PRIMARY KEY DEFAULT gen_random_uuid(),
-    attempt_id    UUID REFERENCES exam_attempts (id) ON DELETE CASCADE,
-    question_id   UUID REFERENCES questions (id),
-    answer_text   TEXT,
-    is_correct    BOOLEAN,
-    score_awarded INTEGER
+    id               UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    attempt_id       UUID REFERENCES exam_attempts (id) ON DELETE CASCADE,
+    question_id      UUID REFERENCES questions (id),
+    answer_text      TEXT,
+    choice_ids       UUID[],
+    is_correct       BOOLEAN,
+    score_awarded    INTEGER,
+    -- Points deducted by negative marking
+    penalty          DOUBLE PRECISION NOT NULL DEFAULT 0,
+    -- Why an automatically graded answer was marked wrong
+    incorrect_reason TEXT,
+    -- Answer to matching, ordering and cloze questions
+    response         JSONB,
+    -- Result of each gap of a cloze answer
+    gap_results      JSONB
     );
 
 
@@ -161,7 +273,147 @@ ON CONFLICT (user_id) DO NOTHING;
 CREATE INDEX idx_class_members_user_id ON class_members(user_id);
 CREATE INDEX idx_class_members_class_id ON class_members(class_id);
 CREATE INDEX idx_questions_exam_id ON questions(exam_id);
+CREATE INDEX idx_questions_section_id ON questions(section_id);
+CREATE INDEX idx_exam_sections_exam_id ON exam_sections(exam_id, position);
 CREATE INDEX idx_choices_question_id ON choices(question_id);
 CREATE INDEX idx_exam_assignments_exam_id ON exam_assignments(exam_id);
 CREATE INDEX idx_exam_assignments_class_id ON exam_assignments(class_id);
 CREATE INDEX idx_exam_attempts_user_id ON exam_attempts(user_id);
+CREATE INDEX idx_exam_attempts_open ON exam_attempts(started_at) WHERE submitted_at IS NULL;
+CREATE INDEX idx_exams_category_difficulty ON exams(category, difficulty);
+CREATE INDEX idx_exams_start_time ON exams(start_time);
+CREATE INDEX idx_exams_title_search ON exams USING GIN (to_tsvector('simple', title));
+CREATE INDEX idx_exams_deleted_at ON exams(deleted_at) WHERE deleted_at IS NOT NULL;
+CREATE INDEX idx_questions_deleted_at ON questions(deleted_at) WHERE deleted_at IS NOT NULL;
+CREATE INDEX idx_classes_deleted_at ON classes(deleted_at) WHERE deleted_at IS NOT NULL;
+
+
+-- Per-student accommodations (extra time). exam_id NULL means the record applies to every exam.
+CREATE TABLE IF NOT EXISTS exam_accommodations (
+    id                UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id           UUID             NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    exam_id           UUID REFERENCES exams (id) ON DELETE CASCADE,
+    time_multiplier   DOUBLE PRECISION NOT NULL DEFAULT 1.0 CHECK (time_multiplier >= 1.0),
+    extra_minutes     INTEGER          NOT NULL DEFAULT 0 CHECK (extra_minutes >= 0),
+    extended_end_time TIMESTAMPTZ,
+    notes             TEXT,
+    created_by        UUID REFERENCES users (id),
+    created_at        TIMESTAMPTZ      NOT NULL DEFAULT CURRENT_TIMESTAMP
+    );
+
+CREATE UNIQUE INDEX idx_exam_accommodations_user_exam ON exam_accommodations(user_id, exam_id) WHERE exam_id IS NOT NULL;
+CREATE UNIQUE INDEX idx_exam_accommodations_user_global ON exam_accommodations(user_id) WHERE exam_id IS NULL;
+
+-- Conditions that unlock an exam: a minimum score on another exam or an achievement
+CREATE TABLE IF NOT EXISTS exam_prerequisites (
+    id                   UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    exam_id              UUID        NOT NULL REFERENCES exams (id) ON DELETE CASCADE,
+    required_exam_id     UUID REFERENCES exams (id) ON DELETE CASCADE,
+    min_score_percent    INTEGER CHECK (min_score_percent BETWEEN 0 AND 100),
+    required_achievement TEXT,
+    created_at           TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CHECK ((required_exam_id IS NULL) <> (required_achievement IS NULL))
+    );
+
+CREATE INDEX idx_exam_prerequisites_exam_id ON exam_prerequisites(exam_id);
+
+CREATE TABLE IF NOT EXISTS exam_draw_rules (
+    id         UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    exam_id    UUID        NOT NULL REFERENCES exams (id) ON DELETE CASCADE,
+    section_id UUID REFERENCES exam_sections (id) ON DELETE CASCADE,
+    pool       TEXT,
+    difficulty TEXT,
+    draw_count INTEGER     NOT NULL CHECK (draw_count > 0),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+    );
+
+CREATE INDEX idx_exam_draw_rules_exam_id ON exam_draw_rules(exam_id);
+
+-- Questions drawn for an attempt, frozen when the attempt starts
+CREATE TABLE IF NOT EXISTS attempt_questions (
+    attempt_id  UUID    NOT NULL REFERENCES exam_attempts (id) ON DELETE CASCADE,
+    question_id UUID    NOT NULL REFERENCES questions (id) ON DELETE CASCADE,
+    position    INTEGER NOT NULL,
+    -- Revision of the question the attempt saw and is graded against
+    revision    INTEGER NOT NULL DEFAULT 1,
+    -- Score the question is worth in this attempt, fixed when it starts
+    score       INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (attempt_id, question_id)
+    );
+
+CREATE INDEX idx_attempt_questions_question_id ON attempt_questions(question_id);
+
+-- Answers saved while an attempt is in progress; graded and removed on submit
+CREATE TABLE IF NOT EXISTS answer_drafts (
+    attempt_id  UUID        NOT NULL REFERENCES exam_attempts (id) ON DELETE CASCADE,
+    question_id UUID        NOT NULL REFERENCES questions (id) ON DELETE CASCADE,
+    answer_text TEXT        NOT NULL DEFAULT '',
+    choice_ids  UUID[]      NOT NULL DEFAULT '{}',
+    response    JSONB,
+    -- Bumped by every save; a save names the version it replaces
+    version     INTEGER     NOT NULL DEFAULT 1,
+    saved_at    TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (attempt_id, question_id)
+    );
+
+-- Immutable snapshots of questions, including their choices. The revision a
+-- question had before an edit is recorded along with the edited one, so a
+-- question that was never edited has no rows here.
+CREATE TABLE IF NOT EXISTS question_revisions (
+    question_id UUID        NOT NULL REFERENCES questions (id) ON DELETE CASCADE,
+    revision    INTEGER     NOT NULL,
+    snapshot    JSONB       NOT NULL,
+    -- Who made the edit and when; unknown for the revision a question was created with
+    created_by  UUID REFERENCES users (id),
+    created_at  TIMESTAMPTZ,
+    PRIMARY KEY (question_id, revision)
+    );
+
+
+-- Bank questions (exam_id IS NULL) linked into exams by reference
+CREATE TABLE IF NOT EXISTS exam_question_links (
+    exam_id        UUID        NOT NULL REFERENCES exams (id) ON DELETE CASCADE,
+    question_id    UUID        NOT NULL REFERENCES questions (id) ON DELETE CASCADE,
+    section_id     UUID REFERENCES exam_sections (id) ON DELETE SET NULL,
+    score_override INTEGER CHECK (score_override > 0),
+    created_at     TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (exam_id, question_id)
+    );
+
+CREATE INDEX idx_exam_question_links_question_id ON exam_question_links(question_id);
+CREATE INDEX idx_questions_owner_id ON questions(owner_id) WHERE exam_id IS NULL;
+CREATE INDEX idx_questions_tags ON questions USING GIN (tags);
+
+-- Every question of every exam: its own questions plus the bank questions
+-- linked into it, with the link's section and score override applied
+CREATE OR REPLACE VIEW exam_questions AS
+SELECT id, exam_id, section_id, question_text, question_type, content_format, correct_answer, score, scoring_policy,
+       accepted_answers, numeric_answer, matching_pairs, ordering_items, cloze_gaps, explanation, attachment_ids,
+       revision, pool, difficulty, owner_id, tags, topic, false AS from_bank, deleted_at
+FROM questions
+WHERE exam_id IS NOT NULL
+UNION ALL
+SELECT q.id, l.exam_id, l.section_id, q.question_text, q.question_type, q.content_format, q.correct_answer,
+       COALESCE(l.score_override, q.score) AS score, q.scoring_policy, q.accepted_answers, q.numeric_answer,
+       q.matching_pairs, q.ordering_items, q.cloze_gaps, q.explanation, q.attachment_ids, q.revision, q.pool, q.difficulty, q.owner_id, q.tags, q.topic,
+       true AS from_bank, q.deleted_at
+FROM exam_question_links l
+JOIN questions q ON q.id = l.question_id;
+
+-- Multiple choice and true/false questions whose choices and correct_answer
+-- disagree. Answers are graded by choice, so these need fixing by hand.
+CREATE OR REPLACE VIEW inconsistent_choice_questions AS
+SELECT q.id AS question_id, q.exam_id, q.question_text, q.question_type, q.correct_answer,
+       COUNT(*) FILTER (WHERE c.is_correct) AS correct_choices,
+       CASE
+           WHEN COUNT(*) FILTER (WHERE c.is_correct) = 0 THEN 'no_correct_choice'
+           WHEN COUNT(*) FILTER (WHERE c.is_correct) > 1 THEN 'multiple_correct_choices'
+           ELSE 'correct_answer_mismatch'
+       END AS issue
+FROM questions q
+JOIN choices c ON c.question_id = q.id
+WHERE q.question_type IN ('multiple_choice', 'true_false') AND q.deleted_at IS NULL
+GROUP BY q.id
+HAVING COUNT(*) FILTER (WHERE c.is_correct) <> 1
+    OR (q.correct_answer IS NOT NULL
+        AND NOT bool_or(c.is_correct AND LOWER(TRIM(c.choice_text)) = LOWER(TRIM(q.correct_answer))));